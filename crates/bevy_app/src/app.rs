@@ -1,6 +1,8 @@
 use crate::{First, Main, MainSchedulePlugin, Plugin, Plugins, StateTransition};
 pub use bevy_derive::AppLabel;
 use bevy_ecs::{
+    component::{ComponentDescriptor, ComponentId},
+    event::DynamicEvents,
     prelude::*,
     schedule::{
         apply_state_transition, common_conditions::run_once as run_once_condition,
@@ -511,6 +513,26 @@ impl App {
         self
     }
 
+    /// Registers an event type which is only known at runtime, and returns the [`ComponentId`]
+    /// created for it.
+    ///
+    /// This is done with [`World::init_dynamic_event`], and by inserting a
+    /// [`dynamic_event_update_system`] into [`First`] the first time it is called. See
+    /// [`DynamicEvents`] for how to send and read the events.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the descriptor is not `Send + Sync`.
+    ///
+    /// [`dynamic_event_update_system`]: bevy_ecs::event::dynamic_event_update_system
+    pub fn add_dynamic_event(&mut self, descriptor: ComponentDescriptor) -> ComponentId {
+        if !self.world.contains_resource::<DynamicEvents>() {
+            self.init_resource::<DynamicEvents>()
+                .add_systems(First, bevy_ecs::event::dynamic_event_update_system);
+        }
+        self.world.init_dynamic_event(descriptor)
+    }
+
     /// Inserts a [`Resource`] to the current [`App`] and overwrites any [`Resource`] previously added of the same type.
     ///
     /// A [`Resource`] in Bevy represents globally unique data. [`Resource`]s must be added to Bevy apps
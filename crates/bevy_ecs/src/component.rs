@@ -3,13 +3,17 @@
 use crate::{
     self as bevy_ecs,
     change_detection::MAX_CHANGE_AGE,
+    entity::Entity,
     storage::{SparseSetIndex, Storages},
     system::{Local, Resource, SystemParam},
-    world::{FromWorld, World},
+    world::{DeferredWorld, FromWorld, World},
     TypeIdMap,
 };
 pub use bevy_ecs_macros::Component;
-use bevy_ptr::{OwningPtr, UnsafeCellDeref};
+use bevy_ptr::{OwningPtr, Ptr, UnsafeCellDeref};
+use bevy_utils::HashMap;
+use fixedbitset::FixedBitSet;
+use serde::{Deserialize, Serialize};
 use std::cell::UnsafeCell;
 use std::{
     alloc::Layout,
@@ -17,6 +21,7 @@ use std::{
     borrow::Cow,
     marker::PhantomData,
     mem::needs_drop,
+    ptr::NonNull,
 };
 
 /// A data type that can be used to store data for an [entity].
@@ -191,7 +196,7 @@ mod sealed {
 /// #[component(storage = "SparseSet")]
 /// struct A;
 /// ```
-#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
 pub enum StorageType {
     /// Provides fast and cache-friendly iteration, but slower addition and removal of components.
     /// This is the default storage type.
@@ -259,6 +264,19 @@ impl ComponentInfo {
         self.descriptor.is_send_and_sync
     }
 
+    /// Returns the function used to clone values of this component, if one was given with
+    /// [`ComponentDescriptor::with_clone`].
+    #[inline]
+    pub fn clone_fn(&self) -> Option<ComponentCloneFn> {
+        self.descriptor.clone
+    }
+
+    /// Returns the [`ComponentHooks`] of the current component.
+    #[inline]
+    pub fn hooks(&self) -> &ComponentHooks {
+        &self.descriptor.hooks
+    }
+
     /// Create a new [`ComponentInfo`].
     pub(crate) fn new(id: ComponentId, descriptor: ComponentDescriptor) -> Self {
         ComponentInfo { id, descriptor }
@@ -286,7 +304,7 @@ impl ComponentInfo {
 /// Given a type `T` which implements [`Component`], the `ComponentId` for `T` can be retrieved
 /// from a `World` using [`World::component_id()`] or via [`Components::component_id()`]. Access
 /// to the `ComponentId` for a [`Resource`] is available via [`Components::resource_id()`].
-#[derive(Debug, Copy, Clone, Hash, Ord, PartialOrd, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Hash, Ord, PartialOrd, Eq, PartialEq, Serialize, Deserialize)]
 pub struct ComponentId(usize);
 
 impl ComponentId {
@@ -334,6 +352,9 @@ pub struct ComponentDescriptor {
     // this descriptor describes.
     // None if the underlying type doesn't need to be dropped
     drop: Option<for<'a> unsafe fn(OwningPtr<'a>)>,
+    // SAFETY: this function must write a valid value of the type this descriptor describes.
+    clone: Option<ComponentCloneFn>,
+    hooks: ComponentHooks,
 }
 
 // We need to ignore the `drop` field in our `Debug` impl
@@ -345,6 +366,8 @@ impl std::fmt::Debug for ComponentDescriptor {
             .field("is_send_and_sync", &self.is_send_and_sync)
             .field("type_id", &self.type_id)
             .field("layout", &self.layout)
+            .field("clone", &self.clone.is_some())
+            .field("hooks", &self.hooks)
             .finish()
     }
 }
@@ -364,11 +387,17 @@ impl ComponentDescriptor {
             type_id: Some(TypeId::of::<T>()),
             layout: Layout::new::<T>(),
             drop: needs_drop::<T>().then_some(Self::drop_ptr::<T> as _),
+            clone: None,
+            hooks: ComponentHooks::default(),
         }
     }
 
     /// Create a new `ComponentDescriptor`.
     ///
+    /// The `drop` fn, if any, is called on each value of the component when it is overwritten,
+    /// removed, or its entity is despawned, as well as for the values left when the world is
+    /// dropped.
+    ///
     /// # Safety
     /// - the `drop` fn must be usable on a pointer with a value of the layout `layout`
     /// - the component type must be safe to access from any thread (Send + Sync in rust terms)
@@ -385,6 +414,8 @@ impl ComponentDescriptor {
             type_id: None,
             layout,
             drop,
+            clone: None,
+            hooks: ComponentHooks::default(),
         }
     }
 
@@ -401,6 +432,8 @@ impl ComponentDescriptor {
             type_id: Some(TypeId::of::<T>()),
             layout: Layout::new::<T>(),
             drop: needs_drop::<T>().then_some(Self::drop_ptr::<T> as _),
+            clone: None,
+            hooks: ComponentHooks::default(),
         }
     }
 
@@ -412,6 +445,8 @@ impl ComponentDescriptor {
             type_id: Some(TypeId::of::<T>()),
             layout: Layout::new::<T>(),
             drop: needs_drop::<T>().then_some(Self::drop_ptr::<T> as _),
+            clone: None,
+            hooks: ComponentHooks::default(),
         }
     }
 
@@ -433,6 +468,120 @@ impl ComponentDescriptor {
     pub fn name(&self) -> &str {
         self.name.as_ref()
     }
+
+    /// Returns this descriptor with the given [`ComponentHooks`].
+    ///
+    /// Hooks are run by the methods which insert and remove components by id, such as
+    /// [`EntityWorldMut::insert_by_id`](crate::world::EntityWorldMut::insert_by_id), and when
+    /// the entity is despawned. The typed methods do not run them, so they are meant for
+    /// components registered with
+    /// [`World::init_component_with_descriptor`](World::init_component_with_descriptor).
+    #[inline]
+    pub fn with_hooks(mut self, hooks: ComponentHooks) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
+    /// Returns the [`ComponentHooks`] of the current component.
+    #[inline]
+    pub fn hooks(&self) -> &ComponentHooks {
+        &self.hooks
+    }
+
+    /// Returns this descriptor with the given [`ComponentCloneFn`], which lets
+    /// [`World::clone_entity`] copy the component without knowing its type.
+    ///
+    /// # Safety
+    /// `clone` must write a valid value of this component, which can be dropped with its `drop`
+    /// fn, when given a pointer to a value of this component.
+    #[inline]
+    pub unsafe fn with_clone(mut self, clone: ComponentCloneFn) -> Self {
+        self.clone = Some(clone);
+        self
+    }
+
+    /// Returns the function used to clone values of this component, if any.
+    #[inline]
+    pub fn clone_fn(&self) -> Option<ComponentCloneFn> {
+        self.clone
+    }
+}
+
+/// A function which clones the value of a component pointed to by its first argument, and writes
+/// the clone to the memory pointed to by the second.
+///
+/// The memory written to has the layout of the component, and does not hold a value yet.
+///
+/// ```
+/// # use bevy_ecs::{component::ComponentCloneFn, ptr::Ptr};
+/// # use std::ptr::NonNull;
+/// unsafe fn clone_string(source: Ptr<'_>, target: NonNull<u8>) {
+///     let value = source.deref::<String>().clone();
+///     target.cast::<String>().as_ptr().write(value);
+/// }
+/// let clone: ComponentCloneFn = clone_string;
+/// ```
+pub type ComponentCloneFn = for<'a> unsafe fn(Ptr<'a>, NonNull<u8>);
+
+/// A function run when a component is added to, inserted into, or removed from an entity.
+///
+/// It gets the entity and the id of the component, with a [`DeferredWorld`] which can modify
+/// the values of components and resources, but not the layout of the world.
+pub type ComponentHook = for<'w> fn(DeferredWorld<'w>, Entity, ComponentId);
+
+/// The [`ComponentHook`]s of a component, which can be used to maintain invariants such as
+/// indexes or reference counts for components which only exist at runtime.
+///
+/// Commands queued by the hooks of an operation are applied once all of them have run. They must
+/// not despawn the entity of an [`EntityWorldMut`](crate::world::EntityWorldMut) which is still
+/// being used.
+#[derive(Clone, Copy, Default)]
+pub struct ComponentHooks {
+    pub(crate) on_add: Option<ComponentHook>,
+    pub(crate) on_insert: Option<ComponentHook>,
+    pub(crate) on_remove: Option<ComponentHook>,
+}
+
+impl ComponentHooks {
+    /// Sets the hook run when the component is added to an entity which did not have it.
+    ///
+    /// Runs after the value has been added, and before [`ComponentHooks::on_insert`].
+    pub fn on_add(&mut self, hook: ComponentHook) -> &mut Self {
+        self.on_add = Some(hook);
+        self
+    }
+
+    /// Sets the hook run each time a value of the component is inserted into an entity, whether
+    /// it replaces another value or not.
+    pub fn on_insert(&mut self, hook: ComponentHook) -> &mut Self {
+        self.on_insert = Some(hook);
+        self
+    }
+
+    /// Sets the hook run when the component is removed from an entity, including when the
+    /// entity is despawned.
+    ///
+    /// Runs before the value is removed, so it can still be read.
+    pub fn on_remove(&mut self, hook: ComponentHook) -> &mut Self {
+        self.on_remove = Some(hook);
+        self
+    }
+
+    /// Returns `true` if no hook is set.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.on_add.is_none() && self.on_insert.is_none() && self.on_remove.is_none()
+    }
+}
+
+impl std::fmt::Debug for ComponentHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ComponentHooks")
+            .field("on_add", &self.on_add.is_some())
+            .field("on_insert", &self.on_insert.is_some())
+            .field("on_remove", &self.on_remove.is_some())
+            .finish()
+    }
 }
 
 /// Stores metadata associated with each kind of [`Component`] in a given [`World`].
@@ -440,7 +589,9 @@ impl ComponentDescriptor {
 pub struct Components {
     components: Vec<ComponentInfo>,
     indices: TypeIdMap<ComponentId>,
+    name_indices: HashMap<Cow<'static, str>, ComponentId>,
     resource_indices: TypeIdMap<ComponentId>,
+    resources: FixedBitSet,
 }
 
 impl Components {
@@ -459,10 +610,16 @@ impl Components {
         let Components {
             indices,
             components,
+            name_indices,
             ..
         } = self;
         *indices.entry(type_id).or_insert_with(|| {
-            Components::init_component_inner(components, storages, ComponentDescriptor::new::<T>())
+            Components::init_component_inner(
+                components,
+                name_indices,
+                storages,
+                ComponentDescriptor::new::<T>(),
+            )
         })
     }
 
@@ -471,7 +628,7 @@ impl Components {
     /// ## Note
     ///
     /// If this method is called multiple times with identical descriptors, a distinct `ComponentId`
-    /// will be created for each one.
+    /// will be created for each one. [`Components::get_id_by_name()`] keeps returning the first one.
     ///
     /// # See also
     ///
@@ -482,12 +639,18 @@ impl Components {
         storages: &mut Storages,
         descriptor: ComponentDescriptor,
     ) -> ComponentId {
-        Components::init_component_inner(&mut self.components, storages, descriptor)
+        Components::init_component_inner(
+            &mut self.components,
+            &mut self.name_indices,
+            storages,
+            descriptor,
+        )
     }
 
     #[inline]
     fn init_component_inner(
         components: &mut Vec<ComponentInfo>,
+        name_indices: &mut HashMap<Cow<'static, str>, ComponentId>,
         storages: &mut Storages,
         descriptor: ComponentDescriptor,
     ) -> ComponentId {
@@ -496,6 +659,9 @@ impl Components {
         if info.descriptor.storage_type == StorageType::SparseSet {
             storages.sparse_sets.get_or_insert(&info);
         }
+        name_indices
+            .entry(info.descriptor.name.clone())
+            .or_insert(component_id);
         components.push(info);
         component_id
     }
@@ -520,6 +686,14 @@ impl Components {
         self.components.get(id.0)
     }
 
+    /// Returns a mutable reference to the [`ComponentHooks`] of the given component.
+    #[inline]
+    pub(crate) fn get_hooks_mut(&mut self, id: ComponentId) -> Option<&mut ComponentHooks> {
+        self.components
+            .get_mut(id.0)
+            .map(|info| &mut info.descriptor.hooks)
+    }
+
     /// Returns the name associated with the given component.
     ///
     /// This will return an incorrect result if `id` did not come from the same world as `self`. It may return `None` or a garbage value.
@@ -544,6 +718,59 @@ impl Components {
         self.indices.get(&type_id).copied()
     }
 
+    /// Returns `true` if the component was registered for a Rust type with
+    /// [`Components::init_component`], rather than from a [`ComponentDescriptor`].
+    ///
+    /// Components which are not typed, including those created from the descriptor of a Rust
+    /// type, can only be accessed by their [`ComponentId`].
+    #[inline]
+    pub fn is_typed(&self, id: ComponentId) -> bool {
+        self.get_info(id)
+            .and_then(ComponentInfo::type_id)
+            .and_then(|type_id| self.get_id(type_id))
+            == Some(id)
+    }
+
+    /// Returns `true` if the id was registered for a resource, including non-send resources,
+    /// resources initialized from a [`ComponentDescriptor`] and dynamic event types.
+    #[inline]
+    pub fn is_resource(&self, id: ComponentId) -> bool {
+        self.resources.contains(id.index())
+    }
+
+    /// Returns the [`ComponentId`] of the component registered with the given name.
+    ///
+    /// Components deriving [`Component`] are registered with their [type name](std::any::type_name),
+    /// while components initialized from a [`ComponentDescriptor`] use the descriptor's name.
+    /// If several components share a name, the first one registered is returned.
+    /// Resources are not included.
+    ///
+    /// ```
+    /// use bevy_ecs::prelude::*;
+    ///
+    /// let mut world = World::new();
+    ///
+    /// #[derive(Component)]
+    /// struct ComponentA;
+    ///
+    /// let component_a_id = world.init_component::<ComponentA>();
+    /// let name = std::any::type_name::<ComponentA>();
+    ///
+    /// assert_eq!(component_a_id, world.components().get_id_by_name(name).unwrap())
+    /// ```
+    #[inline]
+    pub fn get_id_by_name(&self, name: &str) -> Option<ComponentId> {
+        self.name_indices.get(name).copied()
+    }
+
+    /// Gets the metadata of the component registered with the given name.
+    ///
+    /// See [`Components::get_id_by_name()`] for how names are resolved.
+    #[inline]
+    pub fn get_info_by_name(&self, name: &str) -> Option<&ComponentInfo> {
+        self.get_id_by_name(name).and_then(|id| self.get_info(id))
+    }
+
     /// Returns the [`ComponentId`] of the given [`Component`] type `T`.
     ///
     /// The returned `ComponentId` is specific to the `Components` instance
@@ -630,6 +857,22 @@ impl Components {
         }
     }
 
+    /// Initializes a resource described by `descriptor`.
+    ///
+    /// Unlike [`Components::init_component_with_descriptor`], the resource can not be found with
+    /// [`Components::get_id_by_name`]. A distinct `ComponentId` is created on each call.
+    pub fn init_resource_with_descriptor(
+        &mut self,
+        descriptor: ComponentDescriptor,
+    ) -> ComponentId {
+        let component_id = ComponentId(self.components.len());
+        self.components
+            .push(ComponentInfo::new(component_id, descriptor));
+        self.resources.grow(component_id.index() + 1);
+        self.resources.insert(component_id.index());
+        component_id
+    }
+
     /// Initializes a [non-send resource](crate::system::NonSend) of type `T` with this instance.
     /// If a resource of this type has already been initialized, this will return
     /// the ID of the pre-existing resource.
@@ -652,11 +895,18 @@ impl Components {
         type_id: TypeId,
         func: impl FnOnce() -> ComponentDescriptor,
     ) -> ComponentId {
-        let components = &mut self.components;
-        *self.resource_indices.entry(type_id).or_insert_with(|| {
+        let Components {
+            components,
+            resource_indices,
+            resources,
+            ..
+        } = self;
+        *resource_indices.entry(type_id).or_insert_with(|| {
             let descriptor = func();
             let component_id = ComponentId(components.len());
             components.push(ComponentInfo::new(component_id, descriptor));
+            resources.grow(component_id.index() + 1);
+            resources.insert(component_id.index());
             component_id
         })
     }
@@ -871,3 +1121,67 @@ impl<T: Component> FromWorld for InitComponentId<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::alloc::Layout;
+
+    use crate as bevy_ecs;
+    use crate::{
+        component::{ComponentDescriptor, StorageType},
+        prelude::*,
+    };
+
+    #[derive(Component)]
+    struct A;
+
+    #[test]
+    fn get_id_by_name() {
+        let mut world = World::new();
+        let a_id = world.init_component::<A>();
+        // SAFETY: `u64` is `Send + Sync` and has no drop function
+        let descriptor = unsafe {
+            ComponentDescriptor::new_with_layout(
+                "Dynamic",
+                StorageType::Table,
+                Layout::new::<u64>(),
+                None,
+            )
+        };
+        let dynamic_id = world.init_component_with_descriptor(descriptor);
+
+        let components = world.components();
+        assert_eq!(
+            components.get_id_by_name(std::any::type_name::<A>()),
+            Some(a_id)
+        );
+        assert_eq!(components.get_id_by_name("Dynamic"), Some(dynamic_id));
+        assert_eq!(
+            components.get_info_by_name("Dynamic").map(|info| info.id()),
+            Some(dynamic_id)
+        );
+        assert_eq!(components.get_id_by_name("Missing"), None);
+    }
+
+    #[test]
+    fn get_id_by_name_collision() {
+        let mut world = World::new();
+        let descriptor = |storage_type| {
+            // SAFETY: `u64` is `Send + Sync` and has no drop function
+            unsafe {
+                ComponentDescriptor::new_with_layout(
+                    "Shared",
+                    storage_type,
+                    Layout::new::<u64>(),
+                    None,
+                )
+            }
+        };
+        let first = world.init_component_with_descriptor(descriptor(StorageType::Table));
+        let second = world.init_component_with_descriptor(descriptor(StorageType::SparseSet));
+
+        assert_ne!(first, second);
+        assert_eq!(world.components().get_id_by_name("Shared"), Some(first));
+        assert_eq!(world.components().get_name(second), Some("Shared"));
+    }
+}
@@ -1,11 +1,15 @@
 //! Event handling types.
 
 use crate as bevy_ecs;
+use crate::component::ComponentId;
+use crate::storage::BlobVec;
 use crate::system::{Local, Res, ResMut, Resource, SystemParam};
 pub use bevy_ecs_macros::Event;
-use bevy_utils::detailed_trace;
+use bevy_ptr::{OwningPtr, Ptr};
+use bevy_utils::{detailed_trace, HashMap};
 use std::ops::{Deref, DerefMut};
 use std::{
+    alloc::Layout,
     cmp::Ordering,
     fmt,
     hash::{Hash, Hasher},
@@ -812,9 +816,360 @@ impl<E: Event> ExactSizeIterator for SendBatchIds<E> {
     }
 }
 
+/// The events of the event types registered at runtime with
+/// [`World::init_dynamic_event`](crate::world::World::init_dynamic_event), keyed by the
+/// [`ComponentId`] created for each of them.
+///
+/// This is the equivalent of [`Events`] for event types which are only known at runtime, such as
+/// those defined by a scripting language. Events are sent as type-erased values with
+/// [`DynamicEvents::send_by_id`], and read with a [`DynamicEventReader`]. They are double
+/// buffered like [`Events`], so [`DynamicEvents::update`] should be called once per frame, which
+/// [`dynamic_event_update_system`] does.
+///
+/// ```
+/// # use std::alloc::Layout;
+/// # use bevy_ecs::{component::{ComponentDescriptor, StorageType}, event::DynamicEvents, prelude::*, ptr::OwningPtr};
+/// let mut world = World::new();
+/// // SAFETY: `u32` is `Send + Sync` and does not need to be dropped
+/// let damage = world.init_dynamic_event(unsafe {
+///     ComponentDescriptor::new_with_layout("Damage", StorageType::Table, Layout::new::<u32>(), None)
+/// });
+///
+/// let mut events = world.resource_mut::<DynamicEvents>();
+/// let mut reader = events.get_reader(damage);
+/// // SAFETY: the event type was registered with the layout of a `u32`
+/// OwningPtr::make(10u32, |ptr| unsafe { events.send_by_id(damage, ptr) });
+///
+/// let damages: Vec<u32> = reader
+///     .read(&events)
+///     // SAFETY: the event type was registered with the layout of a `u32`
+///     .map(|ptr| unsafe { *ptr.deref::<u32>() })
+///     .collect();
+/// assert_eq!(damages, vec![10]);
+/// ```
+#[derive(Debug, Default, Resource)]
+pub struct DynamicEvents {
+    channels: HashMap<ComponentId, DynamicEventChannel>,
+    names: HashMap<String, ComponentId>,
+}
+
+/// The double buffered events of a single runtime event type, see [`Events`].
+#[derive(Debug)]
+struct DynamicEventChannel {
+    events_a: DynamicEventSequence,
+    events_b: DynamicEventSequence,
+    event_count: usize,
+}
+
+// SAFETY: event types are only registered by `World::init_dynamic_event`, which checks that their
+// descriptor is `Send + Sync`.
+unsafe impl Send for DynamicEventChannel {}
+// SAFETY: see above
+unsafe impl Sync for DynamicEventChannel {}
+
+#[derive(Debug)]
+struct DynamicEventSequence {
+    events: BlobVec,
+    start_event_count: usize,
+}
+
+impl DynamicEventChannel {
+    fn oldest_event_count(&self) -> usize {
+        self.events_a
+            .start_event_count
+            .min(self.events_b.start_event_count)
+    }
+
+    fn len(&self) -> usize {
+        self.events_a.events.len() + self.events_b.events.len()
+    }
+
+    fn update(&mut self) {
+        std::mem::swap(&mut self.events_a, &mut self.events_b);
+        self.events_b.events.clear();
+        self.events_b.start_event_count = self.event_count;
+        debug_assert_eq!(
+            self.events_a.start_event_count + self.events_a.events.len(),
+            self.events_b.start_event_count
+        );
+    }
+}
+
+impl DynamicEvents {
+    /// Adds the channel of a new event type.
+    ///
+    /// # Safety
+    /// - `drop` must be safe to call with a pointer to a value of the event type, of `layout`.
+    /// - the event type must be `Send + Sync`.
+    pub(crate) unsafe fn register(
+        &mut self,
+        id: ComponentId,
+        name: String,
+        layout: Layout,
+        drop: Option<unsafe fn(OwningPtr<'_>)>,
+    ) {
+        let sequence = || DynamicEventSequence {
+            // SAFETY: the caller ensures `drop` matches the values of `layout` pushed into it
+            events: unsafe { BlobVec::new(layout, drop, 0) },
+            start_event_count: 0,
+        };
+        self.channels.insert(
+            id,
+            DynamicEventChannel {
+                events_a: sequence(),
+                events_b: sequence(),
+                event_count: 0,
+            },
+        );
+        self.names.entry(name).or_insert(id);
+    }
+
+    #[track_caller]
+    fn channel(&self, id: ComponentId) -> &DynamicEventChannel {
+        self.channels
+            .get(&id)
+            .unwrap_or_else(|| unknown_event_type(id))
+    }
+
+    /// Returns `true` if an event type was registered with the given id.
+    pub fn contains(&self, id: ComponentId) -> bool {
+        self.channels.contains_key(&id)
+    }
+
+    /// Returns the id of the event type registered with the given name. If several event types
+    /// share a name, the first one registered is returned.
+    pub fn get_id_by_name(&self, name: &str) -> Option<ComponentId> {
+        self.names.get(name).copied()
+    }
+
+    /// Sends an event of the event type with the given id, moving the value of `event` into the
+    /// current event buffer. Returns the index of the event among the events of its type.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no event type was registered with the id.
+    ///
+    /// # Safety
+    ///
+    /// `event` must point to a valid value of the event type.
+    #[track_caller]
+    pub unsafe fn send_by_id(&mut self, id: ComponentId, event: OwningPtr<'_>) -> usize {
+        let channel = self
+            .channels
+            .get_mut(&id)
+            .unwrap_or_else(|| unknown_event_type(id));
+        detailed_trace!("DynamicEvents::send_by_id() -> id: {}", channel.event_count);
+        channel.events_b.events.push(event);
+        channel.event_count += 1;
+        channel.event_count - 1
+    }
+
+    /// Gets a new [`DynamicEventReader`] for the event type with the given id. This will include
+    /// all events already in the event buffers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no event type was registered with the id.
+    #[track_caller]
+    pub fn get_reader(&self, id: ComponentId) -> DynamicEventReader {
+        DynamicEventReader {
+            id,
+            last_event_count: self.channel(id).oldest_event_count(),
+        }
+    }
+
+    /// Gets a new [`DynamicEventReader`] for the event type with the given id. This will ignore
+    /// all events already in the event buffers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no event type was registered with the id.
+    #[track_caller]
+    pub fn get_reader_current(&self, id: ComponentId) -> DynamicEventReader {
+        DynamicEventReader {
+            id,
+            last_event_count: self.channel(id).event_count,
+        }
+    }
+
+    /// Swaps the event buffers of every event type and clears the oldest ones. In general, this
+    /// should be called once per frame/update.
+    pub fn update(&mut self) {
+        self.channels
+            .values_mut()
+            .for_each(DynamicEventChannel::update);
+    }
+
+    /// Removes all events of every event type.
+    pub fn clear(&mut self) {
+        for channel in self.channels.values_mut() {
+            channel.events_a.start_event_count = channel.event_count;
+            channel.events_b.start_event_count = channel.event_count;
+            channel.events_a.events.clear();
+            channel.events_b.events.clear();
+        }
+    }
+}
+
+#[track_caller]
+fn unknown_event_type(id: ComponentId) -> ! {
+    panic!("The event type {id:?} was not registered with World::init_dynamic_event")
+}
+
+/// Reads the events of a runtime event type from [`DynamicEvents`] in order, and tracks which
+/// events have already been read, like [`ManualEventReader`].
+#[derive(Debug, Clone)]
+pub struct DynamicEventReader {
+    id: ComponentId,
+    last_event_count: usize,
+}
+
+#[allow(clippy::len_without_is_empty)] // Check fails since the is_empty implementation has a signature other than `(&self) -> bool`
+impl DynamicEventReader {
+    /// Returns the id of the event type this reader reads.
+    pub fn id(&self) -> ComponentId {
+        self.id
+    }
+
+    /// Iterates over the events this reader has not read yet, oldest first, as pointers to values
+    /// of the event type.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `events` does not contain the event type of this reader.
+    #[track_caller]
+    pub fn read<'a>(&'a mut self, events: &'a DynamicEvents) -> DynamicEventIterator<'a> {
+        let channel = events.channel(self.id);
+        let a_index = self
+            .last_event_count
+            .saturating_sub(channel.events_a.start_event_count)
+            .min(channel.events_a.events.len());
+        let b_index = self
+            .last_event_count
+            .saturating_sub(channel.events_b.start_event_count)
+            .min(channel.events_b.events.len());
+        let unread =
+            channel.events_a.events.len() - a_index + channel.events_b.events.len() - b_index;
+        self.last_event_count = channel.event_count - unread;
+        DynamicEventIterator {
+            reader: self,
+            sequences: [
+                (&channel.events_a.events, a_index),
+                (&channel.events_b.events, b_index),
+            ],
+            unread,
+        }
+    }
+
+    /// Returns the number of events this reader has not read yet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `events` does not contain the event type of this reader.
+    #[track_caller]
+    pub fn len(&self, events: &DynamicEvents) -> usize {
+        let channel = events.channel(self.id);
+        channel
+            .event_count
+            .saturating_sub(self.last_event_count)
+            .min(channel.len())
+    }
+
+    /// Returns `true` if there are no events this reader has not read yet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `events` does not contain the event type of this reader.
+    #[track_caller]
+    pub fn is_empty(&self, events: &DynamicEvents) -> bool {
+        self.len(events) == 0
+    }
+
+    /// Returns the number of events which were dropped before this reader read them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `events` does not contain the event type of this reader.
+    #[track_caller]
+    pub fn missed_events(&self, events: &DynamicEvents) -> usize {
+        events
+            .channel(self.id)
+            .oldest_event_count()
+            .saturating_sub(self.last_event_count)
+    }
+
+    /// Marks all events as read.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `events` does not contain the event type of this reader.
+    #[track_caller]
+    pub fn clear(&mut self, events: &DynamicEvents) {
+        self.last_event_count = events.channel(self.id).event_count;
+    }
+}
+
+/// An iterator over the unread events of a [`DynamicEventReader`].
+#[derive(Debug)]
+pub struct DynamicEventIterator<'a> {
+    reader: &'a mut DynamicEventReader,
+    sequences: [(&'a BlobVec, usize); 2],
+    unread: usize,
+}
+
+impl<'a> Iterator for DynamicEventIterator<'a> {
+    type Item = Ptr<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (events, index) = self
+            .sequences
+            .iter_mut()
+            .find(|(events, index)| *index < events.len())?;
+        let events: &'a BlobVec = *events;
+        // SAFETY: `index` was just checked to be in bounds
+        let event = unsafe { events.get_unchecked(*index) };
+        *index += 1;
+        self.reader.last_event_count += 1;
+        self.unread -= 1;
+        Some(event)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.unread, Some(self.unread))
+    }
+}
+
+impl<'a> ExactSizeIterator for DynamicEventIterator<'a> {
+    fn len(&self) -> usize {
+        self.unread
+    }
+}
+
+/// A system that calls [`DynamicEvents::update`].
+pub fn dynamic_event_update_system(
+    signal: Option<ResMut<EventUpdateSignal>>,
+    events: Option<ResMut<DynamicEvents>>,
+) {
+    if let Some(mut s) = signal {
+        // If we haven't got a signal to update the events, but we *could* get such a signal
+        // return early and update the events later.
+        if !std::mem::replace(&mut s.0, false) {
+            return;
+        }
+    }
+
+    if let Some(mut events) = events {
+        events.update();
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::system::assert_is_read_only_system;
+    use crate::{
+        component::{Component, ComponentDescriptor},
+        system::assert_is_read_only_system,
+        world::World,
+    };
 
     use super::*;
 
@@ -1210,4 +1565,79 @@ mod tests {
             "Only sent two events; got more than two IDs"
         );
     }
+
+    #[derive(Component, PartialEq, Debug)]
+    struct Message(String);
+
+    fn read_messages(events: &DynamicEvents, reader: &mut DynamicEventReader) -> Vec<String> {
+        reader
+            .read(events)
+            // SAFETY: the event type was registered from `Message`
+            .map(|ptr| unsafe { ptr.deref::<Message>() }.0.clone())
+            .collect()
+    }
+
+    fn send_message(events: &mut DynamicEvents, id: ComponentId, message: &str) {
+        OwningPtr::make(Message(message.to_string()), |ptr| {
+            // SAFETY: the event type was registered from `Message`
+            unsafe { events.send_by_id(id, ptr) };
+        });
+    }
+
+    #[test]
+    fn dynamic_events() {
+        let mut world = World::new();
+        let id = world.init_dynamic_event(ComponentDescriptor::new::<Message>());
+        let other_id = world.init_dynamic_event(ComponentDescriptor::new::<Message>());
+        assert_ne!(id, other_id);
+
+        let mut events = world.resource_mut::<DynamicEvents>();
+        assert_eq!(
+            events.get_id_by_name(std::any::type_name::<Message>()),
+            Some(id)
+        );
+        let mut reader_missed = events.get_reader(id);
+        let mut reader = events.get_reader(id);
+        let mut other_reader = events.get_reader(other_id);
+
+        send_message(&mut events, id, "a");
+        assert_eq!(read_messages(&events, &mut reader), vec!["a"]);
+        assert!(reader.is_empty(&events));
+        assert!(other_reader.is_empty(&events));
+
+        events.update();
+        send_message(&mut events, id, "b");
+        let mut reader_late = events.get_reader(id);
+        let mut reader_current = events.get_reader_current(id);
+        assert_eq!(reader.len(&events), 1);
+        assert_eq!(read_messages(&events, &mut reader_late), vec!["a", "b"]);
+        assert_eq!(
+            read_messages(&events, &mut reader_current),
+            Vec::<String>::new()
+        );
+
+        events.update();
+        send_message(&mut events, id, "c");
+        assert_eq!(reader_missed.missed_events(&events), 1);
+        assert_eq!(read_messages(&events, &mut reader_missed), vec!["b", "c"]);
+        assert_eq!(read_messages(&events, &mut reader), vec!["b", "c"]);
+
+        send_message(&mut events, other_id, "d");
+        assert_eq!(read_messages(&events, &mut other_reader), vec!["d"]);
+
+        events.clear();
+        reader.clear(&events);
+        assert!(reader.is_empty(&events));
+        assert_eq!(
+            read_messages(&events, &mut reader_current),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "was not registered with World::init_dynamic_event")]
+    fn dynamic_events_unknown_id() {
+        let events = DynamicEvents::default();
+        events.get_reader(ComponentId::new(0));
+    }
 }
@@ -348,6 +348,21 @@ impl<T: SparseSetIndex> FilteredAccess<T> {
         self.and_with(index);
     }
 
+    /// Returns the indices of the elements that must be present for this access to match.
+    pub fn required(&self) -> impl Iterator<Item = T> + '_ {
+        self.required.ones().map(T::get_sparse_set_index)
+    }
+
+    /// Returns the filter sets of this access.
+    ///
+    /// The filter sets form a disjunction (OR) where each [`AccessFilters`] is a conjunction (AND)
+    /// of `With` and `Without` filters. Together with [`FilteredAccess::access`] and
+    /// [`FilteredAccess::required`], they describe which entities a query matches, for example to
+    /// display a query built at runtime.
+    pub fn filter_sets(&self) -> &[AccessFilters<T>] {
+        &self.filter_sets
+    }
+
     fn add_required(&mut self, index: T) {
         let index = index.sparse_set_index();
         self.required.grow(index + 1);
@@ -469,10 +484,32 @@ impl<T: SparseSetIndex> FilteredAccess<T> {
     pub fn is_subset(&self, other: &FilteredAccess<T>) -> bool {
         self.required.is_subset(&other.required) && self.access().is_subset(other.access())
     }
+
+    /// Returns `true` if `other` allows the accesses of `self`, and every entity matching `other`
+    /// has the elements required by `self`, either because `other` requires them or because
+    /// they are in a `With` filter of each of its filter sets.
+    ///
+    /// Unlike [`FilteredAccess::is_subset`], this is enough to transmute a query to one with the
+    /// access of `self`.
+    pub(crate) fn is_guaranteed_by(&self, other: &FilteredAccess<T>) -> bool {
+        self.required.ones().all(|index| other.guarantees(index))
+            && self.access().is_subset(other.access())
+    }
+
+    /// Returns `true` if every entity matching this access has the element at `index`.
+    fn guarantees(&self, index: usize) -> bool {
+        self.required.contains(index)
+            || self
+                .filter_sets
+                .iter()
+                .all(|filter| filter.with.contains(index))
+    }
 }
 
+/// A conjunction of `With` and `Without` filters, one term of the disjunction held by a
+/// [`FilteredAccess`], see [`FilteredAccess::filter_sets`].
 #[derive(Clone, Eq, PartialEq)]
-pub(crate) struct AccessFilters<T> {
+pub struct AccessFilters<T> {
     pub(crate) with: FixedBitSet,
     pub(crate) without: FixedBitSet,
     _index_type: PhantomData<T>,
@@ -498,6 +535,16 @@ impl<T: SparseSetIndex> Default for AccessFilters<T> {
 }
 
 impl<T: SparseSetIndex> AccessFilters<T> {
+    /// Returns the indices of the elements that must be present to match these filters.
+    pub fn with(&self) -> impl Iterator<Item = T> + '_ {
+        self.with.ones().map(T::get_sparse_set_index)
+    }
+
+    /// Returns the indices of the elements that must be absent to match these filters.
+    pub fn without(&self) -> impl Iterator<Item = T> + '_ {
+        self.without.ones().map(T::get_sparse_set_index)
+    }
+
     fn is_ruled_out_by(&self, other: &Self) -> bool {
         // Although not technically complete, we don't consider the case when `AccessFilters`'s
         // `without` bitset contradicts its own `with` bitset (e.g. `(With<A>, Without<A>)`).
@@ -765,4 +812,47 @@ mod tests {
 
         assert_eq!(access_a, expected);
     }
+
+    #[test]
+    fn filtered_access_filter_sets() {
+        let mut access_a = FilteredAccess::<usize>::default();
+        access_a.add_read(0);
+        access_a.and_without(1);
+
+        let mut access_b = FilteredAccess::<usize>::default();
+        access_b.and_with(2);
+
+        let mut access_c = FilteredAccess::<usize>::default();
+        access_c.and_with(3);
+        access_b.append_or(&access_c);
+
+        access_a.extend(&access_b);
+
+        assert_eq!(access_a.required().collect::<Vec<_>>(), vec![0]);
+
+        let filter_sets = access_a.filter_sets();
+        assert_eq!(filter_sets.len(), 2);
+        assert_eq!(filter_sets[0].with().collect::<Vec<_>>(), vec![0, 2]);
+        assert_eq!(filter_sets[0].without().collect::<Vec<_>>(), vec![1]);
+        assert_eq!(filter_sets[1].with().collect::<Vec<_>>(), vec![0, 3]);
+        assert_eq!(filter_sets[1].without().collect::<Vec<_>>(), vec![1]);
+    }
+
+    #[test]
+    fn filtered_access_guaranteed_by_with_filters() {
+        let mut access_a = FilteredAccess::<usize>::default();
+        access_a.access_mut().read_all();
+        access_a.and_with(0);
+
+        let mut access_b = FilteredAccess::<usize>::default();
+        access_b.add_read(0);
+
+        assert!(!access_b.is_subset(&access_a));
+        assert!(access_b.is_guaranteed_by(&access_a));
+
+        let mut access_c = FilteredAccess::<usize>::default();
+        access_c.and_with(1);
+        access_a.append_or(&access_c);
+        assert!(!access_b.is_guaranteed_by(&access_a));
+    }
 }
@@ -1,8 +1,11 @@
 use std::marker::PhantomData;
 
-use crate::{component::ComponentId, prelude::*};
+use crate::{
+    component::{ComponentId, StorageType},
+    prelude::*,
+};
 
-use super::{FilteredAccess, QueryData, QueryFilter};
+use super::{DynamicChangeBranch, DynamicChangeFilter, FilteredAccess, QueryData, QueryFilter};
 
 /// Builder struct to create [`QueryState`] instances at runtime.
 ///
@@ -34,6 +37,8 @@ use super::{FilteredAccess, QueryData, QueryFilter};
 ///```
 pub struct QueryBuilder<'w, D: QueryData = (), F: QueryFilter = ()> {
     access: FilteredAccess<ComponentId>,
+    change_filters: Vec<DynamicChangeFilter>,
+    or_branches: Vec<DynamicChangeBranch>,
     world: &'w mut World,
     or: bool,
     first: bool,
@@ -61,6 +66,8 @@ impl<'w, D: QueryData, F: QueryFilter> QueryBuilder<'w, D, F> {
 
         Self {
             access,
+            change_filters: Vec::new(),
+            or_branches: Vec::new(),
             world,
             or: false,
             first: false,
@@ -81,6 +88,10 @@ impl<'w, D: QueryData, F: QueryFilter> QueryBuilder<'w, D, F> {
     /// Adds access to self's underlying [`FilteredAccess`] respecting [`Self::or`] and [`Self::and`]
     pub fn extend_access(&mut self, mut access: FilteredAccess<ComponentId>) {
         if self.or {
+            self.or_branches.push(DynamicChangeBranch {
+                filter_sets: access.filter_sets.clone(),
+                filters: Vec::new(),
+            });
             if self.first {
                 access.required.clear();
                 self.access.extend(&access);
@@ -153,12 +164,50 @@ impl<'w, D: QueryData, F: QueryFilter> QueryBuilder<'w, D, F> {
         self
     }
 
+    /// Adds [`Added<T>`] to self from a runtime [`ComponentId`].
+    ///
+    /// Inside [`Self::or`] this is a term of the or expression like any other, and inside
+    /// [`Self::optional`] entities without the component are not filtered out.
+    pub fn added_id(&mut self, id: ComponentId) -> &mut Self {
+        let mut access = FilteredAccess::default();
+        access.add_read(id);
+        self.extend_access(access);
+        self.push_change_filters(vec![DynamicChangeFilter::Added(id)]);
+        self
+    }
+
+    /// Adds [`Changed<T>`] to self from a runtime [`ComponentId`].
+    ///
+    /// Inside [`Self::or`] this is a term of the or expression like any other, and inside
+    /// [`Self::optional`] entities without the component are not filtered out.
+    pub fn changed_id(&mut self, id: ComponentId) -> &mut Self {
+        let mut access = FilteredAccess::default();
+        access.add_read(id);
+        self.extend_access(access);
+        self.push_change_filters(vec![DynamicChangeFilter::Changed(id)]);
+        self
+    }
+
+    /// Adds change filters for the term last added with [`Self::extend_access`], which is its own
+    /// branch when inside [`Self::or`].
+    fn push_change_filters(&mut self, filters: Vec<DynamicChangeFilter>) {
+        match self.or_branches.last_mut() {
+            Some(branch) if self.or => branch.filters.extend(filters),
+            _ => self.change_filters.extend(filters),
+        }
+    }
+
     /// Takes a function over mutable access to a [`QueryBuilder`], calls that function
     /// on an empty builder and then adds all accesses from that builder to self as optional.
     pub fn optional(&mut self, f: impl Fn(&mut QueryBuilder)) -> &mut Self {
         let mut builder = QueryBuilder::new(self.world);
         f(&mut builder);
         self.access.extend_access(builder.access());
+        if !builder.change_filters.is_empty() {
+            let filters = std::mem::take(&mut builder.change_filters);
+            self.change_filters
+                .push(DynamicChangeFilter::Optional(filters));
+        }
         self
     }
 
@@ -171,6 +220,8 @@ impl<'w, D: QueryData, F: QueryFilter> QueryBuilder<'w, D, F> {
         f(&mut builder);
         let access = builder.access().clone();
         self.extend_access(access);
+        let filters = std::mem::take(&mut builder.change_filters);
+        self.push_change_filters(filters);
         self
     }
 
@@ -201,6 +252,16 @@ impl<'w, D: QueryData, F: QueryFilter> QueryBuilder<'w, D, F> {
         builder.first = true;
         f(&mut builder);
         self.access.extend(builder.access());
+        self.change_filters
+            .extend(std::mem::take(&mut builder.change_filters));
+        if builder
+            .or_branches
+            .iter()
+            .any(|branch| !branch.filters.is_empty())
+        {
+            let branches = std::mem::take(&mut builder.or_branches);
+            self.change_filters.push(DynamicChangeFilter::Or(branches));
+        }
         self
     }
 
@@ -209,6 +270,37 @@ impl<'w, D: QueryData, F: QueryFilter> QueryBuilder<'w, D, F> {
         &self.access
     }
 
+    /// Returns `true` if every component filtered on by the builder is stored in
+    /// [`StorageType::Table`], so the built query can iterate whole tables at once.
+    ///
+    /// Components which are only accessed, such as those added with [`Self::optional`], do not
+    /// change which entities are matched, so they may be stored in sparse sets.
+    /// [`FilteredEntityRef`](crate::world::FilteredEntityRef) and
+    /// [`FilteredEntityMut`](crate::world::FilteredEntityMut) then look them up for each entity,
+    /// while the table components are iterated as fast as the equivalent typed query, including
+    /// with [`QueryState::par_iter_mut`].
+    pub fn is_dense(&self) -> bool {
+        let access = self.access.access();
+        if access.has_read_all() || access.has_write_all() {
+            return false;
+        }
+        let components = self.world.components();
+        let is_table = |id: ComponentId| {
+            components
+                .get_info(id)
+                .is_some_and(|info| info.storage_type() == StorageType::Table)
+        };
+        self.access
+            .filter_sets()
+            .iter()
+            .all(|filter| filter.with().chain(filter.without()).all(is_table))
+    }
+
+    /// Returns the change filters added with [`Self::added_id`] and [`Self::changed_id`].
+    pub(crate) fn change_filters(&self) -> &[DynamicChangeFilter] {
+        &self.change_filters
+    }
+
     /// Transmute the existing builder adding required accesses.
     /// This will maintain all exisiting accesses.
     ///
@@ -242,6 +334,9 @@ impl<'w, D: QueryData, F: QueryFilter> QueryBuilder<'w, D, F> {
     ///
     /// Takes `&mut self` to access the innner world reference while initializing
     /// state for the new [`QueryState`]
+    ///
+    /// Like any other [`QueryState`], the result can be stored and reused with the same world,
+    /// matching archetypes created after it was built without needing to build it again.
     pub fn build(&mut self) -> QueryState<D, F> {
         QueryState::<D, F>::from_builder(self)
     }
@@ -250,8 +345,10 @@ impl<'w, D: QueryData, F: QueryFilter> QueryBuilder<'w, D, F> {
 #[cfg(test)]
 mod tests {
     use crate as bevy_ecs;
+    use crate::component::StorageType;
     use crate::prelude::*;
-    use crate::world::FilteredEntityRef;
+    use crate::world::{FilteredEntityMut, FilteredEntityRef};
+    use bevy_tasks::{ComputeTaskPool, TaskPool};
 
     use super::QueryBuilder;
 
@@ -264,6 +361,10 @@ mod tests {
     #[derive(Component, PartialEq, Debug)]
     struct C(usize);
 
+    #[derive(Component, PartialEq, Debug)]
+    #[component(storage = "SparseSet")]
+    struct S(usize);
+
     #[test]
     fn builder_with_without_static() {
         let mut world = World::new();
@@ -398,4 +499,315 @@ mod tests {
             assert_eq!(1, b.deref::<B>().0);
         }
     }
+
+    #[test]
+    fn builder_dynamic_change_filters() {
+        let mut world = World::new();
+        let entity = world.spawn(A(0)).id();
+        let component_id_a = world.init_component::<A>();
+
+        let mut added = QueryBuilder::<Entity>::new(&mut world)
+            .added_id(component_id_a)
+            .build();
+        let mut changed = QueryBuilder::<Entity>::new(&mut world)
+            .changed_id(component_id_a)
+            .build();
+        let mut mutate = QueryBuilder::<FilteredEntityMut>::new(&mut world)
+            .mut_id(component_id_a)
+            .build();
+
+        assert_eq!(vec![entity], added.iter(&world).collect::<Vec<_>>());
+        assert_eq!(vec![entity], changed.iter(&world).collect::<Vec<_>>());
+        world.clear_trackers();
+
+        assert_eq!(0, added.iter(&world).count());
+        assert_eq!(0, changed.iter(&world).count());
+        world.clear_trackers();
+
+        let mut entity_mut = mutate.single_mut(&mut world);
+        let a = entity_mut.get_mut_by_id(component_id_a).unwrap();
+        // SAFETY: We set this pointer to point to a component of type `A`
+        unsafe { a.into_inner().deref_mut::<A>().0 = 1 };
+
+        assert_eq!(0, added.iter(&world).count());
+        assert_eq!(vec![entity], changed.iter(&world).collect::<Vec<_>>());
+        assert_eq!(Ok(entity), changed.get(&world, entity));
+        world.clear_trackers();
+
+        assert_eq!(0, changed.iter(&world).count());
+        assert!(changed.get(&world, entity).is_err());
+    }
+
+    #[test]
+    fn builder_dynamic_change_filters_or_optional() {
+        let mut world = World::new();
+        let entity_a = world.spawn(A(0)).id();
+        world.spawn((A(0), B(0)));
+        let entity_b = world.spawn(B(0)).id();
+        let entity_c = world.spawn(C(0)).id();
+        let component_id_a = world.init_component::<A>();
+        let component_id_b = world.init_component::<B>();
+
+        let mut or = QueryBuilder::<Entity>::new(&mut world)
+            .or(|builder| {
+                builder.changed_id(component_id_a);
+                builder.changed_id(component_id_b);
+            })
+            .build();
+        let mut optional = QueryBuilder::<Entity>::new(&mut world)
+            .with_id(component_id_a)
+            .optional(|builder| {
+                builder.changed_id(component_id_b);
+            })
+            .build();
+        let mut mixed = QueryBuilder::<Entity>::new(&mut world)
+            .or(|builder| {
+                builder.changed_id(component_id_b);
+                builder.and(|builder| {
+                    builder.with_id(component_id_a);
+                    builder.without_id(component_id_b);
+                });
+            })
+            .build();
+        let mut optional_or = QueryBuilder::<Entity>::new(&mut world)
+            .optional(|builder| {
+                builder.or(|builder| {
+                    builder.changed_id(component_id_a);
+                    builder.changed_id(component_id_b);
+                });
+            })
+            .build();
+        let mut optional_or_with = QueryBuilder::<Entity>::new(&mut world)
+            .optional(|builder| {
+                builder.or(|builder| {
+                    builder.and(|builder| {
+                        builder.with_id(component_id_a);
+                        builder.changed_id(component_id_a);
+                    });
+                    builder.and(|builder| {
+                        builder.with_id(component_id_b);
+                        builder.changed_id(component_id_b);
+                    });
+                });
+            })
+            .build();
+        let run = |query: &mut QueryState<Entity>, world: &World| {
+            let mut entities: Vec<_> = query.iter(world).collect();
+            entities.sort();
+            entities
+        };
+        world.clear_trackers();
+
+        world.get_mut::<A>(entity_a).unwrap().0 = 1;
+        assert_eq!(vec![entity_a], run(&mut or, &world));
+        assert_eq!(vec![entity_a], run(&mut optional, &world));
+        assert_eq!(vec![entity_a], run(&mut mixed, &world));
+        assert_eq!(vec![entity_a, entity_c], run(&mut optional_or, &world));
+        assert_eq!(vec![entity_a, entity_c], run(&mut optional_or_with, &world));
+        world.clear_trackers();
+
+        world.get_mut::<B>(entity_b).unwrap().0 = 1;
+        assert_eq!(vec![entity_b], run(&mut or, &world));
+        assert_eq!(vec![entity_a], run(&mut optional, &world));
+        assert_eq!(vec![entity_a, entity_b], run(&mut mixed, &world));
+        assert_eq!(vec![entity_b, entity_c], run(&mut optional_or, &world));
+        assert_eq!(vec![entity_b, entity_c], run(&mut optional_or_with, &world));
+    }
+
+    #[test]
+    fn builder_reuse_state() {
+        let mut world = World::new();
+        let component_id_a = world.init_component::<A>();
+
+        let mut query = QueryBuilder::<FilteredEntityMut>::new(&mut world)
+            .mut_id(component_id_a)
+            .build();
+        assert_eq!(0, query.iter_mut(&mut world).count());
+
+        let increment = |query: &mut QueryState<FilteredEntityMut>, world: &mut World| {
+            query
+                .iter_mut(world)
+                .map(|mut entity| {
+                    let a = entity.get_mut_by_id(component_id_a).unwrap();
+                    // SAFETY: We set this pointer to point to a component of type `A`
+                    unsafe { a.into_inner().deref_mut::<A>().0 += 1 };
+                })
+                .count()
+        };
+
+        let entity_a = world.spawn(A(0)).id();
+        let entity_b = world.spawn((A(10), B(0))).id();
+        assert_eq!(2, increment(&mut query, &mut world));
+
+        let entity_c = world.spawn((A(20), C(0))).id();
+        assert_eq!(3, increment(&mut query, &mut world));
+
+        assert_eq!(Some(&A(2)), world.get::<A>(entity_a));
+        assert_eq!(Some(&A(12)), world.get::<A>(entity_b));
+        assert_eq!(Some(&A(21)), world.get::<A>(entity_c));
+    }
+
+    #[test]
+    fn builder_is_dense() {
+        let mut world = World::new();
+        let component_id_a = world.init_component::<A>();
+        let component_id_s = world.init_component::<S>();
+
+        let mut builder = QueryBuilder::<FilteredEntityMut>::new(&mut world);
+        builder.mut_id(component_id_a).with::<B>().without::<C>();
+        assert!(builder.is_dense());
+        assert!(builder.build().is_dense);
+
+        let mut builder = QueryBuilder::<FilteredEntityMut>::new(&mut world);
+        builder.mut_id(component_id_a).with_id(component_id_s);
+        assert!(!builder.is_dense());
+        assert!(!builder.build().is_dense);
+
+        // Optional sparse components do not change which entities are matched
+        let mut builder = QueryBuilder::<FilteredEntityRef>::new(&mut world);
+        builder.optional(|builder| {
+            builder.ref_id(component_id_s);
+        });
+        assert!(builder.is_dense());
+        assert!(builder.build().is_dense);
+
+        let mut builder = QueryBuilder::<Option<&S>>::new(&mut world);
+        assert!(!builder.build().is_dense);
+
+        assert!(!QueryBuilder::<EntityRef>::new(&mut world).is_dense());
+    }
+
+    #[test]
+    fn builder_par_iter_mut() {
+        ComputeTaskPool::get_or_init(TaskPool::default);
+        let mut world = World::new();
+        let component_id_a = world.init_component::<A>();
+        let entities: Vec<_> = (0..100)
+            .map(|i| match i % 3 {
+                0 => world.spawn(A(i)).id(),
+                1 => world.spawn((A(i), B(0))).id(),
+                _ => world.spawn((A(i), S(0))).id(),
+            })
+            .collect();
+
+        for filter_sparse in [false, true] {
+            let mut builder = QueryBuilder::<FilteredEntityMut>::new(&mut world);
+            builder.mut_id(component_id_a);
+            if filter_sparse {
+                builder.without::<S>();
+            }
+            let mut query = builder.build();
+            assert_eq!(!filter_sparse, query.is_dense);
+            query.par_iter_mut(&mut world).for_each(|mut entity| {
+                let a = entity.get_mut_by_id(component_id_a).unwrap();
+                // SAFETY: We set this pointer to point to a component of type `A`
+                unsafe { a.into_inner().deref_mut::<A>().0 += 1000 };
+            });
+        }
+
+        for (i, entity) in entities.into_iter().enumerate() {
+            let expected = if i % 3 == 2 { i + 1000 } else { i + 2000 };
+            assert_eq!(Some(&A(expected)), world.get::<A>(entity));
+        }
+    }
+
+    #[test]
+    fn builder_transmute_to_typed() {
+        let mut world = World::new();
+        let entity = world.spawn((A(0), B(1))).id();
+        world.spawn(A(2));
+        let component_id_a = world.init_component::<A>();
+        let component_id_b = world.init_component::<B>();
+
+        let query = QueryBuilder::<FilteredEntityMut>::new(&mut world)
+            .mut_id(component_id_a)
+            .ref_id(component_id_b)
+            .build();
+        let mut lens = query.transmute::<(Entity, &mut A, &B)>(&world);
+        let (e, mut a, b) = lens.single_mut(&mut world);
+        assert_eq!(e, entity);
+        assert_eq!(b, &B(1));
+        a.0 = 10;
+        assert_eq!(world.get::<A>(entity), Some(&A(10)));
+
+        // `With` filters guarantee the component is there for terms which access everything
+        let query = QueryBuilder::<EntityRef>::new(&mut world)
+            .with_id(component_id_a)
+            .with_id(component_id_b)
+            .build();
+        let mut lens = query.transmute::<&A>(&world);
+        assert_eq!(lens.single(&world), &A(10));
+    }
+
+    #[test]
+    #[should_panic(expected = "attempts to access terms that are not allowed by original state")]
+    fn builder_transmute_optional_to_typed() {
+        let mut world = World::new();
+        let component_id_a = world.init_component::<A>();
+
+        let query = QueryBuilder::<FilteredEntityRef>::new(&mut world)
+            .optional(|builder| {
+                builder.ref_id(component_id_a);
+            })
+            .build();
+        let _ = query.transmute::<&A>(&world);
+    }
+
+    #[test]
+    #[should_panic(expected = "attempts to access terms that are not allowed by original state")]
+    fn builder_transmute_read_to_mut() {
+        let mut world = World::new();
+        let component_id_a = world.init_component::<A>();
+
+        let query = QueryBuilder::<FilteredEntityMut>::new(&mut world)
+            .ref_id(component_id_a)
+            .build();
+        let _ = query.transmute::<&mut A>(&world);
+    }
+
+    #[test]
+    fn builder_dense_with_optional_sparse() {
+        let mut world = World::new();
+        let component_id_a = world.init_component::<A>();
+        let component_id_s = world.init_component::<S>();
+        world.spawn(A(0));
+        world.spawn((A(1), S(10)));
+        world.spawn((A(2), S(20), B(0)));
+
+        let mut query = QueryBuilder::<FilteredEntityMut>::new(&mut world)
+            .mut_id(component_id_a)
+            .optional(|builder| {
+                builder.mut_id(component_id_s);
+            })
+            .build();
+        assert!(query.is_dense);
+
+        let mut values: Vec<_> = query
+            .iter_mut(&mut world)
+            .map(|mut entity| {
+                assert_eq!(
+                    entity.storage_type(component_id_a),
+                    Some(StorageType::Table)
+                );
+                let a = entity.get_by_id(component_id_a).unwrap();
+                // SAFETY: We set this pointer to point to a component of type `A`
+                let a = unsafe { a.deref::<A>() }.0;
+                let has_s = entity.access().has_write(component_id_s);
+                let s = entity.get_mut_by_id(component_id_s).map(|s| {
+                    // SAFETY: We set this pointer to point to a component of type `S`
+                    let s = unsafe { s.into_inner().deref_mut::<S>() };
+                    s.0 += 1;
+                    s.0
+                });
+                assert_eq!(has_s, s.is_some());
+                assert_eq!(
+                    entity.storage_type(component_id_s),
+                    s.map(|_| StorageType::SparseSet)
+                );
+                (a, s)
+            })
+            .collect();
+        values.sort();
+        assert_eq!(values, vec![(0, None), (1, Some(11)), (2, Some(21))]);
+    }
 }
@@ -0,0 +1,503 @@
+use bevy_utils::HashMap;
+use thiserror::Error;
+
+use crate::component::{ComponentId, Components};
+
+use super::{QueryBuilder, QueryData, QueryFilter};
+
+/// Looks up the [`ComponentId`] of a component by the name used for it in a query string.
+///
+/// See [`QueryBuilder::parse`].
+pub trait ComponentNameResolver {
+    /// Returns the id of the component with the given name, if there is one.
+    fn resolve(&self, name: &str) -> Option<ComponentId>;
+}
+
+impl ComponentNameResolver for Components {
+    fn resolve(&self, name: &str) -> Option<ComponentId> {
+        self.get_id_by_name(name)
+    }
+}
+
+impl ComponentNameResolver for HashMap<String, ComponentId> {
+    fn resolve(&self, name: &str) -> Option<ComponentId> {
+        self.get(name).copied()
+    }
+}
+
+/// An error that occurs when parsing a query string with [`QueryBuilder::parse`].
+///
+/// Each position is the byte offset in the query string where the error was found.
+#[derive(Debug, PartialEq, Eq, Clone, Error)]
+pub enum QueryParseError {
+    /// The name does not belong to a component.
+    #[error("Unknown component `{name}` at position {position}")]
+    UnknownComponent {
+        /// The name of the component.
+        name: String,
+        /// The position of the name.
+        position: usize,
+    },
+    /// The name before the parentheses is not a filter.
+    #[error("Unknown filter `{name}` at position {position}, expected `Added` or `Changed`")]
+    UnknownFilter {
+        /// The name of the filter.
+        name: String,
+        /// The position of the name.
+        position: usize,
+    },
+    /// The query string does not continue with what the grammar allows.
+    #[error("Expected {expected} at position {position}")]
+    Expected {
+        /// A description of what was expected.
+        expected: &'static str,
+        /// The position where it was expected.
+        position: usize,
+    },
+}
+
+/// A term of a parsed query string.
+enum Term {
+    Ref(ComponentId),
+    Mut(ComponentId),
+    With(ComponentId),
+    Without(ComponentId),
+    Added(ComponentId),
+    Changed(ComponentId),
+    Optional(Box<Term>),
+    Or(Vec<Term>),
+    And(Vec<Term>),
+}
+
+impl Term {
+    fn apply<D: QueryData, F: QueryFilter>(&self, builder: &mut QueryBuilder<D, F>) {
+        match self {
+            Term::Ref(id) => {
+                builder.ref_id(*id);
+            }
+            Term::Mut(id) => {
+                builder.mut_id(*id);
+            }
+            Term::With(id) => {
+                builder.with_id(*id);
+            }
+            Term::Without(id) => {
+                builder.without_id(*id);
+            }
+            Term::Added(id) => {
+                builder.added_id(*id);
+            }
+            Term::Changed(id) => {
+                builder.changed_id(*id);
+            }
+            Term::Optional(term) => {
+                builder.optional(|builder| term.apply(builder));
+            }
+            Term::Or(terms) => {
+                builder.or(|builder| terms.iter().for_each(|term| term.apply(builder)));
+            }
+            Term::And(terms) => {
+                builder.and(|builder| terms.iter().for_each(|term| term.apply(builder)));
+            }
+        }
+    }
+}
+
+/// Characters which end a component name which is not quoted.
+const DELIMITERS: &[char] = &[',', '|', '(', ')', '"'];
+
+struct Parser<'a, R: ComponentNameResolver + ?Sized> {
+    input: &'a str,
+    position: usize,
+    resolver: &'a R,
+}
+
+impl<'a, R: ComponentNameResolver + ?Sized> Parser<'a, R> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.position..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        self.rest().starts_with(token)
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        let found = self.peek(token);
+        if found {
+            self.position += token.len();
+        }
+        found
+    }
+
+    fn expect(&mut self, token: &str, expected: &'static str) -> Result<(), QueryParseError> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.expected(expected))
+        }
+    }
+
+    fn expected(&self, expected: &'static str) -> QueryParseError {
+        QueryParseError::Expected {
+            expected,
+            position: self.position,
+        }
+    }
+
+    /// `query := (or ("," or)*)?`, ended by the end of the input or a `)`.
+    fn query(&mut self) -> Result<Vec<Term>, QueryParseError> {
+        let mut terms = vec![self.or()?];
+        while self.eat(",") {
+            terms.push(self.or()?);
+        }
+        Ok(terms)
+    }
+
+    /// `or := term ("||" term)*`
+    fn or(&mut self) -> Result<Term, QueryParseError> {
+        let first = self.term()?;
+        if !self.peek("||") {
+            return Ok(first);
+        }
+        let mut terms = vec![first];
+        while self.eat("||") {
+            terms.push(self.term()?);
+        }
+        Ok(Term::Or(terms))
+    }
+
+    /// `term := "(" query ")" | "?" term | "!" name | "&mut " name | "&" name
+    ///        | ("Added" | "Changed") "(" name ")" | name`
+    fn term(&mut self) -> Result<Term, QueryParseError> {
+        if self.eat("(") {
+            let terms = self.query()?;
+            self.expect(")", "`)`")?;
+            return Ok(Term::And(terms));
+        }
+        if self.eat("?") {
+            return Ok(Term::Optional(Box::new(self.term()?)));
+        }
+        if self.eat("!") {
+            return Ok(Term::Without(self.component()?));
+        }
+        if self.eat("&") {
+            let rest = self.rest();
+            if rest.starts_with("mut") && rest[3..].starts_with(char::is_whitespace) {
+                self.position += 3;
+                return Ok(Term::Mut(self.component()?));
+            }
+            return Ok(Term::Ref(self.component()?));
+        }
+
+        let (name, position) = self.name()?;
+        if !self.eat("(") {
+            return self.resolve(name, position).map(Term::With);
+        }
+        let filter = match name {
+            "Added" => Term::Added,
+            "Changed" => Term::Changed,
+            _ => {
+                return Err(QueryParseError::UnknownFilter {
+                    name: name.to_string(),
+                    position,
+                })
+            }
+        };
+        let id = self.component()?;
+        self.expect(")", "`)`")?;
+        Ok(filter(id))
+    }
+
+    fn component(&mut self) -> Result<ComponentId, QueryParseError> {
+        let (name, position) = self.name()?;
+        self.resolve(name, position)
+    }
+
+    /// `name := '"' [^"]* '"' | [^,|()" \s]+`
+    fn name(&mut self) -> Result<(&'a str, usize), QueryParseError> {
+        self.skip_whitespace();
+        let position = self.position;
+        let rest = self.rest();
+        if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted.find('"').ok_or(QueryParseError::Expected {
+                expected: "a closing `\"`",
+                position: self.input.len(),
+            })?;
+            self.position += end + 2;
+            return Ok((&quoted[..end], position));
+        }
+        let end = rest
+            .find(|c: char| c.is_whitespace() || DELIMITERS.contains(&c))
+            .unwrap_or(rest.len());
+        if end == 0 {
+            return Err(self.expected("a component name"));
+        }
+        self.position += end;
+        Ok((&rest[..end], position))
+    }
+
+    fn resolve(&self, name: &str, position: usize) -> Result<ComponentId, QueryParseError> {
+        self.resolver
+            .resolve(name)
+            .ok_or_else(|| QueryParseError::UnknownComponent {
+                name: name.to_string(),
+                position,
+            })
+    }
+}
+
+/// Parses a query string into its terms, without touching any builder.
+fn parse<R: ComponentNameResolver + ?Sized>(
+    query: &str,
+    resolver: &R,
+) -> Result<Vec<Term>, QueryParseError> {
+    let mut parser = Parser {
+        input: query,
+        position: 0,
+        resolver,
+    };
+    if parser.rest().trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let terms = parser.query()?;
+    parser.skip_whitespace();
+    if !parser.rest().is_empty() {
+        return Err(parser.expected("`,`, `||` or the end of the query"));
+    }
+    Ok(terms)
+}
+
+impl<'w, D: QueryData, F: QueryFilter> QueryBuilder<'w, D, F> {
+    /// Adds the terms of a query string, looking up component names with `resolver`.
+    ///
+    /// A query string is a list of terms separated by `,`, which are all required to match:
+    /// - `&A` reads `A`, and `&mut A` writes it, like [`Self::ref_id`] and [`Self::mut_id`]
+    /// - `A` and `!A` require the entity to have or not have `A`, like [`Self::with_id`] and
+    ///   [`Self::without_id`]
+    /// - `Added(A)` and `Changed(A)` are change filters, like [`Self::added_id`] and
+    ///   [`Self::changed_id`]
+    /// - `?term` makes a term optional, like [`Self::optional`]
+    /// - `term || term` matches either term, like [`Self::or`]
+    /// - `(term, term)` groups terms, like [`Self::and`], so they can be nested in the above
+    ///
+    /// Names containing whitespace or any of `,|()` can be quoted, as in `&"Foo<A, B>"`.
+    ///
+    /// ```
+    /// # use bevy_ecs::{prelude::*, world::FilteredEntityRef};
+    /// # use bevy_utils::HashMap;
+    /// #[derive(Component)]
+    /// struct A;
+    /// #[derive(Component)]
+    /// struct B;
+    ///
+    /// let mut world = World::new();
+    /// let names: HashMap<_, _> = [
+    ///     ("A".to_string(), world.init_component::<A>()),
+    ///     ("B".to_string(), world.init_component::<B>()),
+    /// ]
+    /// .into_iter()
+    /// .collect();
+    /// world.spawn(A);
+    /// world.spawn((A, B));
+    ///
+    /// let mut query = QueryBuilder::<FilteredEntityRef>::new(&mut world)
+    ///     .parse("&A, !B", &names)
+    ///     .unwrap()
+    ///     .build();
+    /// assert_eq!(query.iter(&world).count(), 1);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`QueryParseError`] without adding any terms if the query string is invalid or
+    /// names an unknown component.
+    pub fn parse<R: ComponentNameResolver + ?Sized>(
+        &mut self,
+        query: &str,
+        resolver: &R,
+    ) -> Result<&mut Self, QueryParseError> {
+        let terms = parse(query, resolver)?;
+        terms.iter().for_each(|term| term.apply(self));
+        Ok(self)
+    }
+
+    /// Adds the terms of a query string, looking up components by the name they were registered
+    /// with in the world, see [`Components::get_id_by_name`].
+    ///
+    /// See [`Self::parse`] for the syntax.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`QueryParseError`] without adding any terms if the query string is invalid or
+    /// names an unknown component.
+    pub fn parse_registered(&mut self, query: &str) -> Result<&mut Self, QueryParseError> {
+        let terms = parse(query, self.world().components())?;
+        terms.iter().for_each(|term| term.apply(self));
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_utils::HashMap;
+
+    use crate as bevy_ecs;
+    use crate::component::ComponentId;
+    use crate::prelude::*;
+    use crate::world::FilteredEntityRef;
+
+    use super::{QueryBuilder, QueryParseError};
+
+    #[derive(Component)]
+    struct A(usize);
+
+    #[derive(Component)]
+    struct B(usize);
+
+    #[derive(Component)]
+    struct C(usize);
+
+    fn names(world: &mut World) -> HashMap<String, ComponentId> {
+        [
+            ("A".to_string(), world.init_component::<A>()),
+            ("B".to_string(), world.init_component::<B>()),
+            ("C".to_string(), world.init_component::<C>()),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    fn count(world: &mut World, names: &HashMap<String, ComponentId>, query: &str) -> usize {
+        let mut query = QueryBuilder::<Entity>::new(world)
+            .parse(query, names)
+            .unwrap()
+            .build();
+        query.iter(world).count()
+    }
+
+    #[test]
+    fn parse_terms() {
+        let mut world = World::new();
+        let names = names(&mut world);
+        world.spawn(A(0));
+        world.spawn((A(0), B(0)));
+        world.spawn((A(0), B(0), C(0)));
+        world.spawn((B(0), C(0)));
+
+        assert_eq!(count(&mut world, &names, ""), 4);
+        assert_eq!(count(&mut world, &names, "&A"), 3);
+        assert_eq!(count(&mut world, &names, "&A, &mut B"), 2);
+        assert_eq!(count(&mut world, &names, "A, !C"), 2);
+        assert_eq!(count(&mut world, &names, "&A, ?&mut C"), 3);
+        assert_eq!(count(&mut world, &names, "&B, !A || C"), 2);
+        assert_eq!(count(&mut world, &names, "(A, !B) || (B, !A)"), 2);
+        assert_eq!(count(&mut world, &names, " \"A\" , ( (C) ) "), 1);
+    }
+
+    #[test]
+    fn parse_access() {
+        let mut world = World::new();
+        let names = names(&mut world);
+        let query = QueryBuilder::<FilteredEntityRef>::new(&mut world)
+            .parse("&A, &mut B, ?&C", &names)
+            .unwrap()
+            .build();
+        let access = query.component_access.access();
+        assert!(access.has_read(names["A"]) && !access.has_write(names["A"]));
+        assert!(access.has_write(names["B"]));
+        assert!(access.has_read(names["C"]));
+        assert!(!query.component_access.required().any(|id| id == names["C"]));
+    }
+
+    #[test]
+    fn parse_change_filters() {
+        let mut world = World::new();
+        let names = names(&mut world);
+        let entity = world.spawn((A(0), B(0))).id();
+        world.clear_trackers();
+
+        let mut changed = QueryBuilder::<Entity>::new(&mut world)
+            .parse("Changed(A) || Added(C)", &names)
+            .unwrap()
+            .build();
+        assert_eq!(changed.iter(&world).count(), 0);
+
+        world.get_mut::<A>(entity).unwrap().0 = 1;
+        assert_eq!(changed.iter(&world).collect::<Vec<_>>(), vec![entity]);
+        world.clear_trackers();
+
+        world.entity_mut(entity).insert(C(0));
+        assert_eq!(changed.iter(&world).collect::<Vec<_>>(), vec![entity]);
+    }
+
+    #[test]
+    fn parse_registered_names() {
+        let mut world = World::new();
+        world.init_component::<C>();
+        world.spawn((A(0), B(0)));
+        let name = std::any::type_name::<A>();
+        let mut query = QueryBuilder::<Entity>::new(&mut world)
+            .parse_registered(&format!("&{name}, !\"{}\"", std::any::type_name::<C>()))
+            .unwrap_or_else(|error| panic!("{error}"))
+            .build();
+        assert_eq!(query.iter(&world).count(), 1);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let mut world = World::new();
+        let names = names(&mut world);
+        let mut builder = QueryBuilder::<Entity>::new(&mut world);
+        let mut error = |query: &str| builder.parse(query, &names).err().unwrap();
+
+        assert_eq!(
+            error("&A, &D"),
+            QueryParseError::UnknownComponent {
+                name: "D".to_string(),
+                position: 5
+            }
+        );
+        assert_eq!(
+            error("Removed(A)"),
+            QueryParseError::UnknownFilter {
+                name: "Removed".to_string(),
+                position: 0
+            }
+        );
+        assert_eq!(
+            error("(A, B"),
+            QueryParseError::Expected {
+                expected: "`)`",
+                position: 5
+            }
+        );
+        assert_eq!(
+            error("A,"),
+            QueryParseError::Expected {
+                expected: "a component name",
+                position: 2
+            }
+        );
+        assert_eq!(
+            error("A B"),
+            QueryParseError::Expected {
+                expected: "`,`, `||` or the end of the query",
+                position: 2
+            }
+        );
+        assert_eq!(
+            error("&\"A"),
+            QueryParseError::Expected {
+                expected: "a closing `\"`",
+                position: 3
+            }
+        );
+        assert_eq!(builder.build().iter(&world).count(), 0);
+    }
+}
@@ -6,8 +6,8 @@ use crate::{
     query::{Access, DebugCheckedUnwrap, FilteredAccess, WorldQuery},
     storage::{ComponentSparseSet, Table, TableRow},
     world::{
-        unsafe_world_cell::UnsafeWorldCell, EntityMut, EntityRef, FilteredEntityMut,
-        FilteredEntityRef, Mut, Ref, World,
+        unsafe_world_cell::{UnsafeEntityCell, UnsafeWorldCell},
+        EntityMut, EntityRef, FilteredEntityMut, FilteredEntityRef, Mut, Ref, World,
     },
 };
 use bevy_ptr::{ThinSlicePtr, UnsafeCellDeref};
@@ -497,9 +497,97 @@ unsafe impl<'a> QueryData for EntityMut<'a> {
     type ReadOnly = EntityRef<'a>;
 }
 
+/// The fetch of [`FilteredEntityRef`] and [`FilteredEntityMut`].
+#[doc(hidden)]
+#[derive(Clone)]
+pub struct FilteredEntityFetch<'w> {
+    world: UnsafeWorldCell<'w>,
+    // The accessed components of the current table or archetype
+    access: Access<ComponentId>,
+    // When iterating a table, the accessed components stored in sparse sets, with whether they
+    // are written to, as they are only accessed for the entities which have them
+    sparse: Vec<(ComponentId, bool)>,
+}
+
+impl<'w> FilteredEntityFetch<'w> {
+    fn new(world: UnsafeWorldCell<'w>, access: Access<ComponentId>) -> Self {
+        Self {
+            world,
+            access,
+            sparse: Vec::new(),
+        }
+    }
+
+    fn set_archetype(&mut self, state: &Access<ComponentId>, archetype: &Archetype, write: bool) {
+        let mut access = Access::default();
+        state.reads().for_each(|id| {
+            if archetype.contains(id) {
+                access.add_read(id);
+            }
+        });
+        if write {
+            state.writes().for_each(|id| {
+                if archetype.contains(id) {
+                    access.add_write(id);
+                }
+            });
+        }
+        self.access = access;
+        self.sparse.clear();
+    }
+
+    fn set_table(&mut self, state: &Access<ComponentId>, table: &Table, write: bool) {
+        let components = self.world.components();
+        let is_sparse = |id| {
+            components
+                .get_info(id)
+                .is_some_and(|info| info.storage_type() == StorageType::SparseSet)
+        };
+        let mut access = Access::default();
+        self.sparse.clear();
+        for id in state.reads() {
+            if table.has_column(id) {
+                access.add_read(id);
+            } else if is_sparse(id) {
+                self.sparse.push((id, false));
+            }
+        }
+        if write {
+            for id in state.writes() {
+                if table.has_column(id) {
+                    access.add_write(id);
+                } else if is_sparse(id) {
+                    self.sparse.push((id, true));
+                }
+            }
+        }
+        self.access = access;
+    }
+
+    /// Returns the [`UnsafeEntityCell`] of `entity`, with the access to its components.
+    ///
+    /// # Safety
+    /// `entity` must exist in the world.
+    unsafe fn get(&self, entity: Entity) -> (UnsafeEntityCell<'w>, Access<ComponentId>) {
+        // SAFETY: the caller ensures the entity exists
+        let cell = unsafe { self.world.get_entity(entity).debug_checked_unwrap() };
+        let mut access = self.access.clone();
+        for &(id, write) in &self.sparse {
+            if cell.contains_id(id) {
+                if write {
+                    access.add_write(id);
+                } else {
+                    access.add_read(id);
+                }
+            }
+        }
+        (cell, access)
+    }
+}
+
 /// SAFETY: The accesses of `Self::ReadOnly` are a subset of the accesses of `Self`
 unsafe impl<'a> WorldQuery for FilteredEntityRef<'a> {
-    type Fetch<'w> = (UnsafeWorldCell<'w>, Access<ComponentId>);
+    type Fetch<'w> = FilteredEntityFetch<'w>;
     type Item<'w> = FilteredEntityRef<'w>;
     type State = FilteredAccess<ComponentId>;
 
@@ -507,7 +595,7 @@ unsafe impl<'a> WorldQuery for FilteredEntityRef<'a> {
         item
     }
 
-    const IS_DENSE: bool = false;
+    const IS_DENSE: bool = true;
 
     unsafe fn init_fetch<'w>(
         world: UnsafeWorldCell<'w>,
@@ -517,7 +605,7 @@ unsafe impl<'a> WorldQuery for FilteredEntityRef<'a> {
     ) -> Self::Fetch<'w> {
         let mut access = Access::default();
         access.read_all();
-        (world, access)
+        FilteredEntityFetch::new(world, access)
     }
 
     #[inline]
@@ -527,24 +615,12 @@ unsafe impl<'a> WorldQuery for FilteredEntityRef<'a> {
         archetype: &'w Archetype,
         _table: &Table,
     ) {
-        let mut access = Access::default();
-        state.access.reads().for_each(|id| {
-            if archetype.contains(id) {
-                access.add_read(id);
-            }
-        });
-        fetch.1 = access;
+        fetch.set_archetype(&state.access, archetype, false);
     }
 
     #[inline]
     unsafe fn set_table<'w>(fetch: &mut Self::Fetch<'w>, state: &Self::State, table: &'w Table) {
-        let mut access = Access::default();
-        state.access.reads().for_each(|id| {
-            if table.has_column(id) {
-                access.add_read(id);
-            }
-        });
-        fetch.1 = access;
+        fetch.set_table(&state.access, table, false);
     }
 
     #[inline]
@@ -555,14 +631,14 @@ unsafe impl<'a> WorldQuery for FilteredEntityRef<'a> {
 
     #[inline(always)]
     unsafe fn fetch<'w>(
-        (world, access): &mut Self::Fetch<'w>,
+        fetch: &mut Self::Fetch<'w>,
         entity: Entity,
         _table_row: TableRow,
     ) -> Self::Item<'w> {
         // SAFETY: `fetch` must be called with an entity that exists in the world
-        let cell = world.get_entity(entity).debug_checked_unwrap();
-        // SAFETY: mutable access to every component has been registered.
-        FilteredEntityRef::new(cell, access.clone())
+        let (cell, access) = fetch.get(entity);
+        // SAFETY: read access to every component has been registered.
+        FilteredEntityRef::new(cell, access)
     }
 
     fn update_component_access(
@@ -602,7 +678,7 @@ unsafe impl ReadOnlyQueryData for FilteredEntityRef<'_> {}
 
 /// SAFETY: The accesses of `Self::ReadOnly` are a subset of the accesses of `Self`
 unsafe impl<'a> WorldQuery for FilteredEntityMut<'a> {
-    type Fetch<'w> = (UnsafeWorldCell<'w>, Access<ComponentId>);
+    type Fetch<'w> = FilteredEntityFetch<'w>;
     type Item<'w> = FilteredEntityMut<'w>;
     type State = FilteredAccess<ComponentId>;
 
@@ -610,7 +686,7 @@ unsafe impl<'a> WorldQuery for FilteredEntityMut<'a> {
         item
     }
 
-    const IS_DENSE: bool = false;
+    const IS_DENSE: bool = true;
 
     unsafe fn init_fetch<'w>(
         world: UnsafeWorldCell<'w>,
@@ -620,7 +696,7 @@ unsafe impl<'a> WorldQuery for FilteredEntityMut<'a> {
     ) -> Self::Fetch<'w> {
         let mut access = Access::default();
         access.write_all();
-        (world, access)
+        FilteredEntityFetch::new(world, access)
     }
 
     #[inline]
@@ -630,34 +706,12 @@ unsafe impl<'a> WorldQuery for FilteredEntityMut<'a> {
         archetype: &'w Archetype,
         _table: &Table,
     ) {
-        let mut access = Access::default();
-        state.access.reads().for_each(|id| {
-            if archetype.contains(id) {
-                access.add_read(id);
-            }
-        });
-        state.access.writes().for_each(|id| {
-            if archetype.contains(id) {
-                access.add_write(id);
-            }
-        });
-        fetch.1 = access;
+        fetch.set_archetype(&state.access, archetype, true);
     }
 
     #[inline]
     unsafe fn set_table<'w>(fetch: &mut Self::Fetch<'w>, state: &Self::State, table: &'w Table) {
-        let mut access = Access::default();
-        state.access.reads().for_each(|id| {
-            if table.has_column(id) {
-                access.add_read(id);
-            }
-        });
-        state.access.writes().for_each(|id| {
-            if table.has_column(id) {
-                access.add_write(id);
-            }
-        });
-        fetch.1 = access;
+        fetch.set_table(&state.access, table, true);
     }
 
     #[inline]
@@ -667,14 +721,14 @@ unsafe impl<'a> WorldQuery for FilteredEntityMut<'a> {
 
     #[inline(always)]
     unsafe fn fetch<'w>(
-        (world, access): &mut Self::Fetch<'w>,
+        fetch: &mut Self::Fetch<'w>,
         entity: Entity,
         _table_row: TableRow,
     ) -> Self::Item<'w> {
         // SAFETY: `fetch` must be called with an entity that exists in the world
-        let cell = world.get_entity(entity).debug_checked_unwrap();
+        let (cell, access) = fetch.get(entity);
         // SAFETY: mutable access to every component has been registered.
-        FilteredEntityMut::new(cell, access.clone())
+        FilteredEntityMut::new(cell, access)
     }
 
     fn update_component_access(
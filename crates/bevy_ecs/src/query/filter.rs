@@ -2,8 +2,8 @@ use crate::{
     archetype::Archetype,
     component::{Component, ComponentId, ComponentStorage, StorageType, Tick},
     entity::Entity,
-    query::{DebugCheckedUnwrap, FilteredAccess, WorldQuery},
-    storage::{Column, ComponentSparseSet, Table, TableRow},
+    query::{AccessFilters, DebugCheckedUnwrap, FilteredAccess, WorldQuery},
+    storage::{Column, ComponentSparseSet, SparseSetIndex, SparseSets, Table, TableRow},
     world::{unsafe_world_cell::UnsafeWorldCell, World},
 };
 use bevy_ptr::{ThinSlicePtr, UnsafeCellDeref};
@@ -898,6 +898,146 @@ impl<T: Component> QueryFilter for Changed<T> {
     }
 }
 
+/// A change detection filter on a component only known at runtime, the dynamic equivalent of
+/// [`Added`] and [`Changed`].
+///
+/// These are added with [`QueryBuilder::added_id`](crate::query::QueryBuilder::added_id) and
+/// [`QueryBuilder::changed_id`](crate::query::QueryBuilder::changed_id), and checked for every
+/// entity in addition to the query's static filter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum DynamicChangeFilter {
+    /// Matches entities whose component was added since the last run.
+    Added(ComponentId),
+    /// Matches entities whose component was added or mutably dereferenced since the last run.
+    Changed(ComponentId),
+    /// Matches entities which do not have the components of the inner filters, or pass them.
+    Optional(Vec<DynamicChangeFilter>),
+    /// Matches entities which match any of the branches.
+    Or(Vec<DynamicChangeBranch>),
+}
+
+/// One branch of a [`DynamicChangeFilter::Or`], made from a term added inside
+/// [`QueryBuilder::or`](crate::query::QueryBuilder::or).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct DynamicChangeBranch {
+    /// The `With` and `Without` filters of the term, in disjunctive normal form.
+    pub(crate) filter_sets: Vec<AccessFilters<ComponentId>>,
+    /// The change filters of the term.
+    pub(crate) filters: Vec<DynamicChangeFilter>,
+}
+
+/// The [`DynamicChangeFilter`]s of a query, ready to be checked against entities of a world.
+#[derive(Clone)]
+pub(crate) struct DynamicChangeFetch<'w, 's> {
+    filters: &'s [DynamicChangeFilter],
+    table: Option<&'w Table>,
+    sparse_sets: &'w SparseSets,
+    last_run: Tick,
+    this_run: Tick,
+}
+
+impl<'w, 's> DynamicChangeFetch<'w, 's> {
+    /// # Safety
+    /// - `world` must have permission to read the ticks of every component in `filters`.
+    pub(crate) unsafe fn new(
+        world: UnsafeWorldCell<'w>,
+        filters: &'s [DynamicChangeFilter],
+        last_run: Tick,
+        this_run: Tick,
+    ) -> Self {
+        Self {
+            filters,
+            table: None,
+            sparse_sets: &world.storages().sparse_sets,
+            last_run,
+            this_run,
+        }
+    }
+
+    /// Adjusts the fetch to the entities stored in `table`.
+    #[inline]
+    pub(crate) fn set_table(&mut self, table: &'w Table) {
+        self.table = Some(table);
+    }
+
+    /// Returns true if `entity`, stored at `table_row` of the current table, passes every filter.
+    ///
+    /// Entities missing a filtered component never pass, unless the filter is optional.
+    ///
+    /// # Safety
+    /// - Must always be called _after_ [`Self::set_table`], with the table of `entity`.
+    #[inline(always)]
+    pub(crate) unsafe fn filter_fetch(&self, entity: Entity, table_row: TableRow) -> bool {
+        if self.filters.is_empty() {
+            return true;
+        }
+        let table = self.table.debug_checked_unwrap();
+        self.filters
+            .iter()
+            .all(|filter| self.matches(filter, table, entity, table_row, false))
+    }
+
+    fn matches(
+        &self,
+        filter: &DynamicChangeFilter,
+        table: &Table,
+        entity: Entity,
+        table_row: TableRow,
+        optional: bool,
+    ) -> bool {
+        let (id, added) = match filter {
+            DynamicChangeFilter::Added(id) => (*id, true),
+            DynamicChangeFilter::Changed(id) => (*id, false),
+            DynamicChangeFilter::Optional(filters) => {
+                return filters
+                    .iter()
+                    .all(|filter| self.matches(filter, table, entity, table_row, true));
+            }
+            DynamicChangeFilter::Or(branches) => {
+                let mut matched = branches.iter().filter(|branch| {
+                    branch.filter_sets.iter().any(|set| {
+                        set.with.ones().all(|index| {
+                            self.contains(table, entity, ComponentId::get_sparse_set_index(index))
+                        }) && set.without.ones().all(|index| {
+                            !self.contains(table, entity, ComponentId::get_sparse_set_index(index))
+                        })
+                    })
+                });
+                // An optional filter passes entities which match none of the branches
+                let Some(first) = matched.next() else {
+                    return optional;
+                };
+                return std::iter::once(first).chain(matched).any(|branch| {
+                    branch
+                        .filters
+                        .iter()
+                        .all(|filter| self.matches(filter, table, entity, table_row, optional))
+                });
+            }
+        };
+        let ticks = match table.get_column(id) {
+            Some(column) => column.get_ticks(table_row),
+            None => self
+                .sparse_sets
+                .get(id)
+                .and_then(|sparse_set| sparse_set.get_ticks(entity)),
+        };
+        match ticks {
+            Some(ticks) if added => ticks.is_added(self.last_run, self.this_run),
+            Some(ticks) => ticks.is_changed(self.last_run, self.this_run),
+            None => optional,
+        }
+    }
+
+    fn contains(&self, table: &Table, entity: Entity, id: ComponentId) -> bool {
+        table.has_column(id)
+            || self
+                .sparse_sets
+                .get(id)
+                .is_some_and(|sparse_set| sparse_set.contains(entity))
+    }
+}
+
 /// A marker trait to indicate that the filter works at an archetype level.
 ///
 /// This is needed to implement [`ExactSizeIterator`] for
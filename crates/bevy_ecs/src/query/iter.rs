@@ -2,7 +2,7 @@ use crate::{
     archetype::{Archetype, ArchetypeEntity, ArchetypeId, Archetypes},
     component::Tick,
     entity::{Entities, Entity},
-    query::{ArchetypeFilter, DebugCheckedUnwrap, QueryState},
+    query::{ArchetypeFilter, DebugCheckedUnwrap, DynamicChangeFetch, QueryState},
     storage::{Table, TableId, TableRow, Tables},
     world::unsafe_world_cell::UnsafeWorldCell,
 };
@@ -46,7 +46,7 @@ impl<'w, 's, D: QueryData, F: QueryFilter> QueryIter<'w, 's, D, F> {
     /// # Safety
     ///  - all `rows` must be in `[0, table.entity_count)`.
     ///  - `table` must match D and F
-    ///  - Both `D::IS_DENSE` and `F::IS_DENSE` must be true.
+    ///  - The query state must be dense.
     #[inline]
     #[cfg(all(not(target = "wasm32"), feature = "multi-threaded"))]
     pub(super) unsafe fn for_each_in_table_range<Func>(
@@ -57,7 +57,7 @@ impl<'w, 's, D: QueryData, F: QueryFilter> QueryIter<'w, 's, D, F> {
     ) where
         Func: FnMut(D::Item<'w>),
     {
-        // SAFETY: Caller assures that D::IS_DENSE and F::IS_DENSE are true, that table matches D and F
+        // SAFETY: Caller assures that the query state is dense, that table matches D and F
         // and all indicies in rows are in range.
         unsafe {
             self.fold_over_table_range((), &mut |_, item| func(item), table, rows);
@@ -70,7 +70,7 @@ impl<'w, 's, D: QueryData, F: QueryFilter> QueryIter<'w, 's, D, F> {
     /// # Safety
     ///  - all `indices` must be in `[0, archetype.len())`.
     ///  - `archetype` must match D and F
-    ///  - Either `D::IS_DENSE` or `F::IS_DENSE` must be false.
+    ///  - The query state must not be dense.
     #[inline]
     #[cfg(all(not(target = "wasm32"), feature = "multi-threaded"))]
     pub(super) unsafe fn for_each_in_archetype_range<Func>(
@@ -81,7 +81,7 @@ impl<'w, 's, D: QueryData, F: QueryFilter> QueryIter<'w, 's, D, F> {
     ) where
         Func: FnMut(D::Item<'w>),
     {
-        // SAFETY: Caller assures that either D::IS_DENSE or F::IS_DENSE are false, that archetype matches D and F
+        // SAFETY: Caller assures that the query state is not dense, that archetype matches D and F
         // and all indices in rows are in range.
         unsafe {
             self.fold_over_archetype_range((), &mut |_, item| func(item), archetype, rows);
@@ -94,7 +94,7 @@ impl<'w, 's, D: QueryData, F: QueryFilter> QueryIter<'w, 's, D, F> {
     /// # Safety
     ///  - all `rows` must be in `[0, table.entity_count)`.
     ///  - `table` must match D and F
-    ///  - Both `D::IS_DENSE` and `F::IS_DENSE` must be true.
+    ///  - The query state must be dense.
     #[inline]
     pub(super) unsafe fn fold_over_table_range<B, Func>(
         &mut self,
@@ -117,6 +117,7 @@ impl<'w, 's, D: QueryData, F: QueryFilter> QueryIter<'w, 's, D, F> {
             &self.query_state.filter_state,
             table,
         );
+        self.cursor.changes.set_table(table);
 
         let entities = table.entities();
         for row in rows {
@@ -125,7 +126,9 @@ impl<'w, 's, D: QueryData, F: QueryFilter> QueryIter<'w, 's, D, F> {
             let row = TableRow::from_usize(row);
             // SAFETY: set_table was called prior.
             // Caller assures `row` in range of the current archetype.
-            if !F::filter_fetch(&mut self.cursor.filter, *entity, row) {
+            if !F::filter_fetch(&mut self.cursor.filter, *entity, row)
+                || !self.cursor.changes.filter_fetch(*entity, row)
+            {
                 continue;
             }
 
@@ -144,7 +147,7 @@ impl<'w, 's, D: QueryData, F: QueryFilter> QueryIter<'w, 's, D, F> {
     /// # Safety
     ///  - all `indices` must be in `[0, archetype.len())`.
     ///  - `archetype` must match D and F
-    ///  - Either `D::IS_DENSE` or `F::IS_DENSE` must be false.
+    ///  - The query state must not be dense.
     #[inline]
     pub(super) unsafe fn fold_over_archetype_range<B, Func>(
         &mut self,
@@ -169,6 +172,7 @@ impl<'w, 's, D: QueryData, F: QueryFilter> QueryIter<'w, 's, D, F> {
             archetype,
             table,
         );
+        self.cursor.changes.set_table(table);
 
         let entities = archetype.entities();
         for index in indices {
@@ -180,7 +184,11 @@ impl<'w, 's, D: QueryData, F: QueryFilter> QueryIter<'w, 's, D, F> {
                 &mut self.cursor.filter,
                 archetype_entity.id(),
                 archetype_entity.table_row(),
-            ) {
+            ) || !self
+                .cursor
+                .changes
+                .filter_fetch(archetype_entity.id(), archetype_entity.table_row())
+            {
                 continue;
             }
 
@@ -214,7 +222,7 @@ impl<'w, 's, D: QueryData, F: QueryFilter> Iterator for QueryIter<'w, 's, D, F>
 
     fn size_hint(&self) -> (usize, Option<usize>) {
         let max_size = self.cursor.max_remaining(self.tables, self.archetypes);
-        let archetype_query = F::IS_ARCHETYPAL;
+        let archetype_query = F::IS_ARCHETYPAL && self.query_state.change_filters.is_empty();
         let min_size = if archetype_query { max_size } else { 0 };
         (min_size, Some(max_size))
     }
@@ -230,7 +238,7 @@ impl<'w, 's, D: QueryData, F: QueryFilter> Iterator for QueryIter<'w, 's, D, F>
             let Some(item) = self.next() else { break };
             accum = func(accum, item);
         }
-        if D::IS_DENSE && F::IS_DENSE {
+        if self.query_state.is_dense {
             for table_id in self.cursor.table_id_iter.clone() {
                 // SAFETY: Matched table IDs are guaranteed to still exist.
                 let table = unsafe { self.tables.get(*table_id).debug_checked_unwrap() };
@@ -238,7 +246,7 @@ impl<'w, 's, D: QueryData, F: QueryFilter> Iterator for QueryIter<'w, 's, D, F>
                     // SAFETY: 
                     // - The fetched table matches both D and F
                     // - The provided range is equivalent to [0, table.entity_count)
-                    // - The if block ensures that D::IS_DENSE and F::IS_DENSE are both true
+                    // - The if block ensures that the query state is dense
                     unsafe { self.fold_over_table_range(accum, &mut func, table, 0..table.entity_count()) };
             }
         } else {
@@ -250,7 +258,7 @@ impl<'w, 's, D: QueryData, F: QueryFilter> Iterator for QueryIter<'w, 's, D, F>
                     // SAFETY:
                     // - The fetched archetype matches both D and F
                     // - The provided range is equivalent to [0, archetype.len)
-                    // - The if block ensures that ether D::IS_DENSE or F::IS_DENSE are false
+                    // - The if block ensures that the query state is not dense
                     unsafe { self.fold_over_archetype_range(accum, &mut func, archetype, 0..archetype.len()) };
             }
         }
@@ -277,6 +285,7 @@ where
     archetypes: &'w Archetypes,
     fetch: D::Fetch<'w>,
     filter: F::Fetch<'w>,
+    changes: DynamicChangeFetch<'w, 's>,
     query_state: &'s QueryState<D, F>,
 }
 
@@ -296,6 +305,8 @@ where
     ) -> QueryManyIter<'w, 's, D, F, I> {
         let fetch = D::init_fetch(world, &query_state.fetch_state, last_run, this_run);
         let filter = F::init_fetch(world, &query_state.filter_state, last_run, this_run);
+        let changes =
+            DynamicChangeFetch::new(world, &query_state.change_filters, last_run, this_run);
         QueryManyIter {
             query_state,
             entities: world.entities(),
@@ -305,6 +316,7 @@ where
             tables: &world.storages().tables,
             fetch,
             filter,
+            changes,
             entity_iter: entity_list.into_iter(),
         }
     }
@@ -353,10 +365,13 @@ where
                 archetype,
                 table,
             );
+            self.changes.set_table(table);
 
             // SAFETY: set_archetype was called prior.
             // `location.archetype_row` is an archetype index row in range of the current archetype, because if it was not, the match above would have `continue`d
-            if F::filter_fetch(&mut self.filter, entity, location.table_row) {
+            if F::filter_fetch(&mut self.filter, entity, location.table_row)
+                && self.changes.filter_fetch(entity, location.table_row)
+            {
                 // SAFETY:
                 // - set_archetype was called prior, `location.archetype_row` is an archetype index in range of the current archetype
                 // - fetch is only called once for each entity.
@@ -614,7 +629,7 @@ impl<'w, 's, D: ReadOnlyQueryData, F: QueryFilter, const K: usize> Iterator
                 Some(acc + choose(n, K - i)?)
             });
 
-        let archetype_query = F::IS_ARCHETYPAL;
+        let archetype_query = F::IS_ARCHETYPAL && self.query_state.change_filters.is_empty();
         let known_max = max_combinations.unwrap_or(usize::MAX);
         let min_combinations = if archetype_query { known_max } else { 0 };
         (min_combinations, max_combinations)
@@ -643,9 +658,12 @@ struct QueryIterationCursor<'w, 's, D: QueryData, F: QueryFilter> {
     archetype_entities: &'w [ArchetypeEntity],
     fetch: D::Fetch<'w>,
     filter: F::Fetch<'w>,
-    // length of the table table or length of the archetype, depending on whether both `D`'s and `F`'s fetches are dense
+    changes: DynamicChangeFetch<'w, 's>,
+    // whether the query state is dense, see `QueryState::is_dense`
+    is_dense: bool,
+    // length of the table table or length of the archetype, depending on whether the query state is dense
     current_len: usize,
-    // either table row or archetype index, depending on whether both `D`'s and `F`'s fetches are dense
+    // either table row or archetype index, depending on whether the query state is dense
     current_row: usize,
 }
 
@@ -658,6 +676,8 @@ impl<D: QueryData, F: QueryFilter> Clone for QueryIterationCursor<'_, '_, D, F>
             archetype_entities: self.archetype_entities,
             fetch: self.fetch.clone(),
             filter: self.filter.clone(),
+            changes: self.changes.clone(),
+            is_dense: self.is_dense,
             current_len: self.current_len,
             current_row: self.current_row,
         }
@@ -665,8 +685,6 @@ impl<D: QueryData, F: QueryFilter> Clone for QueryIterationCursor<'_, '_, D, F>
 }
 
 impl<'w, 's, D: QueryData, F: QueryFilter> QueryIterationCursor<'w, 's, D, F> {
-    const IS_DENSE: bool = D::IS_DENSE && F::IS_DENSE;
-
     unsafe fn init_empty(
         world: UnsafeWorldCell<'w>,
         query_state: &'s QueryState<D, F>,
@@ -691,9 +709,13 @@ impl<'w, 's, D: QueryData, F: QueryFilter> QueryIterationCursor<'w, 's, D, F> {
     ) -> Self {
         let fetch = D::init_fetch(world, &query_state.fetch_state, last_run, this_run);
         let filter = F::init_fetch(world, &query_state.filter_state, last_run, this_run);
+        let changes =
+            DynamicChangeFetch::new(world, &query_state.change_filters, last_run, this_run);
         QueryIterationCursor {
             fetch,
             filter,
+            changes,
+            is_dense: query_state.is_dense,
             table_entities: &[],
             archetype_entities: &[],
             table_id_iter: query_state.matched_table_ids.iter(),
@@ -708,7 +730,7 @@ impl<'w, 's, D: QueryData, F: QueryFilter> QueryIterationCursor<'w, 's, D, F> {
     unsafe fn peek_last(&mut self) -> Option<D::Item<'w>> {
         if self.current_row > 0 {
             let index = self.current_row - 1;
-            if Self::IS_DENSE {
+            if self.is_dense {
                 let entity = self.table_entities.get_unchecked(index);
                 Some(D::fetch(
                     &mut self.fetch,
@@ -733,7 +755,7 @@ impl<'w, 's, D: QueryData, F: QueryFilter> QueryIterationCursor<'w, 's, D, F> {
     /// Note that if `D::IS_ARCHETYPAL && F::IS_ARCHETYPAL`, the return value
     /// will be **the exact count of remaining values**.
     fn max_remaining(&self, tables: &'w Tables, archetypes: &'w Archetypes) -> usize {
-        let remaining_matched: usize = if Self::IS_DENSE {
+        let remaining_matched: usize = if self.is_dense {
             let ids = self.table_id_iter.clone();
             ids.map(|id| tables[*id].entity_count()).sum()
         } else {
@@ -756,7 +778,7 @@ impl<'w, 's, D: QueryData, F: QueryFilter> QueryIterationCursor<'w, 's, D, F> {
         archetypes: &'w Archetypes,
         query_state: &'s QueryState<D, F>,
     ) -> Option<D::Item<'w>> {
-        if Self::IS_DENSE {
+        if self.is_dense {
             loop {
                 // we are on the beginning of the query, or finished processing a table, so skip to the next
                 if self.current_row == self.current_len {
@@ -766,6 +788,7 @@ impl<'w, 's, D: QueryData, F: QueryFilter> QueryIterationCursor<'w, 's, D, F> {
                     // `fetch_state`/`filter_state` are the states that `fetch/filter` were initialized with
                     D::set_table(&mut self.fetch, &query_state.fetch_state, table);
                     F::set_table(&mut self.filter, &query_state.filter_state, table);
+                    self.changes.set_table(table);
                     self.table_entities = table.entities();
                     self.current_len = table.entity_count();
                     self.current_row = 0;
@@ -776,7 +799,9 @@ impl<'w, 's, D: QueryData, F: QueryFilter> QueryIterationCursor<'w, 's, D, F> {
                 // `current_row` is a table row in range of the current table, because if it was not, then the if above would have been executed.
                 let entity = self.table_entities.get_unchecked(self.current_row);
                 let row = TableRow::from_usize(self.current_row);
-                if !F::filter_fetch(&mut self.filter, *entity, row) {
+                if !F::filter_fetch(&mut self.filter, *entity, row)
+                    || !self.changes.filter_fetch(*entity, row)
+                {
                     self.current_row += 1;
                     continue;
                 }
@@ -806,6 +831,7 @@ impl<'w, 's, D: QueryData, F: QueryFilter> QueryIterationCursor<'w, 's, D, F> {
                         archetype,
                         table,
                     );
+                    self.changes.set_table(table);
                     self.archetype_entities = archetype.entities();
                     self.current_len = archetype.len();
                     self.current_row = 0;
@@ -819,7 +845,10 @@ impl<'w, 's, D: QueryData, F: QueryFilter> QueryIterationCursor<'w, 's, D, F> {
                     &mut self.filter,
                     archetype_entity.id(),
                     archetype_entity.table_row(),
-                ) {
+                ) || !self
+                    .changes
+                    .filter_fetch(archetype_entity.id(), archetype_entity.table_row())
+                {
                     self.current_row += 1;
                     continue;
                 }
@@ -5,8 +5,8 @@ use crate::{
     entity::Entity,
     prelude::{Component, FromWorld},
     query::{
-        Access, BatchingStrategy, DebugCheckedUnwrap, FilteredAccess, QueryCombinationIter,
-        QueryIter, QueryParIter,
+        Access, BatchingStrategy, DebugCheckedUnwrap, DynamicChangeFetch, DynamicChangeFilter,
+        FilteredAccess, QueryCombinationIter, QueryIter, QueryParIter,
     },
     storage::{SparseSetIndex, TableId},
     world::{unsafe_world_cell::UnsafeWorldCell, World, WorldId},
@@ -39,6 +39,9 @@ pub struct QueryState<D: QueryData, F: QueryFilter = ()> {
     pub(crate) matched_archetype_ids: Vec<ArchetypeId>,
     pub(crate) fetch_state: D::State,
     pub(crate) filter_state: F::State,
+    pub(crate) change_filters: Vec<DynamicChangeFilter>,
+    // whether the matched tables can be iterated densely instead of archetype by archetype
+    pub(crate) is_dense: bool,
     #[cfg(feature = "trace")]
     par_iter_span: Span,
 }
@@ -123,6 +126,8 @@ impl<D: QueryData, F: QueryFilter> QueryState<D, F> {
             matched_archetype_ids: Vec::new(),
             fetch_state,
             filter_state,
+            change_filters: Vec::new(),
+            is_dense: D::IS_DENSE && F::IS_DENSE,
             component_access,
             matched_tables: Default::default(),
             matched_archetypes: Default::default(),
@@ -151,6 +156,9 @@ impl<D: QueryData, F: QueryFilter> QueryState<D, F> {
             matched_archetype_ids: Vec::new(),
             fetch_state,
             filter_state,
+            change_filters: builder.change_filters().to_vec(),
+            // Whether the filters added to the builder are dense is only known at runtime
+            is_dense: D::IS_DENSE && F::IS_DENSE && builder.is_dense(),
             component_access: builder.access().clone(),
             matched_tables: Default::default(),
             matched_archetypes: Default::default(),
@@ -166,6 +174,15 @@ impl<D: QueryData, F: QueryFilter> QueryState<D, F> {
         state
     }
 
+    /// Returns the [`FilteredAccess`] of this query, describing the components it reads, writes
+    /// and filters on.
+    ///
+    /// This lets code which only has the built state, such as editors and debugging tools, show
+    /// what a query built at runtime with [`QueryBuilder`] matches.
+    pub fn component_access(&self) -> &FilteredAccess<ComponentId> {
+        &self.component_access
+    }
+
     /// Checks if the query is empty for the given [`World`], where the last change and current tick are given.
     ///
     /// # Panics
@@ -355,7 +372,7 @@ impl<D: QueryData, F: QueryFilter> QueryState<D, F> {
         NewF::update_component_access(&filter_state, &mut filter_component_access);
 
         component_access.extend(&filter_component_access);
-        assert!(component_access.is_subset(&self.component_access), "Transmuted state for {} attempts to access terms that are not allowed by original state {}.", std::any::type_name::<(NewD, NewF)>(), std::any::type_name::<(D, F)>() );
+        assert!(component_access.is_guaranteed_by(&self.component_access), "Transmuted state for {} attempts to access terms that are not allowed by original state {}.", std::any::type_name::<(NewD, NewF)>(), std::any::type_name::<(D, F)>() );
 
         QueryState {
             world_id: self.world_id,
@@ -364,6 +381,10 @@ impl<D: QueryData, F: QueryFilter> QueryState<D, F> {
             matched_archetype_ids: self.matched_archetype_ids.clone(),
             fetch_state,
             filter_state,
+            change_filters: self.change_filters.clone(),
+            // `NewD` only accesses components of this state, so it can iterate the same tables
+            // unless it reads sparse components itself
+            is_dense: self.is_dense && NewD::IS_DENSE && NewF::IS_DENSE,
             component_access: self.component_access.clone(),
             matched_tables: self.matched_tables.clone(),
             matched_archetypes: self.matched_archetypes.clone(),
@@ -610,6 +631,7 @@ impl<D: QueryData, F: QueryFilter> QueryState<D, F> {
             .debug_checked_unwrap();
         let mut fetch = D::init_fetch(world, &self.fetch_state, last_run, this_run);
         let mut filter = F::init_fetch(world, &self.filter_state, last_run, this_run);
+        let mut changes = DynamicChangeFetch::new(world, &self.change_filters, last_run, this_run);
 
         let table = world
             .storages()
@@ -618,8 +640,11 @@ impl<D: QueryData, F: QueryFilter> QueryState<D, F> {
             .debug_checked_unwrap();
         D::set_archetype(&mut fetch, &self.fetch_state, archetype, table);
         F::set_archetype(&mut filter, &self.filter_state, archetype, table);
+        changes.set_table(table);
 
-        if F::filter_fetch(&mut filter, entity, location.table_row) {
+        if F::filter_fetch(&mut filter, entity, location.table_row)
+            && changes.filter_fetch(entity, location.table_row)
+        {
             Ok(D::fetch(&mut fetch, entity, location.table_row))
         } else {
             Err(QueryEntityError::QueryDoesNotMatch(entity))
@@ -1216,7 +1241,7 @@ impl<D: QueryData, F: QueryFilter> QueryState<D, F> {
         // NOTE: If you are changing query iteration code, remember to update the following places, where relevant:
         // QueryIter, QueryIterationCursor, QueryManyIter, QueryCombinationIter, QueryState::for_each_unchecked_manual, QueryState::par_for_each_unchecked_manual
         bevy_tasks::ComputeTaskPool::get().scope(|scope| {
-            if D::IS_DENSE && F::IS_DENSE {
+            if self.is_dense {
                 // SAFETY: We only access table data that has been registered in `self.archetype_component_access`.
                 let tables = &world.storages().tables;
                 for table_id in &self.matched_table_ids {
@@ -1415,8 +1440,12 @@ impl<D: QueryData, F: QueryFilter> From<QueryBuilder<'_, D, F>> for QueryState<D
 #[cfg(test)]
 mod tests {
     use crate as bevy_ecs;
-    use crate::world::FilteredEntityRef;
-    use crate::{component::Component, prelude::*, query::QueryEntityError};
+    use crate::world::{FilteredEntityMut, FilteredEntityRef};
+    use crate::{
+        component::Component,
+        prelude::*,
+        query::{QueryBuilder, QueryEntityError},
+    };
 
     #[test]
     fn get_many_unchecked_manual_uniqueness() {
@@ -1718,4 +1747,36 @@ mod tests {
 
         assert_eq!(entity_a, detection_query.single(&world));
     }
+
+    #[test]
+    fn component_access_of_built_query() {
+        let mut world = World::new();
+        let component_id_a = world.init_component::<A>();
+        let component_id_b = world.init_component::<B>();
+        let component_id_c = world.init_component::<C>();
+
+        let query = QueryBuilder::<FilteredEntityMut>::new(&mut world)
+            .ref_id(component_id_a)
+            .optional(|builder| {
+                builder.mut_id(component_id_b);
+            })
+            .without_id(component_id_c)
+            .build();
+        let access = query.component_access();
+        assert!(access.access().has_read(component_id_a));
+        assert!(!access.access().has_write(component_id_a));
+        assert!(access.access().has_write(component_id_b));
+        assert_eq!(access.required().collect::<Vec<_>>(), vec![component_id_a]);
+
+        let filter_sets = access.filter_sets();
+        assert_eq!(filter_sets.len(), 1);
+        assert_eq!(
+            filter_sets[0].with().collect::<Vec<_>>(),
+            vec![component_id_a]
+        );
+        assert_eq!(
+            filter_sets[0].without().collect::<Vec<_>>(),
+            vec![component_id_c]
+        );
+    }
 }
@@ -0,0 +1,188 @@
+use std::{alloc::Layout, borrow::Cow, mem::needs_drop};
+
+use bevy_ptr::OwningPtr;
+use bevy_reflect::{FromType, Reflect};
+
+use crate::{
+    component::{ComponentDescriptor, ComponentId, StorageType},
+    world::World,
+};
+
+/// A struct used to register runtime components with the layout and drop function of a reflected type.
+///
+/// A [`ReflectComponentDescriptor`] for type `T` can be obtained via
+/// [`bevy_reflect::TypeRegistration::data`], after registering it with
+/// [`TypeRegistry::register_type_data`](bevy_reflect::TypeRegistry::register_type_data)
+/// or the `#[reflect(ComponentDescriptor)]` attribute.
+///
+/// Unlike [`ComponentDescriptor::new_with_layout`], building a component from this is safe, as the
+/// layout and drop function always match `T`. The registered component does not have a
+/// [`TypeId`](std::any::TypeId), so it is distinct from `T` even if `T` is a [`Component`](crate::component::Component),
+/// and its values can only be accessed by id, e.g. with
+/// [`EntityWorldMut::insert_by_id`](crate::world::EntityWorldMut::insert_by_id) and
+/// [`EntityRef::get_by_id`](crate::world::EntityRef::get_by_id).
+#[derive(Clone)]
+pub struct ReflectComponentDescriptor {
+    name: Cow<'static, str>,
+    storage_type: StorageType,
+    layout: Layout,
+    drop: Option<for<'a> unsafe fn(OwningPtr<'a>)>,
+}
+
+impl ReflectComponentDescriptor {
+    /// Returns a copy of this descriptor with the given name, which defaults to the name of the type.
+    ///
+    /// The name can be used to look up the component with [`Components::get_id_by_name`](crate::component::Components::get_id_by_name).
+    pub fn with_name(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Returns a copy of this descriptor with the given [`StorageType`], which defaults to [`StorageType::Table`].
+    pub fn with_storage_type(mut self, storage_type: StorageType) -> Self {
+        self.storage_type = storage_type;
+        self
+    }
+
+    /// Returns the name the component will be registered with.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the [`StorageType`] the component will be registered with.
+    pub fn storage_type(&self) -> StorageType {
+        self.storage_type
+    }
+
+    /// Returns the [`Layout`] of the reflected type.
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// Builds the [`ComponentDescriptor`] of the component.
+    pub fn descriptor(&self) -> ComponentDescriptor {
+        // SAFETY:
+        // - `drop` was created for the type with `layout`
+        // - `Reflect` types are `Send + Sync`
+        unsafe {
+            ComponentDescriptor::new_with_layout(
+                self.name.clone(),
+                self.storage_type,
+                self.layout,
+                self.drop,
+            )
+        }
+    }
+}
+
+impl<T: Reflect> FromType<T> for ReflectComponentDescriptor {
+    fn from_type() -> Self {
+        // SAFETY: the pointer points to a valid value of type `T` and it is safe to drop this value.
+        unsafe fn drop_ptr<T>(x: OwningPtr<'_>) {
+            x.drop_as::<T>();
+        }
+
+        ReflectComponentDescriptor {
+            name: Cow::Borrowed(std::any::type_name::<T>()),
+            storage_type: StorageType::Table,
+            layout: Layout::new::<T>(),
+            drop: needs_drop::<T>().then_some(drop_ptr::<T> as _),
+        }
+    }
+}
+
+impl World {
+    /// Initializes a new runtime component from a [`ReflectComponentDescriptor`] and returns the
+    /// [`ComponentId`] created for it.
+    ///
+    /// Each call creates a new component, even if one with the same name has already been
+    /// registered.
+    ///
+    /// ```
+    /// # use bevy_ecs::{prelude::*, reflect::ReflectComponentDescriptor};
+    /// # use bevy_reflect::{Reflect, TypeRegistry};
+    /// #[derive(Reflect)]
+    /// struct Speed(f32);
+    ///
+    /// let mut registry = TypeRegistry::default();
+    /// registry.register::<Speed>();
+    /// registry.register_type_data::<Speed, ReflectComponentDescriptor>();
+    ///
+    /// let mut world = World::new();
+    /// let descriptor = registry
+    ///     .get_type_data::<ReflectComponentDescriptor>(std::any::TypeId::of::<Speed>())
+    ///     .unwrap()
+    ///     .clone()
+    ///     .with_name("Speed");
+    /// let id = world.register_dynamic_component(&descriptor);
+    /// assert_eq!(world.components().get_id_by_name("Speed"), Some(id));
+    /// ```
+    pub fn register_dynamic_component(
+        &mut self,
+        descriptor: &ReflectComponentDescriptor,
+    ) -> ComponentId {
+        self.init_component_with_descriptor(descriptor.descriptor())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        any::TypeId,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    use bevy_reflect::{Reflect, TypeRegistry};
+
+    use super::ReflectComponentDescriptor;
+    use crate::{component::StorageType, ptr::OwningPtr, world::World};
+
+    #[derive(Reflect)]
+    struct DropCounter(#[reflect(ignore)] Arc<AtomicUsize>);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn register_dynamic_component() {
+        let mut registry = TypeRegistry::default();
+        registry.register::<DropCounter>();
+        registry.register_type_data::<DropCounter, ReflectComponentDescriptor>();
+        let descriptor = registry
+            .get_type_data::<ReflectComponentDescriptor>(TypeId::of::<DropCounter>())
+            .unwrap()
+            .clone()
+            .with_name("Counter")
+            .with_storage_type(StorageType::SparseSet);
+
+        let mut world = World::new();
+        let id = world.register_dynamic_component(&descriptor);
+        let info = world.components().get_info(id).unwrap();
+        assert_eq!(info.name(), "Counter");
+        assert_eq!(info.storage_type(), StorageType::SparseSet);
+        assert_eq!(info.type_id(), None);
+        assert!(info.drop().is_some());
+
+        let drops = Arc::new(AtomicUsize::new(0));
+        let mut entity = world.spawn_empty();
+        OwningPtr::make(DropCounter(drops.clone()), |ptr| {
+            // SAFETY: `ptr` is a `DropCounter`, the type the component was registered from
+            unsafe { entity.insert_by_id(id, ptr) };
+        });
+        let entity = entity.id();
+        let counter = world.entity(entity).get_by_id(id).unwrap();
+        // SAFETY: the component was registered from `DropCounter`
+        let counter = unsafe { counter.deref::<DropCounter>() };
+        assert!(Arc::ptr_eq(&counter.0, &drops));
+        assert_eq!(drops.load(Ordering::Relaxed), 0);
+
+        world.despawn(entity);
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+    }
+}
@@ -8,12 +8,14 @@ use bevy_reflect::{impl_reflect_value, ReflectDeserialize, ReflectSerialize, Typ
 
 mod bundle;
 mod component;
+mod component_descriptor;
 mod entity_commands;
 mod map_entities;
 mod resource;
 
 pub use bundle::{ReflectBundle, ReflectBundleFns};
 pub use component::{ReflectComponent, ReflectComponentFns};
+pub use component_descriptor::ReflectComponentDescriptor;
 pub use entity_commands::ReflectCommandExt;
 pub use map_entities::ReflectMapEntities;
 pub use resource::{ReflectResource, ReflectResourceFns};
@@ -25,6 +25,7 @@ mod resource;
 mod sparse_set;
 mod table;
 
+pub(crate) use blob_vec::BlobVec;
 pub use resource::*;
 pub use sparse_set::*;
 pub use table::*;
@@ -0,0 +1,457 @@
+use std::{any::TypeId, borrow::Cow};
+
+use bevy_ptr::Ptr;
+
+use crate::{
+    archetype::{ArchetypeComponentId, ArchetypeGeneration},
+    change_detection::MutUntyped,
+    component::{ComponentId, Tick},
+    query::{Access, QueryState},
+    system::{
+        assert_component_access_compatibility, check_system_change_tick, Query, System, SystemMeta,
+    },
+    world::{unsafe_world_cell::UnsafeWorldCell, FilteredEntityMut, World, WorldId},
+};
+
+/// Builds a [`DynamicSystem`] from system parameters created at runtime, such as queries built
+/// with [`QueryBuilder`](crate::query::QueryBuilder) and resources known only by [`ComponentId`].
+///
+/// The access of each parameter is computed when it is added, so the system can be run in
+/// parallel with any other system it does not conflict with. Like for function systems, adding a
+/// parameter which conflicts with a previous one panics.
+///
+/// ```
+/// # use bevy_ecs::{prelude::*, query::QueryBuilder, system::SystemBuilder, world::FilteredEntityMut};
+/// #[derive(Component)]
+/// struct Health(u32);
+///
+/// #[derive(Resource)]
+/// struct Damage(u32);
+///
+/// let mut world = World::new();
+/// world.insert_resource(Damage(10));
+/// world.spawn(Health(100));
+/// let health = world.init_component::<Health>();
+/// let damage = world.components().resource_id::<Damage>().unwrap();
+///
+/// let query = QueryBuilder::<FilteredEntityMut>::new(&mut world)
+///     .mut_id(health)
+///     .build();
+/// let mut system = SystemBuilder::new(&mut world)
+///     .with_name("apply_damage")
+///     .query(query)
+///     .resource(damage)
+///     .build(move |mut params| {
+///         // SAFETY: the resource was registered for `Damage`
+///         let damage = unsafe { params.resources[0].deref::<Damage>() }.0;
+///         for mut entity in params.queries[0].iter_mut() {
+///             let value = entity.get_mut_by_id(health).unwrap();
+///             // SAFETY: the component was registered for `Health`
+///             unsafe { value.into_inner().deref_mut::<Health>() }.0 -= damage;
+///         }
+///     });
+///
+/// system.run((), &mut world);
+/// let mut query = world.query::<&Health>();
+/// assert_eq!(query.single(&world).0, 90);
+/// ```
+pub struct SystemBuilder<'w> {
+    world: &'w mut World,
+    meta: SystemMeta,
+    queries: Vec<QueryState<FilteredEntityMut<'static>>>,
+    resources: Vec<ComponentId>,
+    resources_mut: Vec<ComponentId>,
+}
+
+impl<'w> SystemBuilder<'w> {
+    /// Creates a builder for a system without any parameters, which will run in `world`.
+    pub fn new(world: &'w mut World) -> Self {
+        let mut meta = SystemMeta::new::<DynamicSystem>();
+        meta.last_run = world.change_tick().relative_to(Tick::MAX);
+        Self {
+            world,
+            meta,
+            queries: Vec::new(),
+            resources: Vec::new(),
+            resources_mut: Vec::new(),
+        }
+    }
+
+    /// Sets the name of the system, which is used in diagnostics and error messages.
+    pub fn with_name(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.meta.name = name.into();
+        self
+    }
+
+    /// Adds a query, which will be available in [`DynamicSystemParams::queries`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query was built for another [`World`], or if its access conflicts with a
+    /// previous parameter.
+    pub fn query(mut self, state: QueryState<FilteredEntityMut<'static>>) -> Self {
+        state.validate_world(self.world.id());
+        assert_component_access_compatibility(
+            &self.meta.name,
+            "FilteredEntityMut",
+            "()",
+            &self.meta.component_access_set,
+            &state.component_access,
+            self.world,
+        );
+        self.meta
+            .component_access_set
+            .add(state.component_access.clone());
+        self.queries.push(state);
+        self
+    }
+
+    /// Adds read access to the resource with the given id, which will be available in
+    /// [`DynamicSystemParams::resources`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resource does not exist in this world, or if it is already accessed mutably
+    /// by a previous parameter.
+    pub fn resource(mut self, component_id: ComponentId) -> Self {
+        let name = self.resource_name(component_id);
+        let combined_access = self.meta.component_access_set.combined_access();
+        assert!(
+            !combined_access.has_write(component_id),
+            "error[B0002]: Res<{}> in system {} conflicts with a previous ResMut<{0}> access. Consider removing the duplicate access.",
+            name,
+            self.meta.name,
+        );
+        self.meta
+            .component_access_set
+            .add_unfiltered_read(component_id);
+        let archetype_component_id = self.world.initialize_resource_internal(component_id).id();
+        self.meta
+            .archetype_component_access
+            .add_read(archetype_component_id);
+        self.resources.push(component_id);
+        self
+    }
+
+    /// Adds write access to the resource with the given id, which will be available in
+    /// [`DynamicSystemParams::resources_mut`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resource does not exist in this world, or if it is already accessed by a
+    /// previous parameter.
+    pub fn resource_mut(mut self, component_id: ComponentId) -> Self {
+        let name = self.resource_name(component_id);
+        let combined_access = self.meta.component_access_set.combined_access();
+        if combined_access.has_write(component_id) {
+            panic!(
+                "error[B0002]: ResMut<{}> in system {} conflicts with a previous ResMut<{0}> access. Consider removing the duplicate access.",
+                name, self.meta.name);
+        } else if combined_access.has_read(component_id) {
+            panic!(
+                "error[B0002]: ResMut<{}> in system {} conflicts with a previous Res<{0}> access. Consider removing the duplicate access.",
+                name, self.meta.name);
+        }
+        self.meta
+            .component_access_set
+            .add_unfiltered_write(component_id);
+        let archetype_component_id = self.world.initialize_resource_internal(component_id).id();
+        self.meta
+            .archetype_component_access
+            .add_write(archetype_component_id);
+        self.resources_mut.push(component_id);
+        self
+    }
+
+    fn resource_name(&self, component_id: ComponentId) -> String {
+        self.world
+            .components()
+            .get_info(component_id)
+            .unwrap_or_else(|| {
+                panic!(
+                    "Resource {component_id:?} requested by {} does not exist in this world",
+                    self.meta.name
+                )
+            })
+            .name()
+            .to_string()
+    }
+
+    /// Builds the system, which calls `func` with the values of its parameters each time it runs.
+    ///
+    /// The system can only be run in the [`World`] this builder was created with.
+    pub fn build<Out, F>(self, func: F) -> DynamicSystem<Out>
+    where
+        F: for<'a, 'b> FnMut(DynamicSystemParams<'a, 'b>) -> Out + Send + Sync + 'static,
+    {
+        DynamicSystem {
+            func: Box::new(func),
+            meta: self.meta,
+            queries: self.queries,
+            resources: self.resources,
+            resources_mut: self.resources_mut,
+            world_id: self.world.id(),
+            archetype_generation: ArchetypeGeneration::initial(),
+        }
+    }
+}
+
+/// The values of the parameters of a [`DynamicSystem`], in the order they were added to its
+/// [`SystemBuilder`].
+///
+/// Parameters never conflict with each other, so any of them can be used at the same time.
+pub struct DynamicSystemParams<'w, 's> {
+    /// The queries added with [`SystemBuilder::query`].
+    pub queries: Vec<Query<'w, 's, FilteredEntityMut<'static>>>,
+    /// The resources added with [`SystemBuilder::resource`].
+    pub resources: Vec<Ptr<'w>>,
+    /// The resources added with [`SystemBuilder::resource_mut`].
+    pub resources_mut: Vec<MutUntyped<'w>>,
+}
+
+/// A [`System`] built at runtime with a [`SystemBuilder`].
+pub struct DynamicSystem<Out = ()> {
+    func: Box<dyn for<'a, 'b> FnMut(DynamicSystemParams<'a, 'b>) -> Out + Send + Sync>,
+    meta: SystemMeta,
+    queries: Vec<QueryState<FilteredEntityMut<'static>>>,
+    resources: Vec<ComponentId>,
+    resources_mut: Vec<ComponentId>,
+    world_id: WorldId,
+    archetype_generation: ArchetypeGeneration,
+}
+
+impl<Out: 'static> System for DynamicSystem<Out> {
+    type In = ();
+    type Out = Out;
+
+    #[inline]
+    fn name(&self) -> Cow<'static, str> {
+        self.meta.name.clone()
+    }
+
+    #[inline]
+    fn type_id(&self) -> TypeId {
+        TypeId::of::<Self>()
+    }
+
+    #[inline]
+    fn component_access(&self) -> &Access<ComponentId> {
+        self.meta.component_access_set.combined_access()
+    }
+
+    #[inline]
+    fn archetype_component_access(&self) -> &Access<ArchetypeComponentId> {
+        &self.meta.archetype_component_access
+    }
+
+    #[inline]
+    fn is_send(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn is_exclusive(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn has_deferred(&self) -> bool {
+        false
+    }
+
+    unsafe fn run_unsafe(&mut self, _input: Self::In, world: UnsafeWorldCell) -> Self::Out {
+        #[cfg(feature = "trace")]
+        let _span_guard = self.meta.system_span.enter();
+
+        let change_tick = world.increment_change_tick();
+        let last_run = self.meta.last_run;
+
+        // SAFETY:
+        // - The caller has invoked `update_archetype_component_access`, which will panic
+        //   if the world does not match.
+        // - All world accesses of the parameters have been registered, so the caller
+        //   will ensure that there are no data access conflicts.
+        let params = DynamicSystemParams {
+            queries: self
+                .queries
+                .iter()
+                .map(|state| Query::new(world, state, last_run, change_tick, false))
+                .collect(),
+            resources: self
+                .resources
+                .iter()
+                .map(|&id| {
+                    world
+                        .get_resource_by_id(id)
+                        .unwrap_or_else(|| missing_resource(&self.meta, world, id))
+                })
+                .collect(),
+            resources_mut: self
+                .resources_mut
+                .iter()
+                .map(|&id| {
+                    let mut value = world
+                        .get_resource_mut_by_id(id)
+                        .unwrap_or_else(|| missing_resource(&self.meta, world, id));
+                    value.ticks.last_run = last_run;
+                    value.ticks.this_run = change_tick;
+                    value
+                })
+                .collect(),
+        };
+        let out = (self.func)(params);
+        self.meta.last_run = change_tick;
+        out
+    }
+
+    #[inline]
+    fn apply_deferred(&mut self, _world: &mut World) {}
+
+    fn initialize(&mut self, world: &mut World) {
+        assert_eq!(self.world_id, world.id(), "Encountered a mismatched World. A DynamicSystem can only be used with the World it was built for.");
+    }
+
+    fn update_archetype_component_access(&mut self, world: UnsafeWorldCell) {
+        assert_eq!(self.world_id, world.id(), "Encountered a mismatched World. A DynamicSystem can only be used with the World it was built for.");
+        let archetypes = world.archetypes();
+        let old_generation =
+            std::mem::replace(&mut self.archetype_generation, archetypes.generation());
+
+        for archetype in &archetypes[old_generation..] {
+            for state in &mut self.queries {
+                state.new_archetype(archetype);
+                self.meta
+                    .archetype_component_access
+                    .extend(&state.archetype_component_access);
+            }
+        }
+    }
+
+    #[inline]
+    fn check_change_tick(&mut self, change_tick: Tick) {
+        check_system_change_tick(
+            &mut self.meta.last_run,
+            change_tick,
+            self.meta.name.as_ref(),
+        );
+    }
+
+    fn get_last_run(&self) -> Tick {
+        self.meta.last_run
+    }
+
+    fn set_last_run(&mut self, last_run: Tick) {
+        self.meta.last_run = last_run;
+    }
+}
+
+fn missing_resource(meta: &SystemMeta, world: UnsafeWorldCell, component_id: ComponentId) -> ! {
+    panic!(
+        "Resource requested by {} does not exist: {}",
+        meta.name,
+        world.components().get_info(component_id).unwrap().name()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        self as bevy_ecs,
+        prelude::*,
+        query::QueryBuilder,
+        schedule::{ExecutorKind, Schedule},
+        system::{System, SystemBuilder},
+        world::FilteredEntityMut,
+    };
+
+    #[derive(Component, Debug, PartialEq)]
+    struct A(usize);
+
+    #[derive(Component)]
+    struct B;
+
+    #[derive(Resource)]
+    struct Count(usize);
+
+    #[test]
+    fn dynamic_system_params() {
+        let mut world = World::new();
+        world.insert_resource(Count(0));
+        let a = world.init_component::<A>();
+        let b = world.init_component::<B>();
+        let count = world.components().resource_id::<Count>().unwrap();
+        world.spawn((A(1), B));
+        world.spawn(A(2));
+        world.spawn(B);
+
+        let with_a = QueryBuilder::<FilteredEntityMut>::new(&mut world)
+            .mut_id(a)
+            .build();
+        let with_b = QueryBuilder::<FilteredEntityMut>::new(&mut world)
+            .with_id(b)
+            .build();
+        let mut system = SystemBuilder::new(&mut world)
+            .query(with_a)
+            .query(with_b)
+            .resource_mut(count)
+            .build(|mut params| {
+                let with_b = params.queries[1].iter().count();
+                for mut entity in params.queries[0].iter_mut() {
+                    let value = entity.get_mut_by_id(a).unwrap();
+                    // SAFETY: the component was registered for `A`
+                    unsafe { value.into_inner().deref_mut::<A>() }.0 += with_b;
+                }
+                let count = params.resources_mut.pop().unwrap();
+                // SAFETY: the resource was registered for `Count`
+                unsafe { count.into_inner().deref_mut::<Count>() }.0 += 1;
+                with_b
+            });
+
+        assert!(system.component_access().has_write(a));
+        assert!(system.component_access().has_write(count));
+        assert!(!system.component_access().has_write(b));
+        assert_eq!(system.run((), &mut world), 2);
+
+        // Archetypes created after the system was built are matched as well
+        world.spawn((A(3), B));
+        assert_eq!(system.run((), &mut world), 3);
+
+        let mut values: Vec<_> = world.query::<&A>().iter(&world).map(|a| a.0).collect();
+        values.sort_unstable();
+        assert_eq!(values, vec![1 + 2 + 3, 3 + 3, 2 + 2 + 3]);
+        assert_eq!(world.resource::<Count>().0, 2);
+    }
+
+    #[test]
+    fn dynamic_system_in_schedule() {
+        let mut world = World::new();
+        world.insert_resource(Count(0));
+        let count = world.components().resource_id::<Count>().unwrap();
+        let system = SystemBuilder::new(&mut world)
+            .with_name("increment")
+            .resource_mut(count)
+            .build(|mut params| {
+                let count = params.resources_mut.pop().unwrap();
+                // SAFETY: the resource was registered for `Count`
+                unsafe { count.into_inner().deref_mut::<Count>() }.0 += 1;
+            });
+
+        let mut schedule = Schedule::default();
+        schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+        schedule.add_systems(system);
+        schedule.run(&mut world);
+        schedule.run(&mut world);
+        assert_eq!(world.resource::<Count>().0, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "conflicts with a previous Res")]
+    fn dynamic_system_conflicting_resources() {
+        let mut world = World::new();
+        world.insert_resource(Count(0));
+        let count = world.components().resource_id::<Count>().unwrap();
+        SystemBuilder::new(&mut world)
+            .resource(count)
+            .resource_mut(count);
+    }
+}
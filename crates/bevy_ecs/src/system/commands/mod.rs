@@ -4,15 +4,17 @@ mod parallel_scope;
 use crate::{
     self as bevy_ecs,
     bundle::Bundle,
+    component::ComponentId,
     entity::{Entities, Entity},
     system::{RunSystemWithInput, SystemId},
     world::{EntityWorldMut, FromWorld, World},
 };
 use bevy_ecs_macros::SystemParam;
+use bevy_ptr::OwningPtr;
 use bevy_utils::tracing::{error, info};
 pub use command_queue::CommandQueue;
 pub use parallel_scope::*;
-use std::marker::PhantomData;
+use std::{alloc::Layout, any::Any, marker::PhantomData, ptr::NonNull};
 
 use super::{Deferred, Resource, SystemBuffer, SystemMeta};
 
@@ -194,6 +196,22 @@ impl<'w, 's> Commands<'w, 's> {
         }
     }
 
+    /// Reserves a new [`Entity`] and pushes a [`Command`] to the queue which inserts a clone of each
+    /// component of `source` into it, returning the [`EntityCommands`] of the new entity.
+    ///
+    /// This is the deferred version of [`World::clone_entity`], which describes how the
+    /// components are cloned.
+    ///
+    /// # Panics
+    ///
+    /// The command will panic when applied if `source` does not exist, or one of its components
+    /// cannot be cloned.
+    pub fn clone_entity<'a>(&'a mut self, source: Entity) -> EntityCommands<'w, 's, 'a> {
+        let mut e = self.spawn_empty();
+        e.add(clone_entity(source));
+        e
+    }
+
     /// Pushes a [`Command`] to the queue for creating a new [`Entity`] if the given one does not exists,
     /// and returns its corresponding [`EntityCommands`].
     ///
@@ -847,6 +865,95 @@ impl<'w, 's, 'a> EntityCommands<'w, 's, 'a> {
         self.add(remove::<T>)
     }
 
+    /// Adds a dynamic component to the entity, moving `value` into it when the command is applied.
+    ///
+    /// This is the deferred version of [`EntityWorldMut::insert_by_id`], which can be used from
+    /// systems without exclusive [`World`] access.
+    ///
+    /// # Panics
+    ///
+    /// The command will panic when applied if the associated entity does not exist, the component
+    /// does not exist, or its layout is not the layout of `T`.
+    ///
+    /// # Safety
+    ///
+    /// `value` must be a valid value of the component represented by `component_id`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::alloc::Layout;
+    /// # use bevy_ecs::{component::{ComponentDescriptor, StorageType}, prelude::*};
+    /// # use bevy_ecs::system::CommandQueue;
+    /// # let mut world = World::new();
+    /// // SAFETY: `u64` is `Send + Sync` and does not need to be dropped
+    /// let score = world.init_component_with_descriptor(unsafe {
+    ///     ComponentDescriptor::new_with_layout("Score", StorageType::Table, Layout::new::<u64>(), None)
+    /// });
+    ///
+    /// # let mut queue = CommandQueue::default();
+    /// # let mut commands = Commands::new(&mut queue, &world);
+    /// // SAFETY: the component was registered with the layout of a `u64`
+    /// let entity = unsafe { commands.spawn_empty().insert_by_id(score, 10u64).id() };
+    /// # queue.apply(&mut world);
+    /// # // SAFETY: the component is a `u64`
+    /// # assert_eq!(unsafe { *world.entity(entity).get_by_id(score).unwrap().deref::<u64>() }, 10);
+    /// ```
+    pub unsafe fn insert_by_id<T: Send + 'static>(
+        &mut self,
+        component_id: ComponentId,
+        value: T,
+    ) -> &mut Self {
+        self.add(insert_by_id(component_id, value))
+    }
+
+    /// Adds several dynamic components to the entity, moving `values` into them when the command
+    /// is applied.
+    ///
+    /// This is the deferred version of [`EntityWorldMut::insert_by_ids`]. Each value is boxed, so
+    /// the components can have different types.
+    ///
+    /// # Panics
+    ///
+    /// The command will panic when applied if the associated entity does not exist, or if
+    /// [`EntityWorldMut::try_insert_by_ids`] would return an error. It will also panic if the
+    /// layout of a value is not the layout of the component at the same position. The values are
+    /// dropped before panicking.
+    ///
+    /// # Safety
+    ///
+    /// Each value must be a valid value of the component represented by the [`ComponentId`] at the
+    /// same position.
+    pub unsafe fn insert_by_ids(
+        &mut self,
+        component_ids: &[ComponentId],
+        values: Vec<Box<dyn Any + Send>>,
+    ) -> &mut Self {
+        self.add(insert_by_ids(component_ids.to_vec(), values))
+    }
+
+    /// Removes a dynamic component from the entity if it exists.
+    ///
+    /// This is the deferred version of [`EntityWorldMut::remove_by_id`].
+    ///
+    /// # Panics
+    ///
+    /// The command will panic when applied if the component does not exist in the [`World`].
+    pub fn remove_by_id(&mut self, component_id: ComponentId) -> &mut Self {
+        self.add(remove_by_ids(vec![component_id]))
+    }
+
+    /// Removes several dynamic components from the entity, ignoring those it does not have.
+    ///
+    /// This is the deferred version of [`EntityWorldMut::remove_by_ids`].
+    ///
+    /// # Panics
+    ///
+    /// The command will panic when applied if a component does not exist in the [`World`].
+    pub fn remove_by_ids(&mut self, component_ids: &[ComponentId]) -> &mut Self {
+        self.add(remove_by_ids(component_ids.to_vec()))
+    }
+
     /// Despawns the entity.
     ///
     /// See [`World::despawn`] for more details.
@@ -946,6 +1053,21 @@ impl<'w, 's, 'a> EntityCommands<'w, 's, 'a> {
         self.add(retain::<T>)
     }
 
+    /// Removes all components except those with the given ids from the entity.
+    ///
+    /// This is the deferred version of [`EntityWorldMut::retain_by_ids`].
+    pub fn retain_by_ids(&mut self, component_ids: &[ComponentId]) -> &mut Self {
+        self.add(retain_by_ids(component_ids.to_vec()))
+    }
+
+    /// Removes all the components of the entity which are not typed, see
+    /// [`Components::is_typed`](crate::component::Components::is_typed).
+    ///
+    /// This is the deferred version of [`EntityWorldMut::clear_dynamic`].
+    pub fn clear_dynamic(&mut self) -> &mut Self {
+        self.add(clear_dynamic)
+    }
+
     /// Logs the components of the entity at the info level.
     ///
     /// # Panics
@@ -1061,6 +1183,105 @@ fn remove<T: Bundle>(entity: Entity, world: &mut World) {
     }
 }
 
+/// An [`EntityCommand`] that adds a dynamic component to an entity.
+///
+/// # Safety
+///
+/// `value` must be a valid value of the component represented by `component_id`.
+unsafe fn insert_by_id<T: Send + 'static>(
+    component_id: ComponentId,
+    value: T,
+) -> impl EntityCommand {
+    move |entity: Entity, world: &mut World| {
+        let Some(info) = world.components().get_info(component_id) else {
+            panic!("Could not insert the component {component_id:?} for entity {entity:?} because it doesn't exist in this World.");
+        };
+        assert_eq!(
+            info.layout(),
+            Layout::new::<T>(),
+            "Could not insert a value of type `{}` as the component `{}` because their layouts differ.",
+            std::any::type_name::<T>(),
+            info.name(),
+        );
+        if let Some(mut entity) = world.get_entity_mut(entity) {
+            OwningPtr::make(value, |ptr| {
+                // SAFETY:
+                // - the component exists in this world
+                // - the caller ensures `value` is a valid value of the component
+                unsafe { entity.insert_by_id(component_id, ptr) };
+            });
+        } else {
+            panic!("error[B0003]: Could not insert the component {component_id:?} for entity {entity:?} because it doesn't exist in this World.");
+        }
+    }
+}
+
+/// An [`EntityCommand`] that adds several dynamic components to an entity.
+///
+/// # Safety
+///
+/// Each value must be a valid value of the component represented by the [`ComponentId`] at the
+/// same position.
+unsafe fn insert_by_ids(
+    component_ids: Vec<ComponentId>,
+    values: Vec<Box<dyn Any + Send>>,
+) -> impl EntityCommand {
+    move |entity: Entity, world: &mut World| {
+        if world.get_entity(entity).is_none() {
+            panic!("error[B0003]: Could not insert the components {component_ids:?} for entity {entity:?} because it doesn't exist in this World.");
+        }
+        let layouts: Vec<_> = values
+            .iter()
+            .map(|value| Layout::for_value(&**value))
+            .collect();
+        for (&id, layout) in component_ids.iter().zip(&layouts) {
+            if let Some(info) = world.components().get_info(id) {
+                assert_eq!(
+                    info.layout(),
+                    *layout,
+                    "Could not insert a value as the component `{}` because their layouts differ.",
+                    info.name(),
+                );
+            }
+        }
+
+        let values: Vec<_> = values.into_iter().map(Box::into_raw).collect();
+        // SAFETY: pointers from `Box::into_raw` are non-null and point to an owned value
+        let ptrs = values
+            .iter()
+            .map(|&value| unsafe { OwningPtr::new(NonNull::new_unchecked(value.cast::<u8>())) });
+        let mut entity_mut = world.entity_mut(entity);
+        // SAFETY: the caller ensures each value is a valid value of its component
+        match unsafe { entity_mut.try_insert_by_ids(&component_ids, ptrs) } {
+            Ok(_) => {
+                for (value, layout) in values.into_iter().zip(layouts) {
+                    if layout.size() != 0 {
+                        // SAFETY: the value was moved out, so only its allocation is left to free
+                        unsafe { std::alloc::dealloc(value.cast::<u8>(), layout) };
+                    }
+                }
+            }
+            Err(error) => {
+                for value in values {
+                    // SAFETY: the values were not consumed, so the boxes still own them
+                    drop(unsafe { Box::from_raw(value) });
+                }
+                panic!("Could not insert the components {component_ids:?} for entity {entity:?}: {error}");
+            }
+        }
+    }
+}
+
+/// An [`EntityCommand`] that removes dynamic components from an entity.
+/// Any components that aren't found on the entity will be ignored.
+fn remove_by_ids(component_ids: Vec<ComponentId>) -> impl EntityCommand {
+    move |entity: Entity, world: &mut World| {
+        if let Some(mut entity_mut) = world.get_entity_mut(entity) {
+            entity_mut.remove_by_ids(&component_ids);
+        }
+    }
+}
+
 /// A [`Command`] that removes components from an entity.
 /// For a [`Bundle`] type `T`, this will remove all components except those in the bundle.
 /// Any components in the bundle that aren't found on the entity will be ignored.
@@ -1070,6 +1291,31 @@ fn retain<T: Bundle>(entity: Entity, world: &mut World) {
     }
 }
 
+/// An [`EntityCommand`] that removes all components of an entity except those with the given ids.
+fn retain_by_ids(component_ids: Vec<ComponentId>) -> impl EntityCommand {
+    move |entity: Entity, world: &mut World| {
+        if let Some(mut entity_mut) = world.get_entity_mut(entity) {
+            entity_mut.retain_by_ids(&component_ids);
+        }
+    }
+}
+
+/// A [`Command`] that removes the components of an entity which are not typed.
+fn clear_dynamic(entity: Entity, world: &mut World) {
+    if let Some(mut entity_mut) = world.get_entity_mut(entity) {
+        entity_mut.clear_dynamic();
+    }
+}
+
+/// An [`EntityCommand`] that inserts a clone of each component of `source` into an entity.
+fn clone_entity(source: Entity) -> impl EntityCommand {
+    move |entity: Entity, world: &mut World| {
+        if let Err(err) = world.clone_entity_into(source, entity) {
+            panic!("Could not clone entity {source:?} into {entity:?}: {err}");
+        }
+    }
+}
+
 /// A [`Command`] that inserts a [`Resource`] into the world using a value
 /// created with the [`FromWorld`] trait.
 fn init_resource<R: Resource + FromWorld>(world: &mut World) {
@@ -1107,9 +1353,12 @@ mod tests {
         system::{CommandQueue, Commands, Resource},
         world::World,
     };
-    use std::sync::{
-        atomic::{AtomicUsize, Ordering},
-        Arc,
+    use std::{
+        any::Any,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
     };
 
     #[derive(Component)]
@@ -1235,6 +1484,63 @@ mod tests {
         assert_eq!(results_after_u64, vec![]);
     }
 
+    #[test]
+    fn insert_and_remove_by_id() {
+        let mut world = World::default();
+        let mut command_queue = CommandQueue::default();
+        let u32_id = world.init_component::<W<u32>>();
+        let dense_id = world.init_component::<DropCk>();
+        let sparse_id = world.init_component::<SparseDropCk>();
+        let (dense_dropck, dense_is_dropped) = DropCk::new_pair();
+        let (sparse_dropck, sparse_is_dropped) = DropCk::new_pair();
+
+        let entity = {
+            let mut commands = Commands::new(&mut command_queue, &world);
+            let mut entity = commands.spawn_empty();
+            let values: Vec<Box<dyn Any + Send>> = vec![
+                Box::new(dense_dropck),
+                Box::new(SparseDropCk(sparse_dropck)),
+            ];
+            // SAFETY: each value has the type of its component
+            unsafe {
+                entity
+                    .insert_by_id(u32_id, W(1u32))
+                    .insert_by_ids(&[dense_id, sparse_id], values);
+            }
+            entity.id()
+        };
+        command_queue.apply(&mut world);
+        assert_eq!(world.get::<W<u32>>(entity).unwrap().0, 1);
+        assert!(world.get::<DropCk>(entity).is_some());
+        assert!(world.get::<SparseDropCk>(entity).is_some());
+        assert_eq!(dense_is_dropped.load(Ordering::Relaxed), 0);
+        assert_eq!(sparse_is_dropped.load(Ordering::Relaxed), 0);
+
+        Commands::new(&mut command_queue, &world)
+            .entity(entity)
+            .remove_by_id(u32_id)
+            .remove_by_ids(&[dense_id, sparse_id, u32_id]);
+        command_queue.apply(&mut world);
+        assert!(world.get::<W<u32>>(entity).is_none());
+        assert_eq!(dense_is_dropped.load(Ordering::Relaxed), 1);
+        assert_eq!(sparse_is_dropped.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_by_id_layout_mismatch() {
+        let mut world = World::default();
+        let mut command_queue = CommandQueue::default();
+        let u32_id = world.init_component::<W<u32>>();
+        // SAFETY: the layout check panics before the value is inserted
+        unsafe {
+            Commands::new(&mut command_queue, &world)
+                .spawn_empty()
+                .insert_by_id(u32_id, 1u64);
+        }
+        command_queue.apply(&mut world);
+    }
+
     #[test]
     fn remove_resources() {
         let mut world = World::default();
@@ -1491,6 +1491,13 @@ impl<'w, 's, D: QueryData, F: QueryFilter> Query<'w, 's, D, F> {
     /// * `&mut T` -> `&T`
     /// * `&mut T` -> `Ref<T>`
     /// * [`EntityMut`](crate::world::EntityMut) -> [`EntityRef`](crate::world::EntityRef)
+    /// * [`FilteredEntityMut`](crate::world::FilteredEntityMut) and
+    ///   [`FilteredEntityRef`](crate::world::FilteredEntityRef), such as queries built with
+    ///   [`QueryBuilder`](crate::query::QueryBuilder), -> typed terms for the components they
+    ///   can access
+    ///
+    /// A typed term like `&T` can only be used if every entity of the original query has `T`,
+    /// because the original query accesses it as a required term or filters on `With<T>`.
     ///    
     pub fn transmute_lens<NewD: QueryData>(&mut self) -> QueryLens<'_, NewD> {
         self.transmute_lens_filtered::<NewD, ()>()
@@ -0,0 +1,283 @@
+use std::alloc::Layout;
+
+use bevy_utils::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+
+use crate as bevy_ecs;
+use crate::{
+    component::{ComponentDescriptor, ComponentId, StorageType},
+    system::Resource,
+    world::{error::RestoreComponentRegistryError, FromWorld, World},
+};
+
+/// The components registered at runtime in a [`World`], without a Rust type, which can be saved
+/// and restored into another world with [`World::restore_component_registry`].
+///
+/// Component ids depend on the order components are registered in, so saved data referring to
+/// them by id should be saved with the registry, and mapped to the ids of the new world.
+///
+/// Initializing this resource records the components of the world it is initialized in.
+#[derive(Resource, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DynamicComponentRegistry {
+    /// The registered components, in the order of their ids.
+    pub components: Vec<DynamicComponentRegistration>,
+}
+
+/// A component in a [`DynamicComponentRegistry`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DynamicComponentRegistration {
+    /// The id the component had in the world it was recorded from.
+    pub id: ComponentId,
+    /// The name the component was registered with.
+    pub name: String,
+    /// The size of the component in bytes.
+    pub size: usize,
+    /// The alignment of the component in bytes.
+    pub align: usize,
+    /// The storage type of the component.
+    pub storage_type: StorageType,
+    /// Whether the component has a drop function, which cannot be saved.
+    pub needs_drop: bool,
+}
+
+impl DynamicComponentRegistry {
+    /// Returns the registration of the component with the given name, if any.
+    pub fn get_by_name(&self, name: &str) -> Option<&DynamicComponentRegistration> {
+        self.components
+            .iter()
+            .find(|registration| registration.name == name)
+    }
+}
+
+impl FromWorld for DynamicComponentRegistry {
+    fn from_world(world: &mut World) -> Self {
+        world.dynamic_component_registry()
+    }
+}
+
+impl World {
+    /// Records the components registered in this world without a Rust type, such as those
+    /// initialized with [`World::init_component_with_descriptor`] from a
+    /// [`ComponentDescriptor::new_with_layout`].
+    ///
+    /// Resources and dynamic event types are not recorded.
+    pub fn dynamic_component_registry(&self) -> DynamicComponentRegistry {
+        let components = self
+            .components()
+            .iter()
+            .filter(|info| info.type_id().is_none() && !self.components().is_resource(info.id()))
+            .map(|info| DynamicComponentRegistration {
+                id: info.id(),
+                name: info.name().to_string(),
+                size: info.layout().size(),
+                align: info.layout().align(),
+                storage_type: info.storage_type(),
+                needs_drop: info.drop().is_some(),
+            })
+            .collect();
+        DynamicComponentRegistry { components }
+    }
+
+    /// Registers the components of a [`DynamicComponentRegistry`] in this world, and returns the
+    /// id of each of them in this world, by their id in the world the registry was recorded from.
+    ///
+    /// Components are matched by name to those already registered in this world, which must have
+    /// the same layout and storage type. This lets components with a drop or clone function be
+    /// registered with their full [`ComponentDescriptor`] before restoring the registry. The
+    /// others are registered in the order of their old ids, with the recorded name, layout and
+    /// storage type, so restoring the same registry into worlds with the same components always
+    /// gives the same ids.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RestoreComponentRegistryError`] without registering anything if two components
+    /// of the registry share a name, a component does not match the one registered with its name,
+    /// or it needs to be dropped and is not registered yet.
+    pub fn restore_component_registry(
+        &mut self,
+        registry: &DynamicComponentRegistry,
+    ) -> Result<HashMap<ComponentId, ComponentId>, RestoreComponentRegistryError> {
+        let mut registrations: Vec<_> = registry.components.iter().collect();
+        registrations.sort_by_key(|registration| registration.id);
+
+        let mut names = HashSet::with_capacity(registrations.len());
+        let mut layouts = Vec::with_capacity(registrations.len());
+        for registration in &registrations {
+            let name = || registration.name.clone();
+            if !names.insert(registration.name.as_str()) {
+                return Err(RestoreComponentRegistryError::DuplicateName(name()));
+            }
+            let layout = Layout::from_size_align(registration.size, registration.align)
+                .map_err(|_| RestoreComponentRegistryError::InvalidLayout(name()))?;
+            match self.components().get_info_by_name(&registration.name) {
+                Some(info) if info.layout() != layout => {
+                    return Err(RestoreComponentRegistryError::LayoutMismatch(name()));
+                }
+                Some(info) if info.storage_type() != registration.storage_type => {
+                    return Err(RestoreComponentRegistryError::StorageTypeMismatch(name()));
+                }
+                None if registration.needs_drop => {
+                    return Err(RestoreComponentRegistryError::NeedsDrop(name()));
+                }
+                _ => {}
+            }
+            layouts.push(layout);
+        }
+
+        let ids = registrations
+            .into_iter()
+            .zip(layouts)
+            .map(|(registration, layout)| {
+                let id = self
+                    .components()
+                    .get_id_by_name(&registration.name)
+                    .unwrap_or_else(|| {
+                        // SAFETY: the component has no drop function, and its values are plain
+                        // bytes which are safe to access from any thread
+                        let descriptor = unsafe {
+                            ComponentDescriptor::new_with_layout(
+                                registration.name.clone(),
+                                registration.storage_type,
+                                layout,
+                                None,
+                            )
+                        };
+                        self.init_component_with_descriptor(descriptor)
+                    });
+                (registration.id, id)
+            })
+            .collect();
+        Ok(ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::alloc::Layout;
+
+    use crate::{
+        self as bevy_ecs,
+        component::{ComponentDescriptor, ComponentId, StorageType},
+        prelude::*,
+        ptr::OwningPtr,
+        world::{error::RestoreComponentRegistryError, DynamicComponentRegistry},
+    };
+
+    #[derive(Component)]
+    struct Typed;
+
+    fn init_component(world: &mut World, name: &str, storage_type: StorageType) -> ComponentId {
+        // SAFETY: the components are plain integers, which are `Send + Sync` and have no drop function
+        let descriptor = unsafe {
+            ComponentDescriptor::new_with_layout(
+                name.to_string(),
+                storage_type,
+                Layout::new::<u32>(),
+                None,
+            )
+        };
+        world.init_component_with_descriptor(descriptor)
+    }
+
+    #[test]
+    fn restore_component_registry() {
+        let mut world = World::new();
+        let position = init_component(&mut world, "Position", StorageType::Table);
+        world.init_component::<Typed>();
+        let health = init_component(&mut world, "Health", StorageType::SparseSet);
+        // SAFETY: the resource and the events are plain integers, which are `Send + Sync` and
+        // have no drop function
+        let (resource, event) = unsafe {
+            (
+                ComponentDescriptor::new_with_layout(
+                    "Score",
+                    StorageType::Table,
+                    Layout::new::<u32>(),
+                    None,
+                ),
+                ComponentDescriptor::new_with_layout(
+                    "Damage",
+                    StorageType::Table,
+                    Layout::new::<u32>(),
+                    None,
+                ),
+            )
+        };
+        world.init_resource_with_descriptor(resource);
+        world.init_dynamic_event(event);
+        world.init_resource::<DynamicComponentRegistry>();
+
+        let registry = world.resource::<DynamicComponentRegistry>().clone();
+        assert_eq!(registry.components.len(), 2);
+        assert_eq!(registry.get_by_name("Health").unwrap().id, health);
+
+        let mut restored = World::new();
+        restored.init_component::<Typed>();
+        let existing_health = init_component(&mut restored, "Health", StorageType::SparseSet);
+        let ids = restored.restore_component_registry(&registry).unwrap();
+        assert_eq!(ids.len(), 2);
+        assert_eq!(ids[&health], existing_health);
+        assert_eq!(
+            restored.components().get_id_by_name("Position"),
+            Some(ids[&position])
+        );
+
+        let entity = restored.spawn_empty().id();
+        OwningPtr::make(7u32, |ptr| {
+            // SAFETY: the component is a `u32`
+            unsafe {
+                restored
+                    .entity_mut(entity)
+                    .insert_by_id(ids[&position], ptr)
+            };
+        });
+        assert_eq!(restored.dynamic_component_registry().components.len(), 2);
+
+        let mut again = World::new();
+        again.init_component::<Typed>();
+        let again_ids = again.restore_component_registry(&registry).unwrap();
+        let components = again.components().len();
+        assert_eq!(
+            again.restore_component_registry(&registry).unwrap(),
+            again_ids
+        );
+        assert_eq!(again.components().len(), components);
+    }
+
+    #[test]
+    fn restore_component_registry_errors() {
+        let mut world = World::new();
+        init_component(&mut world, "Position", StorageType::Table);
+        let registry = world.dynamic_component_registry();
+
+        let mut restored = World::new();
+        init_component(&mut restored, "Position", StorageType::SparseSet);
+        assert_eq!(
+            restored.restore_component_registry(&registry),
+            Err(RestoreComponentRegistryError::StorageTypeMismatch(
+                "Position".to_string()
+            ))
+        );
+
+        let mut needs_drop = registry.clone();
+        needs_drop.components[0].name = "Name".to_string();
+        needs_drop.components[0].needs_drop = true;
+        let components = restored.components().len();
+        assert_eq!(
+            restored.restore_component_registry(&needs_drop),
+            Err(RestoreComponentRegistryError::NeedsDrop("Name".to_string()))
+        );
+        assert_eq!(restored.components().len(), components);
+
+        let mut duplicate = registry.clone();
+        let mut copy = duplicate.components[0].clone();
+        copy.id = ComponentId::new(copy.id.index() + 1);
+        duplicate.components.push(copy);
+        assert_eq!(
+            World::new().restore_component_registry(&duplicate),
+            Err(RestoreComponentRegistryError::DuplicateName(
+                "Position".to_string()
+            ))
+        );
+    }
+}
@@ -0,0 +1,341 @@
+use std::ops::Deref;
+
+use crate::{
+    change_detection::MutUntyped,
+    component::{Component, ComponentHook, ComponentHooks, ComponentId},
+    entity::Entity,
+    system::{CommandQueue, Commands, Resource},
+    world::{Mut, World},
+};
+
+/// A [`World`] which can only be modified in ways that do not change its layout, given to
+/// [`ComponentHook`]s.
+///
+/// Values of components and resources can be read and changed, but entities cannot be spawned
+/// or despawned, and components cannot be inserted or removed. Use [`DeferredWorld::commands`]
+/// to do so once the hooks have run.
+pub struct DeferredWorld<'w> {
+    world: &'w mut World,
+    queue: &'w mut CommandQueue,
+}
+
+impl<'w> Deref for DeferredWorld<'w> {
+    type Target = World;
+
+    fn deref(&self) -> &Self::Target {
+        self.world
+    }
+}
+
+impl<'w> DeferredWorld<'w> {
+    /// Returns [`Commands`] which are applied to the world once the hooks of the current
+    /// operation have run.
+    pub fn commands(&mut self) -> Commands<'_, '_> {
+        Commands::new(self.queue, self.world)
+    }
+
+    /// Retrieves a mutable reference to the given `entity`'s [`Component`] of the given type.
+    /// Returns `None` if the `entity` does not have a [`Component`] of the given type.
+    #[inline]
+    pub fn get_mut<T: Component>(&mut self, entity: Entity) -> Option<Mut<T>> {
+        self.world.get_mut(entity)
+    }
+
+    /// Retrieves a mutable untyped reference to the given `entity`'s [`Component`] of the given
+    /// [`ComponentId`]. Returns `None` if the `entity` does not have the component.
+    ///
+    /// **You should prefer to use the typed API [`DeferredWorld::get_mut`] where possible and only
+    /// use this in cases where the actual types are not known at compile time.**
+    #[inline]
+    pub fn get_mut_by_id(
+        &mut self,
+        entity: Entity,
+        component_id: ComponentId,
+    ) -> Option<MutUntyped<'_>> {
+        // SAFETY: `&mut self` ensures that there are no other references to the world data
+        unsafe {
+            self.world
+                .as_unsafe_world_cell()
+                .get_entity(entity)?
+                .get_mut_by_id(component_id)
+        }
+    }
+
+    /// Gets a mutable reference to the resource of the given type.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resource does not exist.
+    #[inline]
+    #[track_caller]
+    pub fn resource_mut<R: Resource>(&mut self) -> Mut<'_, R> {
+        self.world.resource_mut()
+    }
+
+    /// Gets a mutable reference to the resource of the given type if it exists.
+    #[inline]
+    pub fn get_resource_mut<R: Resource>(&mut self) -> Option<Mut<'_, R>> {
+        self.world.get_resource_mut()
+    }
+
+    /// Gets a pointer to the resource with the id [`ComponentId`] if it exists.
+    /// The returned pointer may be used to modify the resource, as long as the mutable borrow
+    /// of the [`DeferredWorld`] is still valid.
+    ///
+    /// **You should prefer to use the typed API [`DeferredWorld::get_resource_mut`] where possible
+    /// and only use this in cases where the actual types are not known at compile time.**
+    #[inline]
+    pub fn get_resource_mut_by_id(&mut self, component_id: ComponentId) -> Option<MutUntyped<'_>> {
+        self.world.get_resource_mut_by_id(component_id)
+    }
+}
+
+/// The hook of [`ComponentHooks`] to run.
+#[derive(Clone, Copy)]
+pub(crate) enum ComponentHookKind {
+    OnAdd,
+    OnInsert,
+    OnRemove,
+}
+
+impl ComponentHookKind {
+    fn get(self, hooks: &ComponentHooks) -> Option<ComponentHook> {
+        match self {
+            ComponentHookKind::OnAdd => hooks.on_add,
+            ComponentHookKind::OnInsert => hooks.on_insert,
+            ComponentHookKind::OnRemove => hooks.on_remove,
+        }
+    }
+}
+
+impl World {
+    /// Returns a mutable reference to the [`ComponentHooks`] of the component with the given id,
+    /// or `None` if it does not exist.
+    ///
+    /// Hooks only run for changes made after they are set. Like hooks set with
+    /// [`ComponentDescriptor::with_hooks`](crate::component::ComponentDescriptor::with_hooks),
+    /// they are run by the methods which insert and remove components by id, and when the
+    /// entity is despawned.
+    ///
+    /// ```
+    /// # use bevy_ecs::{component::{ComponentDescriptor, StorageType}, prelude::*};
+    /// # use std::alloc::Layout;
+    /// #[derive(Resource, Default)]
+    /// struct Count(usize);
+    ///
+    /// let mut world = World::new();
+    /// world.init_resource::<Count>();
+    /// // SAFETY: the component is a zero-sized marker without a drop function
+    /// let descriptor = unsafe {
+    ///     ComponentDescriptor::new_with_layout("Marker", StorageType::Table, Layout::new::<()>(), None)
+    /// };
+    /// let marker = world.init_component_with_descriptor(descriptor);
+    /// world
+    ///     .register_component_hooks_by_id(marker)
+    ///     .unwrap()
+    ///     .on_add(|mut world, _, _| {
+    ///         world.resource_mut::<Count>().0 += 1;
+    ///     })
+    ///     .on_remove(|mut world, _, _| {
+    ///         world.resource_mut::<Count>().0 -= 1;
+    ///     });
+    ///
+    /// let mut entity = world.spawn_empty();
+    /// // SAFETY: the component is a zero-sized marker
+    /// unsafe { entity.insert_by_id(marker, bevy_ecs::ptr::OwningPtr::new(std::ptr::NonNull::dangling())) };
+    /// assert_eq!(entity.world().resource::<Count>().0, 1);
+    /// entity.despawn();
+    /// assert_eq!(world.resource::<Count>().0, 0);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the component was registered for a Rust type with [`World::init_component`], as
+    /// it could then be inserted and removed by methods which do not run hooks.
+    pub fn register_component_hooks_by_id(
+        &mut self,
+        component_id: ComponentId,
+    ) -> Option<&mut ComponentHooks> {
+        let info = self.components.get_info(component_id)?;
+        assert!(
+            !self.components.is_typed(component_id),
+            "Hooks cannot be registered for the typed component {}",
+            info.name(),
+        );
+        self.components.get_hooks_mut(component_id)
+    }
+
+    /// Returns `true` if any of the given components has a hook.
+    pub(crate) fn has_component_hooks(
+        &self,
+        component_ids: impl IntoIterator<Item = ComponentId>,
+    ) -> bool {
+        component_ids.into_iter().any(|id| {
+            self.components
+                .get_info(id)
+                .is_some_and(|info| !info.hooks().is_empty())
+        })
+    }
+
+    /// Runs the given hook of each of the components for `entity`, queueing their commands in
+    /// `queue`.
+    pub(crate) fn trigger_component_hooks(
+        &mut self,
+        kind: ComponentHookKind,
+        entity: Entity,
+        component_ids: impl IntoIterator<Item = ComponentId>,
+        queue: &mut CommandQueue,
+    ) {
+        let hooks: Vec<_> = component_ids
+            .into_iter()
+            .filter_map(|id| Some((kind.get(self.components.get_info(id)?.hooks())?, id)))
+            .collect();
+        for (hook, id) in hooks {
+            hook(
+                DeferredWorld {
+                    world: &mut *self,
+                    queue: &mut *queue,
+                },
+                entity,
+                id,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::alloc::Layout;
+
+    use crate::{
+        self as bevy_ecs,
+        component::{ComponentDescriptor, ComponentHooks, ComponentId, StorageType},
+        prelude::*,
+        ptr::OwningPtr,
+        world::DeferredWorld,
+    };
+
+    #[derive(Resource, Default)]
+    struct Log(Vec<(&'static str, Entity, u32)>);
+
+    #[derive(Component)]
+    struct Seen;
+
+    fn init_counter(world: &mut World, hooks: ComponentHooks) -> ComponentId {
+        // SAFETY: the component is a plain integer without a drop function
+        let descriptor = unsafe {
+            ComponentDescriptor::new_with_layout(
+                "Counter",
+                StorageType::Table,
+                Layout::new::<u32>(),
+                None,
+            )
+        };
+        world.init_component_with_descriptor(descriptor.with_hooks(hooks))
+    }
+
+    fn log(world: &mut DeferredWorld, name: &'static str, entity: Entity, id: ComponentId) {
+        let value = world.entity(entity).get_by_id(id).map(|ptr| {
+            // SAFETY: the component is a `u32`
+            unsafe { *ptr.deref::<u32>() }
+        });
+        world
+            .resource_mut::<Log>()
+            .0
+            .push((name, entity, value.unwrap()));
+    }
+
+    fn insert(world: &mut World, entity: Entity, id: ComponentId, value: u32) {
+        OwningPtr::make(value, |ptr| {
+            // SAFETY: the component is a `u32`
+            unsafe { world.entity_mut(entity).insert_by_id(id, ptr) };
+        });
+    }
+
+    #[test]
+    fn component_hooks() {
+        let mut world = World::new();
+        world.init_resource::<Log>();
+        let mut hooks = ComponentHooks::default();
+        hooks
+            .on_add(|mut world, entity, id| log(&mut world, "add", entity, id))
+            .on_insert(|mut world, entity, id| log(&mut world, "insert", entity, id))
+            .on_remove(|mut world, entity, id| log(&mut world, "remove", entity, id));
+        let counter = init_counter(&mut world, hooks);
+
+        let a = world.spawn_empty().id();
+        insert(&mut world, a, counter, 1);
+        insert(&mut world, a, counter, 2);
+        world.entity_mut(a).remove_by_id(counter);
+        world.entity_mut(a).remove_by_id(counter);
+        insert(&mut world, a, counter, 3);
+        world.despawn(a);
+
+        let b = OwningPtr::make(4u32, |ptr| {
+            // SAFETY: the component is a `u32`
+            unsafe { world.spawn_batch_by_ids(&[counter], [[ptr]]) }.unwrap()[0]
+        });
+
+        assert_eq!(
+            world.resource::<Log>().0,
+            vec![
+                ("add", a, 1),
+                ("insert", a, 1),
+                ("insert", a, 2),
+                ("remove", a, 2),
+                ("add", a, 3),
+                ("insert", a, 3),
+                ("remove", a, 3),
+                ("add", b, 4),
+                ("insert", b, 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn component_hook_commands() {
+        let mut world = World::new();
+        let mut hooks = ComponentHooks::default();
+        hooks
+            .on_add(|mut world, entity, _| {
+                world.commands().entity(entity).insert(Seen);
+            })
+            .on_remove(|mut world, entity, _| {
+                world.commands().entity(entity).remove::<Seen>();
+            });
+        let counter = init_counter(&mut world, hooks);
+
+        let entity = world.spawn_empty().id();
+        insert(&mut world, entity, counter, 0);
+        assert!(world.entity(entity).contains::<Seen>());
+        world.entity_mut(entity).remove_by_id(counter);
+        assert!(!world.entity(entity).contains::<Seen>());
+    }
+
+    #[test]
+    fn component_hook_despawns_entity() {
+        let mut world = World::new();
+        let mut hooks = ComponentHooks::default();
+        hooks.on_add(|mut world, entity, _| {
+            world.commands().entity(entity).despawn();
+        });
+        let counter = init_counter(&mut world, hooks);
+
+        let entity = world.spawn_empty().id();
+        OwningPtr::make(0u32, |ptr| {
+            let mut entity_mut = world.entity_mut(entity);
+            // SAFETY: the component is a `u32`
+            unsafe { entity_mut.insert_by_id(counter, ptr) };
+            assert!(entity_mut.is_despawned());
+        });
+        assert!(world.get_entity(entity).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Hooks cannot be registered for the typed component")]
+    fn typed_component_hooks() {
+        let mut world = World::new();
+        let id = world.init_component::<Seen>();
+        world.register_component_hooks_by_id(id);
+    }
+}
@@ -0,0 +1,411 @@
+use std::{
+    alloc::{alloc, dealloc, handle_alloc_error, Layout},
+    num::NonZeroUsize,
+    ptr::NonNull,
+};
+
+use bevy_ptr::OwningPtr;
+use bevy_utils::HashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    component::{ComponentDescriptor, ComponentId, StorageType},
+    entity::Entity,
+    world::{error::DynamicSnapshotError, World},
+};
+
+/// A copy of the raw bytes of some components of a [`World`], taken with
+/// [`World::serialize_dynamic`] and restored with [`World::apply_dynamic_snapshot`].
+///
+/// Unlike scenes this does not need any reflection data, so it also works for components
+/// created with [`ComponentDescriptor::new_with_layout`]. It can be saved with any serde format,
+/// and restored into a world where the same components are registered.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DynamicSnapshot {
+    /// The components in this snapshot.
+    pub components: Vec<DynamicSnapshotComponent>,
+    /// The entities in this snapshot, with the values of their components.
+    pub entities: Vec<DynamicSnapshotEntity>,
+}
+
+/// The description of a component in a [`DynamicSnapshot`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DynamicSnapshotComponent {
+    /// The name the component was registered with.
+    pub name: String,
+    /// The size of the component in bytes.
+    pub size: usize,
+    /// The alignment of the component in bytes.
+    pub align: usize,
+    /// The storage type of the component.
+    pub storage_type: StorageType,
+}
+
+/// An entity in a [`DynamicSnapshot`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DynamicSnapshotEntity {
+    /// The entity the values were copied from.
+    pub entity: Entity,
+    /// The index of each component in [`DynamicSnapshot::components`], with its bytes.
+    pub values: Vec<(usize, Vec<u8>)>,
+}
+
+/// A heap allocation with the layout of a component, holding a copy of one of its values.
+struct AlignedValue {
+    ptr: NonNull<u8>,
+    layout: Layout,
+}
+
+impl AlignedValue {
+    fn new(bytes: &[u8], layout: Layout) -> Self {
+        debug_assert_eq!(bytes.len(), layout.size());
+        let ptr = if layout.size() == 0 {
+            // SAFETY: the alignment of a layout is never zero
+            bevy_ptr::dangling_with_align(unsafe { NonZeroUsize::new_unchecked(layout.align()) })
+        } else {
+            // SAFETY: `layout` has a non-zero size
+            NonNull::new(unsafe { alloc(layout) }).unwrap_or_else(|| handle_alloc_error(layout))
+        };
+        // SAFETY: `ptr` is valid for writes of `layout.size()` bytes, which is the length of `bytes`
+        unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr.as_ptr(), layout.size()) };
+        Self { ptr, layout }
+    }
+
+    /// # Safety
+    /// The value must only be moved out of the returned pointer once.
+    unsafe fn as_owning_ptr(&mut self) -> OwningPtr<'_> {
+        OwningPtr::new(self.ptr)
+    }
+}
+
+impl Drop for AlignedValue {
+    fn drop(&mut self) {
+        if self.layout.size() != 0 {
+            // SAFETY: `ptr` was allocated with `layout`
+            unsafe { dealloc(self.ptr.as_ptr(), self.layout) };
+        }
+    }
+}
+
+impl World {
+    /// Copies the raw bytes of the given components of every entity which has at least one of them.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`DynamicSnapshotError`] if a component does not exist, or has a drop function,
+    /// as copying its bytes would not be a valid clone.
+    ///
+    /// # Safety
+    ///
+    /// The components must not contain uninitialized bytes, such as padding.
+    pub unsafe fn serialize_dynamic(
+        &self,
+        component_ids: &[ComponentId],
+    ) -> Result<DynamicSnapshot, DynamicSnapshotError> {
+        let mut components = Vec::with_capacity(component_ids.len());
+        for &id in component_ids {
+            let Some(info) = self.components().get_info(id) else {
+                return Err(DynamicSnapshotError::UnknownComponent(id));
+            };
+            if info.drop().is_some() {
+                return Err(DynamicSnapshotError::NeedsDrop(info.name().to_string()));
+            }
+            components.push(DynamicSnapshotComponent {
+                name: info.name().to_string(),
+                size: info.layout().size(),
+                align: info.layout().align(),
+                storage_type: info.storage_type(),
+            });
+        }
+
+        let entities = self
+            .iter_entities()
+            .filter_map(|entity| {
+                let values: Vec<_> = component_ids
+                    .iter()
+                    .zip(&components)
+                    .enumerate()
+                    .filter_map(|(index, (&id, component))| {
+                        let ptr = entity.get_by_id(id)?;
+                        // SAFETY: the component is `size` bytes long, and the caller ensures
+                        // they are all initialized
+                        let bytes = std::slice::from_raw_parts(ptr.as_ptr(), component.size);
+                        Some((index, bytes.to_vec()))
+                    })
+                    .collect();
+                (!values.is_empty()).then(|| DynamicSnapshotEntity {
+                    entity: entity.id(),
+                    values,
+                })
+            })
+            .collect();
+
+        Ok(DynamicSnapshot {
+            components,
+            entities,
+        })
+    }
+
+    /// Spawns the entities of a [`DynamicSnapshot`], returning the new entities in the same order.
+    ///
+    /// Each component is matched by name to one already registered in this world, which must have
+    /// the same layout and no drop function. Components which are not registered are initialized
+    /// from a new [`ComponentDescriptor`] with the snapshot's name, layout and storage type, once
+    /// every component and value has been checked.
+    ///
+    /// Entities with the same components are spawned together with
+    /// [`World::spawn_batch_by_ids`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`DynamicSnapshotError`] without registering or spawning anything if a component
+    /// cannot be matched, or a value does not have the size of its component.
+    ///
+    /// # Panics
+    ///
+    /// Panics without registering or spawning anything if a value refers to a component outside
+    /// of [`DynamicSnapshot::components`], or an entity has more than one value for the same
+    /// component.
+    ///
+    /// # Safety
+    ///
+    /// The bytes of each value must be a valid value of its component, which is the case for
+    /// snapshots taken with [`World::serialize_dynamic`] of components with the same names.
+    pub unsafe fn apply_dynamic_snapshot(
+        &mut self,
+        snapshot: &DynamicSnapshot,
+    ) -> Result<Vec<Entity>, DynamicSnapshotError> {
+        let mut layouts = Vec::with_capacity(snapshot.components.len());
+        for component in &snapshot.components {
+            let layout_mismatch = || DynamicSnapshotError::LayoutMismatch(component.name.clone());
+            let layout = Layout::from_size_align(component.size, component.align)
+                .map_err(|_| layout_mismatch())?;
+            if let Some(info) = self.components().get_info_by_name(&component.name) {
+                if info.layout() != layout {
+                    return Err(layout_mismatch());
+                }
+                if info.drop().is_some() {
+                    return Err(DynamicSnapshotError::NeedsDrop(component.name.clone()));
+                }
+            }
+            layouts.push(layout);
+        }
+        // The indices of the components of each group of entities, with the indices of the entities
+        let mut groups: Vec<(Vec<usize>, Vec<usize>)> = Vec::new();
+        let mut group_of_components = HashMap::<Vec<usize>, usize>::default();
+        for (entity_index, entity) in snapshot.entities.iter().enumerate() {
+            let mut names = Vec::with_capacity(entity.values.len());
+            for (index, bytes) in &entity.values {
+                let component = &snapshot.components[*index];
+                if bytes.len() != component.size {
+                    return Err(DynamicSnapshotError::LayoutMismatch(component.name.clone()));
+                }
+                names.push(component.name.as_str());
+            }
+            names.sort_unstable();
+            if let Some(pair) = names.windows(2).find(|pair| pair[0] == pair[1]) {
+                panic!(
+                    "Entity {:?} has more than one value for component {}",
+                    entity.entity, pair[0]
+                );
+            }
+
+            let mut components: Vec<_> = entity.values.iter().map(|(index, _)| *index).collect();
+            components.sort_unstable();
+            let group = *group_of_components
+                .entry(components.clone())
+                .or_insert_with(|| {
+                    groups.push((components, Vec::new()));
+                    groups.len() - 1
+                });
+            groups[group].1.push(entity_index);
+        }
+
+        let ids: Vec<_> = snapshot
+            .components
+            .iter()
+            .zip(&layouts)
+            .map(|(component, &layout)| {
+                self.components()
+                    .get_id_by_name(&component.name)
+                    .unwrap_or_else(|| {
+                        // SAFETY: the component has no drop function, and its values are plain
+                        // bytes which are safe to access from any thread
+                        let descriptor = ComponentDescriptor::new_with_layout(
+                            component.name.clone(),
+                            component.storage_type,
+                            layout,
+                            None,
+                        );
+                        self.init_component_with_descriptor(descriptor)
+                    })
+            })
+            .collect();
+
+        let mut spawned = vec![Entity::PLACEHOLDER; snapshot.entities.len()];
+        for (components, entity_indices) in &groups {
+            let component_ids: Vec<_> = components.iter().map(|&index| ids[index]).collect();
+            let mut values: Vec<Vec<_>> = entity_indices
+                .iter()
+                .map(|&entity_index| {
+                    let mut entity_values: Vec<_> =
+                        snapshot.entities[entity_index].values.iter().collect();
+                    entity_values.sort_unstable_by_key(|(index, _)| *index);
+                    entity_values
+                        .into_iter()
+                        .map(|(index, bytes)| AlignedValue::new(bytes, layouts[*index]))
+                        .collect()
+                })
+                .collect();
+            // SAFETY:
+            // - the values of each entity are sorted by component, in the order of `component_ids`
+            // - the caller ensures the bytes are valid values of the components, and each value is
+            //   only moved into the world once
+            let batch = values
+                .iter_mut()
+                .map(|entity_values| entity_values.iter_mut().map(|value| value.as_owning_ptr()));
+            let entities = self
+                .spawn_batch_by_ids(&component_ids, batch)
+                .expect("the components of an entity were checked to have distinct names");
+            for (&entity_index, entity) in entity_indices.iter().zip(entities) {
+                spawned[entity_index] = entity;
+            }
+        }
+        Ok(spawned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::alloc::Layout;
+
+    use super::{DynamicSnapshot, DynamicSnapshotComponent, DynamicSnapshotEntity};
+    use crate::{
+        component::{ComponentDescriptor, ComponentId, StorageType},
+        entity::Entity,
+        ptr::OwningPtr,
+        world::{error::DynamicSnapshotError, World},
+    };
+
+    fn init_component(world: &mut World, name: &str, layout: Layout) -> ComponentId {
+        // SAFETY: the components are plain integers, which are `Send + Sync` and have no drop function
+        let descriptor = unsafe {
+            ComponentDescriptor::new_with_layout(name.to_string(), StorageType::Table, layout, None)
+        };
+        world.init_component_with_descriptor(descriptor)
+    }
+
+    #[test]
+    fn dynamic_snapshot_round_trip() {
+        let mut world = World::new();
+        let position = init_component(&mut world, "Position", Layout::new::<[u64; 2]>());
+        let health = init_component(&mut world, "Health", Layout::new::<u32>());
+        for i in 0..5u64 {
+            let mut entity = world.spawn_empty();
+            OwningPtr::make([i, i * 10], |ptr| {
+                // SAFETY: `ptr` is a `[u64; 2]`, matching the component layout
+                unsafe { entity.insert_by_id(position, ptr) };
+            });
+            if i % 2 == 0 {
+                OwningPtr::make(100 - i as u32, |ptr| {
+                    // SAFETY: `ptr` is a `u32`, matching the component layout
+                    unsafe { entity.insert_by_id(health, ptr) };
+                });
+            }
+        }
+        let read = |world: &World| {
+            let mut values: Vec<_> = world
+                .iter_entities()
+                .map(|entity| {
+                    // SAFETY: the components have the layout they were registered with
+                    unsafe {
+                        let position = world.components().get_id_by_name("Position").unwrap();
+                        let health = world.components().get_id_by_name("Health").unwrap();
+                        (
+                            *entity.get_by_id(position).unwrap().deref::<[u64; 2]>(),
+                            entity.get_by_id(health).map(|ptr| *ptr.deref::<u32>()),
+                        )
+                    }
+                })
+                .collect();
+            values.sort();
+            values
+        };
+        let original = read(&world);
+
+        // SAFETY: neither component has padding
+        let snapshot = unsafe { world.serialize_dynamic(&[position, health]) }.unwrap();
+        assert_eq!(snapshot.entities.len(), 5);
+
+        world.clear_entities();
+        assert_eq!(world.entities().len(), 0);
+
+        // SAFETY: the snapshot was taken from this world
+        let spawned = unsafe { world.apply_dynamic_snapshot(&snapshot) }.unwrap();
+        assert_eq!(spawned.len(), 5);
+        assert_eq!(read(&world), original);
+        // Entities are spawned in groups, but returned in the order of the snapshot
+        for (entity, snapshot_entity) in spawned.iter().zip(&snapshot.entities) {
+            let (_, bytes) = &snapshot_entity.values[0];
+            let ptr = world.get_by_id(*entity, position).unwrap();
+            // SAFETY: `Position` is `bytes.len()` bytes long
+            let value = unsafe { std::slice::from_raw_parts(ptr.as_ptr(), bytes.len()) };
+            assert_eq!(value, bytes.as_slice());
+        }
+
+        let bytes = bincode::serialize(&snapshot).unwrap();
+        let snapshot: DynamicSnapshot = bincode::deserialize(&bytes).unwrap();
+        let mut other = World::new();
+        // SAFETY: the snapshot was taken from components with the same names
+        unsafe { other.apply_dynamic_snapshot(&snapshot) }.unwrap();
+        assert_eq!(read(&other), original);
+    }
+
+    #[test]
+    fn dynamic_snapshot_refuses_drop() {
+        let mut world = World::new();
+        // SAFETY: `Box<u64>` is `Send + Sync` and the drop function drops a `Box<u64>`
+        let descriptor = unsafe {
+            ComponentDescriptor::new_with_layout(
+                "Boxed",
+                StorageType::Table,
+                Layout::new::<Box<u64>>(),
+                Some(|ptr| ptr.drop_as::<Box<u64>>()),
+            )
+        };
+        let id = world.init_component_with_descriptor(descriptor);
+
+        // SAFETY: nothing is copied
+        let result = unsafe { world.serialize_dynamic(&[id]) };
+        assert_eq!(
+            result,
+            Err(DynamicSnapshotError::NeedsDrop("Boxed".to_string()))
+        );
+    }
+
+    #[test]
+    fn dynamic_snapshot_error_registers_nothing() {
+        let snapshot = DynamicSnapshot {
+            components: vec![DynamicSnapshotComponent {
+                name: "Health".to_string(),
+                size: 4,
+                align: 4,
+                storage_type: StorageType::Table,
+            }],
+            entities: vec![DynamicSnapshotEntity {
+                entity: Entity::from_raw(0),
+                values: vec![(0, vec![0; 3])],
+            }],
+        };
+
+        let mut world = World::new();
+        // SAFETY: the snapshot is rejected before any value is read
+        let result = unsafe { world.apply_dynamic_snapshot(&snapshot) };
+        assert_eq!(
+            result,
+            Err(DynamicSnapshotError::LayoutMismatch("Health".to_string()))
+        );
+        assert!(world.components().get_id_by_name("Health").is_none());
+        assert_eq!(world.entities().len(), 0);
+    }
+}
@@ -0,0 +1,278 @@
+use std::ptr::NonNull;
+
+#[cfg(feature = "bevy_reflect")]
+use bevy_reflect::Reflect;
+
+#[cfg(feature = "bevy_reflect")]
+use crate::reflect::{AppTypeRegistry, ReflectComponent};
+use crate::{
+    component::ComponentId,
+    entity::Entity,
+    query::DebugCheckedUnwrap,
+    storage::BlobVec,
+    world::{error::CloneEntityError, EntityWorldMut, World},
+};
+
+/// The values of the components of an entity, cloned before they are inserted into another one.
+#[derive(Default)]
+struct ClonedComponents {
+    ids: Vec<ComponentId>,
+    // Each of these holds exactly one value, of the component at the same index in `ids`
+    values: Vec<BlobVec>,
+    #[cfg(feature = "bevy_reflect")]
+    reflected: Vec<(ReflectComponent, Box<dyn Reflect>)>,
+}
+
+impl ClonedComponents {
+    fn insert_into(mut self, entity: &mut EntityWorldMut) {
+        if !self.ids.is_empty() {
+            let values = self.values.iter_mut().map(|value| {
+                // SAFETY: each `BlobVec` holds one value, which is only moved out here
+                unsafe { value.swap_remove_and_forget_unchecked(0) }
+            });
+            // SAFETY: the values were cloned from components of the same world, with the ids
+            // at the same index
+            unsafe { entity.insert_by_ids(&self.ids, values) };
+        }
+        #[cfg(feature = "bevy_reflect")]
+        for (reflect_component, value) in &self.reflected {
+            reflect_component.insert(entity, value.as_ref());
+        }
+    }
+}
+
+impl World {
+    /// Spawns a new entity with a clone of each component of `source`, and returns it.
+    ///
+    /// Components registered with a [`ComponentCloneFn`](crate::component::ComponentCloneFn),
+    /// see [`ComponentDescriptor::with_clone`](crate::component::ComponentDescriptor::with_clone),
+    /// are cloned with it. Other components are cloned through reflection, if their type is
+    /// registered with [`ReflectComponent`] in the `AppTypeRegistry` resource.
+    ///
+    /// Hooks of the components are run as they are inserted into the new entity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `source` does not exist, or one of its components cannot be cloned. See
+    /// [`World::try_clone_entity`] for a version which returns an error instead.
+    #[track_caller]
+    pub fn clone_entity(&mut self, source: Entity) -> Entity {
+        match self.try_clone_entity(source) {
+            Ok(entity) => entity,
+            Err(err) => panic!("Could not clone entity {source:?}: {err}"),
+        }
+    }
+
+    /// Spawns a new entity with a clone of each component of `source`, and returns it.
+    ///
+    /// See [`World::clone_entity`] for how components are cloned.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CloneEntityError`] without spawning anything if `source` does not exist, or
+    /// one of its components cannot be cloned.
+    pub fn try_clone_entity(&mut self, source: Entity) -> Result<Entity, CloneEntityError> {
+        let cloned = self.clone_components(source)?;
+        let mut entity = self.spawn_empty();
+        cloned.insert_into(&mut entity);
+        Ok(entity.id())
+    }
+
+    /// Inserts a clone of each component of `source` into `target`.
+    pub(crate) fn clone_entity_into(
+        &mut self,
+        source: Entity,
+        target: Entity,
+    ) -> Result<(), CloneEntityError> {
+        let cloned = self.clone_components(source)?;
+        let Some(mut entity) = self.get_entity_mut(target) else {
+            return Err(CloneEntityError::NoSuchEntity(target));
+        };
+        cloned.insert_into(&mut entity);
+        Ok(())
+    }
+
+    fn clone_components(&self, source: Entity) -> Result<ClonedComponents, CloneEntityError> {
+        let Some(entity) = self.get_entity(source) else {
+            return Err(CloneEntityError::NoSuchEntity(source));
+        };
+        #[cfg(feature = "bevy_reflect")]
+        let registry = self
+            .get_resource::<AppTypeRegistry>()
+            .map(|registry| registry.read());
+
+        let mut cloned = ClonedComponents::default();
+        for id in entity.archetype().components() {
+            // SAFETY: the components of an archetype exist in its world
+            let info = unsafe { self.components().get_info_unchecked(id) };
+            // SAFETY: the entity has the component
+            let ptr = unsafe { entity.get_by_id(id).debug_checked_unwrap() };
+
+            if let Some(clone) = info.clone_fn() {
+                // SAFETY: `drop` is the drop function of the values with this layout
+                let mut value = unsafe { BlobVec::new(info.layout(), info.drop(), 1) };
+                // SAFETY:
+                // - the `BlobVec` has room for one value, which is written by `clone`
+                // - the length is only set once the value has been written
+                unsafe {
+                    clone(ptr, NonNull::from(value.get_ptr_mut()));
+                    value.set_len(1);
+                }
+                cloned.ids.push(id);
+                cloned.values.push(value);
+                continue;
+            }
+
+            #[cfg(feature = "bevy_reflect")]
+            if let Some(reflect_component) = info
+                .type_id()
+                .zip(registry.as_ref())
+                .and_then(|(type_id, registry)| registry.get_type_data::<ReflectComponent>(type_id))
+            {
+                if let Some(value) = reflect_component.reflect(entity) {
+                    cloned
+                        .reflected
+                        .push((reflect_component.clone(), value.clone_value()));
+                    continue;
+                }
+            }
+
+            return Err(CloneEntityError::Uncloneable(info.name().to_string()));
+        }
+        Ok(cloned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{alloc::Layout, ptr::NonNull};
+
+    use crate::{
+        self as bevy_ecs,
+        component::{ComponentDescriptor, ComponentId, StorageType},
+        prelude::*,
+        ptr::{OwningPtr, Ptr},
+        world::error::CloneEntityError,
+    };
+
+    #[derive(Component)]
+    struct Unreflected;
+
+    fn init_name(world: &mut World) -> ComponentId {
+        // SAFETY: the component is a `String`
+        unsafe fn clone_string(source: Ptr<'_>, target: NonNull<u8>) {
+            let value = source.deref::<String>().clone();
+            target.cast::<String>().as_ptr().write(value);
+        }
+
+        // SAFETY: the component is a `String`
+        unsafe fn drop_string(value: OwningPtr<'_>) {
+            value.drop_as::<String>();
+        }
+
+        // SAFETY:
+        // - `drop_string` and `clone_string` are used on values of the layout of `String`
+        // - `String` is `Send + Sync`
+        let descriptor = unsafe {
+            ComponentDescriptor::new_with_layout(
+                "Name",
+                StorageType::SparseSet,
+                Layout::new::<String>(),
+                Some(drop_string),
+            )
+            .with_clone(clone_string)
+        };
+        world.init_component_with_descriptor(descriptor)
+    }
+
+    fn get_name(world: &World, entity: Entity, id: ComponentId) -> &str {
+        let ptr = world.entity(entity).get_by_id(id).unwrap();
+        // SAFETY: the component is a `String`
+        unsafe { ptr.deref::<String>() }
+    }
+
+    #[test]
+    fn clone_entity_with_clone_fn() {
+        let mut world = World::new();
+        let name = init_name(&mut world);
+        let source = world.spawn_empty().id();
+        OwningPtr::make("a".to_string(), |ptr| {
+            // SAFETY: the component is a `String`
+            unsafe { world.entity_mut(source).insert_by_id(name, ptr) };
+        });
+
+        let clone = world.clone_entity(source);
+        assert_ne!(clone, source);
+        assert_eq!(get_name(&world, clone, name), "a");
+
+        world.despawn(source);
+        assert_eq!(get_name(&world, clone, name), "a");
+    }
+
+    #[test]
+    fn clone_entity_errors() {
+        let mut world = World::new();
+        let source = world.spawn(Unreflected).id();
+        let entities = world.entities().len();
+        assert_eq!(
+            world.try_clone_entity(source),
+            Err(CloneEntityError::Uncloneable(
+                std::any::type_name::<Unreflected>().to_string()
+            ))
+        );
+        assert_eq!(world.entities().len(), entities);
+
+        world.despawn(source);
+        assert_eq!(
+            world.try_clone_entity(source),
+            Err(CloneEntityError::NoSuchEntity(source))
+        );
+
+        let empty = world.spawn_empty().id();
+        let clone = world.clone_entity(empty);
+        assert_eq!(world.entity(clone).archetype().components().count(), 0);
+    }
+
+    #[cfg(feature = "bevy_reflect")]
+    #[derive(Component, bevy_reflect::Reflect, Default, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct Health(u32);
+
+    #[cfg(feature = "bevy_reflect")]
+    #[test]
+    fn clone_entity_with_reflect() {
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        world
+            .resource::<AppTypeRegistry>()
+            .write()
+            .register::<Health>();
+        let name = init_name(&mut world);
+        let source = world.spawn(Health(10)).id();
+        OwningPtr::make("a".to_string(), |ptr| {
+            // SAFETY: the component is a `String`
+            unsafe { world.entity_mut(source).insert_by_id(name, ptr) };
+        });
+
+        let clone = world.clone_entity(source);
+        assert_eq!(world.get::<Health>(clone), Some(&Health(10)));
+        assert_eq!(get_name(&world, clone, name), "a");
+    }
+
+    #[cfg(feature = "bevy_reflect")]
+    #[test]
+    fn clone_entity_command() {
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        world
+            .resource::<AppTypeRegistry>()
+            .write()
+            .register::<Health>();
+        let source = world.spawn(Health(10)).id();
+
+        let mut queue = crate::system::CommandQueue::default();
+        let clone = Commands::new(&mut queue, &world).clone_entity(source).id();
+        queue.apply(&mut world);
+        assert_eq!(world.get::<Health>(clone), Some(&Health(10)));
+    }
+}
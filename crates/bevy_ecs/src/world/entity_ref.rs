@@ -7,11 +7,15 @@ use crate::{
     query::{Access, DebugCheckedUnwrap},
     removal_detection::RemovedComponentEvents,
     storage::Storages,
-    world::{Mut, World},
+    system::CommandQueue,
+    world::{
+        error::{InsertByIdsError, InsertTypedError},
+        ComponentHookKind, Mut, World,
+    },
 };
 use bevy_ptr::{OwningPtr, Ptr};
 use bevy_utils::tracing::debug;
-use std::{any::TypeId, marker::PhantomData};
+use std::{alloc::Layout, any::TypeId, marker::PhantomData};
 
 use super::{unsafe_world_cell::UnsafeEntityCell, Ref};
 
@@ -393,6 +397,7 @@ pub struct EntityWorldMut<'w> {
 
 impl<'w> EntityWorldMut<'w> {
     fn as_unsafe_entity_cell_readonly(&self) -> UnsafeEntityCell<'_> {
+        self.assert_not_despawned();
         UnsafeEntityCell::new(
             self.world.as_unsafe_world_cell_readonly(),
             self.entity,
@@ -400,6 +405,7 @@ impl<'w> EntityWorldMut<'w> {
         )
     }
     fn as_unsafe_entity_cell(&mut self) -> UnsafeEntityCell<'_> {
+        self.assert_not_despawned();
         UnsafeEntityCell::new(
             self.world.as_unsafe_world_cell(),
             self.entity,
@@ -407,6 +413,7 @@ impl<'w> EntityWorldMut<'w> {
         )
     }
     fn into_unsafe_entity_cell(self) -> UnsafeEntityCell<'w> {
+        self.assert_not_despawned();
         UnsafeEntityCell::new(
             self.world.as_unsafe_world_cell(),
             self.entity,
@@ -446,12 +453,14 @@ impl<'w> EntityWorldMut<'w> {
     /// Gets metadata indicating the location where the current entity is stored.
     #[inline]
     pub fn location(&self) -> EntityLocation {
+        self.assert_not_despawned();
         self.location
     }
 
     /// Returns the archetype that the current entity belongs to.
     #[inline]
     pub fn archetype(&self) -> &Archetype {
+        self.assert_not_despawned();
         &self.world.archetypes[self.location.archetype_id]
     }
 
@@ -569,6 +578,7 @@ impl<'w> EntityWorldMut<'w> {
     ///
     /// This will overwrite any previous value(s) of the same component type.
     pub fn insert<T: Bundle>(&mut self, bundle: T) -> &mut Self {
+        self.assert_not_despawned();
         let change_tick = self.world.change_tick();
         let bundle_info = self
             .world
@@ -605,6 +615,7 @@ impl<'w> EntityWorldMut<'w> {
         component_id: ComponentId,
         component: OwningPtr<'_>,
     ) -> &mut Self {
+        self.assert_not_despawned();
         let change_tick = self.world.change_tick();
 
         let bundles = &mut self.world.bundles;
@@ -620,6 +631,7 @@ impl<'w> EntityWorldMut<'w> {
             change_tick,
         );
 
+        let old_archetype = self.location.archetype_id;
         self.location = insert_dynamic_bundle(
             bundle_inserter,
             self.entity,
@@ -627,10 +639,58 @@ impl<'w> EntityWorldMut<'w> {
             Some(component).into_iter(),
             Some(storage_type).into_iter(),
         );
+        self.trigger_insert_hooks(old_archetype, &[component_id]);
 
         self
     }
 
+    /// Inserts a dynamic [`Component`] into the entity, initialized from a typed value.
+    ///
+    /// This will overwrite any previous value(s) of the same component type.
+    ///
+    /// This is the safe counterpart to [`EntityWorldMut::insert_by_id`]: the value's layout is
+    /// checked against the layout registered for the component, so it can be used for components
+    /// created with [`ComponentDescriptor::new_with_layout`](crate::component::ComponentDescriptor::new_with_layout).
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`InsertTypedError`] and leaves the entity untouched if the component does not
+    /// exist, is a Rust type other than `T`, has a drop function, or has a different layout to `T`.
+    pub fn insert_typed_by_id<T: Copy + Send + Sync + 'static>(
+        &mut self,
+        component_id: ComponentId,
+        value: T,
+    ) -> Result<&mut Self, InsertTypedError> {
+        let Some(info) = self.world.components().get_info(component_id) else {
+            return Err(InsertTypedError::UnknownComponent(component_id));
+        };
+        if info
+            .type_id()
+            .is_some_and(|type_id| type_id != TypeId::of::<T>())
+        {
+            return Err(InsertTypedError::TypeMismatch(component_id));
+        }
+        if info.drop().is_some() {
+            return Err(InsertTypedError::NeedsDrop(component_id));
+        }
+        let layout = Layout::new::<T>();
+        if info.layout() != layout {
+            return Err(InsertTypedError::LayoutMismatch {
+                id: component_id,
+                expected: info.layout(),
+                found: layout,
+            });
+        }
+
+        OwningPtr::make(value, |ptr| {
+            // SAFETY:
+            // - The component id was found in this world
+            // - The component is either `T` or has no drop function and the same layout as `T`
+            unsafe { self.insert_by_id(component_id, ptr) }
+        });
+        Ok(self)
+    }
+
     /// Inserts a dynamic [`Bundle`] into the entity.
     ///
     /// This will overwrite any previous value(s) of the same component type.
@@ -648,6 +708,7 @@ impl<'w> EntityWorldMut<'w> {
         component_ids: &[ComponentId],
         iter_components: I,
     ) -> &mut Self {
+        self.assert_not_despawned();
         let change_tick = self.world.change_tick();
 
         let bundles = &mut self.world.bundles;
@@ -663,6 +724,7 @@ impl<'w> EntityWorldMut<'w> {
             change_tick,
         );
 
+        let old_archetype = self.location.archetype_id;
         self.location = insert_dynamic_bundle(
             bundle_inserter,
             self.entity,
@@ -670,10 +732,50 @@ impl<'w> EntityWorldMut<'w> {
             iter_components,
             storage_types.iter().cloned(),
         );
+        self.trigger_insert_hooks(old_archetype, component_ids);
 
         self
     }
 
+    /// Checked version of [`EntityWorldMut::insert_by_ids`], which validates the component ids
+    /// and the number of values before touching any storage.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`InsertByIdsError`] and leaves the entity untouched if a component does not
+    /// exist, appears more than once, or the number of values does not match the number of
+    /// component ids. The values are not consumed, so they are still owned by the caller.
+    ///
+    /// # Safety
+    /// - Each [`OwningPtr`] must be a valid reference to the type represented by the [`ComponentId`]
+    ///   at the same position
+    pub unsafe fn try_insert_by_ids<'a, I: ExactSizeIterator<Item = OwningPtr<'a>>>(
+        &mut self,
+        component_ids: &[ComponentId],
+        iter_components: I,
+    ) -> Result<&mut Self, InsertByIdsError> {
+        if iter_components.len() != component_ids.len() {
+            return Err(InsertByIdsError::LengthMismatch {
+                expected: component_ids.len(),
+                found: iter_components.len(),
+            });
+        }
+        let components = self.world.components();
+        for (i, &id) in component_ids.iter().enumerate() {
+            if components.get_info(id).is_none() {
+                return Err(InsertByIdsError::UnknownComponent(id));
+            }
+            if component_ids[..i].contains(&id) {
+                return Err(InsertByIdsError::DuplicateComponent(id));
+            }
+        }
+
+        // SAFETY:
+        // - The component ids were found in this world
+        // - The caller ensures the values match the component ids
+        Ok(self.insert_by_ids(component_ids, iter_components))
+    }
+
     /// Removes all components in the [`Bundle`] from the entity and returns their previous values.
     ///
     /// **Note:** If the entity does not have every component in the bundle, this method will not
@@ -681,6 +783,7 @@ impl<'w> EntityWorldMut<'w> {
     // TODO: BundleRemover?
     #[must_use]
     pub fn take<T: Bundle>(&mut self) -> Option<T> {
+        self.assert_not_despawned();
         let archetypes = &mut self.world.archetypes;
         let storages = &mut self.world.storages;
         let components = &mut self.world.components;
@@ -894,6 +997,7 @@ impl<'w> EntityWorldMut<'w> {
     /// See [`EntityCommands::remove`](crate::system::EntityCommands::remove) for more details.
     // TODO: BundleRemover?
     pub fn remove<T: Bundle>(&mut self) -> &mut Self {
+        self.assert_not_despawned();
         let archetypes = &mut self.world.archetypes;
         let storages = &mut self.world.storages;
         let components = &mut self.world.components;
@@ -926,20 +1030,118 @@ impl<'w> EntityWorldMut<'w> {
     ///
     /// See [`EntityCommands::retain`](crate::system::EntityCommands::retain) for more details.
     pub fn retain<T: Bundle>(&mut self) -> &mut Self {
+        self.assert_not_despawned();
+        let retained_bundle_info = self
+            .world
+            .bundles
+            .init_info::<T>(&mut self.world.components, &mut self.world.storages);
+        let to_remove = self.world.archetypes[self.location.archetype_id]
+            .components()
+            .filter(|c| !retained_bundle_info.components().contains(c))
+            .collect::<Vec<_>>();
+        self.remove_dynamic_bundle(&to_remove)
+    }
+
+    /// Removes a dynamic [`Component`] from the entity if it exists.
+    ///
+    /// You should prefer to use the typed API [`EntityWorldMut::remove`] where possible.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the provided [`ComponentId`] does not exist in the [`World`].
+    pub fn remove_by_id(&mut self, component_id: ComponentId) -> &mut Self {
+        self.assert_not_despawned();
+        let queue = self.trigger_remove_hooks(&[component_id]);
+
         let archetypes = &mut self.world.archetypes;
         let storages = &mut self.world.storages;
         let components = &mut self.world.components;
         let entities = &mut self.world.entities;
         let removed_components = &mut self.world.removed_components;
 
-        let retained_bundle_info = self.world.bundles.init_info::<T>(components, storages);
+        let (bundle_info, _) = self
+            .world
+            .bundles
+            .init_component_info(components, component_id);
         let old_location = self.location;
-        let old_archetype = &mut archetypes[old_location.archetype_id];
 
-        let to_remove = &old_archetype
+        // SAFETY: The component exists in `bundle_info` because `Bundles::init_component_info`
+        // initializes a `BundleInfo` containing only `component_id`.
+        unsafe {
+            Self::remove_bundle_info(
+                self.entity,
+                &mut self.location,
+                old_location,
+                bundle_info,
+                archetypes,
+                storages,
+                components,
+                entities,
+                removed_components,
+            );
+        }
+
+        self.apply_hook_commands(queue);
+
+        self
+    }
+
+    /// Removes the dynamic [`Component`]s with the given ids from the entity, ignoring those it
+    /// does not have. The entity is only moved to a new archetype once.
+    ///
+    /// You should prefer to use the typed API [`EntityWorldMut::remove`] where possible.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the provided [`ComponentId`]s do not exist in the [`World`].
+    pub fn remove_by_ids(&mut self, component_ids: &[ComponentId]) -> &mut Self {
+        let mut to_remove = component_ids.to_vec();
+        to_remove.sort_unstable();
+        to_remove.dedup();
+        self.remove_dynamic_bundle(&to_remove)
+    }
+
+    /// Removes any components except those with the given ids from the entity.
+    ///
+    /// This is the dynamic version of [`EntityWorldMut::retain`]. Passing no ids removes all the
+    /// components of the entity.
+    pub fn retain_by_ids(&mut self, component_ids: &[ComponentId]) -> &mut Self {
+        self.assert_not_despawned();
+        let to_remove = self.world.archetypes[self.location.archetype_id]
             .components()
-            .filter(|c| !retained_bundle_info.components().contains(c))
+            .filter(|c| !component_ids.contains(c))
             .collect::<Vec<_>>();
+        self.remove_dynamic_bundle(&to_remove)
+    }
+
+    /// Removes all the components of the entity which are not typed, see
+    /// [`Components::is_typed`], keeping those which were inserted with the typed API.
+    pub fn clear_dynamic(&mut self) -> &mut Self {
+        self.assert_not_despawned();
+        let components = &self.world.components;
+        let to_remove = self.world.archetypes[self.location.archetype_id]
+            .components()
+            .filter(|&c| !components.is_typed(c))
+            .collect::<Vec<_>>();
+        self.remove_dynamic_bundle(&to_remove)
+    }
+
+    /// Removes the components with the given ids, which must not contain duplicates, running
+    /// their hooks.
+    fn remove_dynamic_bundle(&mut self, to_remove: &[ComponentId]) -> &mut Self {
+        self.assert_not_despawned();
+        if to_remove.is_empty() {
+            return self;
+        }
+        let queue = self.trigger_remove_hooks(to_remove);
+
+        let archetypes = &mut self.world.archetypes;
+        let storages = &mut self.world.storages;
+        let components = &mut self.world.components;
+        let entities = &mut self.world.entities;
+        let removed_components = &mut self.world.removed_components;
+
+        let old_location = self.location;
         let remove_bundle_info = self
             .world
             .bundles
@@ -961,6 +1163,7 @@ impl<'w> EntityWorldMut<'w> {
                 removed_components,
             );
         }
+        self.apply_hook_commands(queue);
 
         self
     }
@@ -968,8 +1171,17 @@ impl<'w> EntityWorldMut<'w> {
     /// Despawns the current entity.
     ///
     /// See [`World::despawn`] for more details.
-    pub fn despawn(self) {
+    pub fn despawn(mut self) {
+        self.assert_not_despawned();
         debug!("Despawning entity {:?}", self.entity);
+        let archetype = &self.world.archetypes[self.location.archetype_id];
+        let queue = if self.world.has_component_hooks(archetype.components()) {
+            let component_ids: Vec<_> = archetype.components().collect();
+            self.trigger_remove_hooks(&component_ids)
+        } else {
+            None
+        };
+
         let world = self.world;
         world.flush();
         let location = world
@@ -1031,6 +1243,96 @@ impl<'w> EntityWorldMut<'w> {
             world.archetypes[moved_location.archetype_id]
                 .set_entity_table_row(moved_location.archetype_row, table_row);
         }
+
+        if let Some(mut queue) = queue {
+            queue.apply(world);
+        }
+    }
+
+    /// Runs the `on_add` hooks of the inserted components which were not in `old_archetype`,
+    /// then the `on_insert` hooks of all of them, and applies the commands they queued.
+    fn trigger_insert_hooks(&mut self, old_archetype: ArchetypeId, component_ids: &[ComponentId]) {
+        if !self
+            .world
+            .has_component_hooks(component_ids.iter().copied())
+        {
+            return;
+        }
+        let old_archetype = &self.world.archetypes[old_archetype];
+        let added: Vec<_> = component_ids
+            .iter()
+            .copied()
+            .filter(|&id| !old_archetype.contains(id))
+            .collect();
+        let mut queue = CommandQueue::default();
+        self.world.trigger_component_hooks(
+            ComponentHookKind::OnAdd,
+            self.entity,
+            added,
+            &mut queue,
+        );
+        self.world.trigger_component_hooks(
+            ComponentHookKind::OnInsert,
+            self.entity,
+            component_ids.iter().copied(),
+            &mut queue,
+        );
+        self.apply_hook_commands(Some(queue));
+    }
+
+    /// Runs the `on_remove` hooks of the components the entity has, before they are removed.
+    /// Returns the commands they queued, which must be applied with
+    /// [`EntityWorldMut::apply_hook_commands`] once the components are removed.
+    fn trigger_remove_hooks(&mut self, component_ids: &[ComponentId]) -> Option<CommandQueue> {
+        if !self
+            .world
+            .has_component_hooks(component_ids.iter().copied())
+        {
+            return None;
+        }
+        let archetype = &self.world.archetypes[self.location.archetype_id];
+        let removed: Vec<_> = component_ids
+            .iter()
+            .copied()
+            .filter(|&id| archetype.contains(id))
+            .collect();
+        let mut queue = CommandQueue::default();
+        self.world.trigger_component_hooks(
+            ComponentHookKind::OnRemove,
+            self.entity,
+            removed,
+            &mut queue,
+        );
+        Some(queue)
+    }
+
+    /// Applies the commands queued by component hooks, and updates the location of the entity.
+    ///
+    /// If the commands despawned the entity, it is marked as despawned instead, see
+    /// [`EntityWorldMut::is_despawned`].
+    fn apply_hook_commands(&mut self, queue: Option<CommandQueue>) {
+        if let Some(mut queue) = queue {
+            queue.apply(self.world);
+            self.update_location();
+        }
+    }
+
+    /// Returns `true` if the entity was despawned while this [`EntityWorldMut`] was alive, for
+    /// example by the commands of a component hook or inside [`EntityWorldMut::world_scope`].
+    ///
+    /// Any further use of a despawned [`EntityWorldMut`] other than [`EntityWorldMut::id`],
+    /// [`EntityWorldMut::world`] and [`EntityWorldMut::into_world_mut`] will panic.
+    #[inline]
+    pub fn is_despawned(&self) -> bool {
+        self.location.archetype_id == ArchetypeId::INVALID
+    }
+
+    #[inline]
+    #[track_caller]
+    fn assert_not_despawned(&self) {
+        if self.is_despawned() {
+            panic!("Entity {:?} does not exist", self.entity);
+        }
     }
 
     /// Gets read-only access to the world that the current entity belongs to.
@@ -1106,8 +1408,15 @@ impl<'w> EntityWorldMut<'w> {
     ///
     /// This is *only* required when using the unsafe function [`EntityWorldMut::world_mut`],
     /// which enables the location to change.
+    ///
+    /// If the entity no longer exists, it is marked as despawned, see
+    /// [`EntityWorldMut::is_despawned`].
     pub fn update_location(&mut self) {
-        self.location = self.world.entities().get(self.entity).unwrap();
+        self.location = self
+            .world
+            .entities()
+            .get(self.entity)
+            .unwrap_or(EntityLocation::INVALID);
     }
 
     /// Gets an Entry into the world for this entity and component for in-place manipulation.
@@ -1581,6 +1890,20 @@ impl<'w> FilteredEntityRef<'w> {
         self.entity.contains_type_id(type_id)
     }
 
+    /// Returns the [`StorageType`] of the component identified by `component_id`, or `None` if
+    /// the current entity does not have it.
+    ///
+    /// Components stored in [`StorageType::SparseSet`] are looked up for each entity, even when
+    /// the query iterates whole tables.
+    #[inline]
+    pub fn storage_type(&self, component_id: ComponentId) -> Option<StorageType> {
+        if !self.entity.contains_id(component_id) {
+            return None;
+        }
+        let info = self.entity.world().components().get_info(component_id)?;
+        Some(info.storage_type())
+    }
+
     /// Gets access to the component of type `T` for the current entity.
     /// Returns `None` if the entity does not have a component of type `T`.
     #[inline]
@@ -1653,6 +1976,42 @@ impl<'w> FilteredEntityRef<'w> {
             // SAFETY: We have read access so we must have the component
             .then(|| unsafe { self.entity.get_by_id(component_id).debug_checked_unwrap() })
     }
+
+    /// Lazily iterates over the component of the given [`ComponentId`] as `u64` elements,
+    /// without copying it into a slice or [`Vec`].
+    ///
+    /// Returns `None` if the component is not accessible or its layout is not a whole number of
+    /// `u64`s aligned to a `u64`.
+    ///
+    /// # Safety
+    ///
+    /// Every byte of the component must be initialized, as is the case for components made
+    /// entirely of `u64`s such as `[u64; N]`.
+    #[inline]
+    pub unsafe fn iter_u64_by_id(
+        &self,
+        component_id: ComponentId,
+    ) -> Option<impl Iterator<Item = u64> + '_> {
+        let layout = self
+            .entity
+            .world()
+            .components()
+            .get_info(component_id)?
+            .layout();
+        if layout.size() % std::mem::size_of::<u64>() != 0
+            || layout.align() < std::mem::align_of::<u64>()
+        {
+            return None;
+        }
+        let ptr = self.get_by_id(component_id)?.as_ptr().cast::<u64>();
+        let len = layout.size() / std::mem::size_of::<u64>();
+        Some((0..len).map(move |i| {
+            // SAFETY:
+            // - The pointer is aligned for `u64` and `i` is within the component
+            // - The caller ensures every byte of the component is initialized
+            unsafe { ptr.add(i).read() }
+        }))
+    }
 }
 
 impl<'w> From<FilteredEntityMut<'w>> for FilteredEntityRef<'w> {
@@ -1769,6 +2128,20 @@ impl<'w> FilteredEntityMut<'w> {
         self.entity.contains_type_id(type_id)
     }
 
+    /// Returns the [`StorageType`] of the component identified by `component_id`, or `None` if
+    /// the current entity does not have it.
+    ///
+    /// Components stored in [`StorageType::SparseSet`] are looked up for each entity, even when
+    /// the query iterates whole tables.
+    #[inline]
+    pub fn storage_type(&self, component_id: ComponentId) -> Option<StorageType> {
+        if !self.entity.contains_id(component_id) {
+            return None;
+        }
+        let info = self.entity.world().components().get_info(component_id)?;
+        Some(info.storage_type())
+    }
+
     /// Gets access to the component of type `T` for the current entity.
     /// Returns `None` if the entity does not have a component of type `T`.
     #[inline]
@@ -1837,6 +2210,9 @@ impl<'w> FilteredEntityMut<'w> {
     ///
     /// Unlike [`FilteredEntityMut::get_mut`], this returns a raw pointer to the component,
     /// which is only valid while the [`FilteredEntityMut`] is alive.
+    ///
+    /// Returns `None` if the query only has read access to the component, or no access at all.
+    /// Mutably dereferencing the returned [`MutUntyped`] marks the component as changed.
     #[inline]
     pub fn get_mut_by_id(&mut self, component_id: ComponentId) -> Option<MutUntyped<'_>> {
         // SAFETY: We have write access so we must have the component
@@ -2048,9 +2424,23 @@ pub(crate) unsafe fn take_component<'a>(
 #[cfg(test)]
 mod tests {
     use bevy_ptr::OwningPtr;
-    use std::panic::AssertUnwindSafe;
+    use std::{
+        alloc::Layout,
+        panic::AssertUnwindSafe,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
 
-    use crate::{self as bevy_ecs, component::ComponentId, prelude::*, system::assert_is_system};
+    use crate::{
+        self as bevy_ecs,
+        component::{ComponentDescriptor, ComponentId, StorageType},
+        prelude::*,
+        query::QueryBuilder,
+        system::assert_is_system,
+        world::{
+            error::{InsertByIdsError, InsertTypedError},
+            FilteredEntityMut, FilteredEntityRef,
+        },
+    };
 
     #[test]
     fn sorted_remove() {
@@ -2360,6 +2750,95 @@ mod tests {
         assert_eq!(world.entity(e2).get::<Dense>().unwrap(), &Dense(1));
     }
 
+    #[test]
+    fn entity_mut_remove_by_id() {
+        #[derive(Component)]
+        struct Marker<const N: usize>;
+
+        let mut world = World::new();
+        let ent = world.spawn((Marker::<1>, Marker::<2>)).id();
+        let id = world.init_component::<Marker<1>>();
+        let missing = world.init_component::<Marker<3>>();
+
+        world.entity_mut(ent).remove_by_id(id).remove_by_id(missing);
+        assert!(world.entity(ent).get::<Marker<1>>().is_none());
+        assert!(world.entity(ent).get::<Marker<2>>().is_some());
+    }
+
+    #[test]
+    fn entity_mut_remove_by_ids() {
+        #[derive(Component)]
+        struct Marker<const N: usize>;
+
+        let mut world = World::new();
+        let ent = world.spawn((Marker::<1>, Marker::<2>, Marker::<3>)).id();
+        let id_1 = world.init_component::<Marker<1>>();
+        let id_3 = world.init_component::<Marker<3>>();
+        let missing = world.init_component::<Marker<4>>();
+
+        world
+            .entity_mut(ent)
+            .remove_by_ids(&[id_3, missing, id_1, id_3]);
+        assert_eq!(world.entity(ent).archetype().components().count(), 1);
+        assert!(world.entity(ent).get::<Marker<2>>().is_some());
+    }
+
+    #[test]
+    fn entity_mut_retain_by_ids() {
+        #[derive(Component)]
+        struct Marker<const N: usize>;
+
+        let mut world = World::new();
+        let ent = world.spawn((Marker::<1>, Marker::<2>, Marker::<3>)).id();
+        let id_2 = world.init_component::<Marker<2>>();
+        let missing = world.init_component::<Marker<4>>();
+
+        world.entity_mut(ent).retain_by_ids(&[id_2, missing]);
+        let archetype = world.entity(ent).archetype();
+        assert_eq!(archetype.components().collect::<Vec<_>>(), vec![id_2]);
+
+        world.entity_mut(ent).retain_by_ids(&[]);
+        assert_eq!(world.entity(ent).archetype().components().count(), 0);
+    }
+
+    #[test]
+    fn entity_mut_clear_dynamic() {
+        #[derive(Component)]
+        struct Marker;
+
+        let mut world = World::new();
+        // SAFETY: `u32` is `Send + Sync` and does not need to be dropped
+        let runtime_id = world.init_component_with_descriptor(unsafe {
+            ComponentDescriptor::new_with_layout(
+                "Runtime",
+                StorageType::SparseSet,
+                Layout::new::<u32>(),
+                None,
+            )
+        });
+        let untyped_id = world.init_component_with_descriptor(ComponentDescriptor::new::<Marker>());
+        let typed_id = world.init_component::<Marker>();
+        assert!(world.components().is_typed(typed_id));
+        assert!(!world.components().is_typed(untyped_id));
+        assert!(!world.components().is_typed(runtime_id));
+
+        let mut entity = world.spawn(Marker);
+        // SAFETY: the components have the layouts of `u32` and `Marker`
+        unsafe {
+            OwningPtr::make(1u32, |ptr| {
+                entity.insert_by_id(runtime_id, ptr);
+            });
+            OwningPtr::make(Marker, |ptr| {
+                entity.insert_by_id(untyped_id, ptr);
+            });
+        }
+        entity.clear_dynamic();
+        assert_eq!(
+            entity.archetype().components().collect::<Vec<_>>(),
+            vec![typed_id]
+        );
+    }
+
     #[test]
     fn entity_mut_insert_by_id() {
         let mut world = World::new();
@@ -2388,6 +2867,237 @@ mod tests {
         assert_eq!(components, vec![&TestComponent(42), &TestComponent(84)]);
     }
 
+    #[test]
+    fn entity_mut_insert_typed_by_id() {
+        let mut world = World::new();
+        // SAFETY: `[u64; 3]` is `Send + Sync` and has no drop function
+        let descriptor = unsafe {
+            ComponentDescriptor::new_with_layout(
+                "Elements",
+                StorageType::Table,
+                Layout::new::<[u64; 3]>(),
+                None,
+            )
+        };
+        let elements_id = world.init_component_with_descriptor(descriptor);
+        let test_component_id = world.init_component::<TestComponent>();
+
+        let mut entity = world.spawn_empty();
+        entity
+            .insert_typed_by_id(elements_id, [1u64, 2, 3])
+            .unwrap();
+        let ptr = entity.get_by_id(elements_id).unwrap();
+        // SAFETY: the component was just inserted as `[u64; 3]`
+        assert_eq!(unsafe { ptr.deref::<[u64; 3]>() }, &[1, 2, 3]);
+
+        let entity_id = entity.id();
+        assert_eq!(
+            entity.insert_typed_by_id(elements_id, [1u32, 2, 3]).err(),
+            Some(InsertTypedError::LayoutMismatch {
+                id: elements_id,
+                expected: Layout::new::<[u64; 3]>(),
+                found: Layout::new::<[u32; 3]>(),
+            })
+        );
+        assert_eq!(
+            entity.insert_typed_by_id(test_component_id, 42u32).err(),
+            Some(InsertTypedError::TypeMismatch(test_component_id))
+        );
+        assert_eq!(
+            entity
+                .insert_typed_by_id(ComponentId::new(100), 42u32)
+                .err(),
+            Some(InsertTypedError::UnknownComponent(ComponentId::new(100)))
+        );
+        assert!(!world.entity(entity_id).contains_id(test_component_id));
+    }
+
+    #[test]
+    fn filtered_entity_ref_iter_u64_by_id() {
+        let mut world = World::new();
+        // SAFETY: `[u64; 1024]` is `Send + Sync` and has no drop function
+        let descriptor = unsafe {
+            ComponentDescriptor::new_with_layout(
+                "Blob",
+                StorageType::Table,
+                Layout::new::<[u64; 1024]>(),
+                None,
+            )
+        };
+        let blob_id = world.init_component_with_descriptor(descriptor);
+        let test_component_id = world.init_component::<TestComponent>();
+        let mut blob = [0u64; 1024];
+        blob.iter_mut().enumerate().for_each(|(i, e)| *e = i as u64);
+        world
+            .spawn(TestComponent(0))
+            .insert_typed_by_id(blob_id, blob)
+            .unwrap();
+
+        let mut query = QueryBuilder::<FilteredEntityRef>::new(&mut world)
+            .ref_id(blob_id)
+            .ref_id(test_component_id)
+            .build();
+        let entity = query.single(&world);
+        // SAFETY: the blob is a `[u64; 1024]` and so fully initialized
+        let sum: u64 = unsafe { entity.iter_u64_by_id(blob_id) }.unwrap().sum();
+        assert_eq!(sum, 1023 * 1024 / 2);
+        // SAFETY: `TestComponent` is a fully initialized `u32`, although too small to be read
+        assert!(unsafe { entity.iter_u64_by_id(test_component_id) }.is_none());
+    }
+
+    #[test]
+    fn entity_mut_try_insert_by_ids() {
+        let mut world = World::new();
+        let test_component_id = world.init_component::<TestComponent>();
+        let test_component_2_id = world.init_component::<TestComponent2>();
+        let unknown_id = ComponentId::new(world.components().len());
+        let mut entity = world.spawn(TestComponent(1));
+        let archetype = entity.archetype().id();
+
+        OwningPtr::make(TestComponent(42), |ptr| {
+            let ids = [test_component_id, test_component_2_id];
+            // SAFETY: `ptr` matches the first component id
+            let result = unsafe { entity.try_insert_by_ids(&ids, std::iter::once(ptr)) };
+            assert_eq!(
+                result.err(),
+                Some(InsertByIdsError::LengthMismatch {
+                    expected: 2,
+                    found: 1
+                })
+            );
+        });
+        OwningPtr::make(TestComponent(42), |ptr1| {
+            OwningPtr::make(TestComponent2(84), |ptr2| {
+                let ids = [test_component_id, unknown_id];
+                // SAFETY: `ptr1` matches the first component id
+                let result = unsafe { entity.try_insert_by_ids(&ids, [ptr1, ptr2].into_iter()) };
+                assert_eq!(
+                    result.err(),
+                    Some(InsertByIdsError::UnknownComponent(unknown_id))
+                );
+            });
+        });
+        OwningPtr::make(TestComponent(42), |ptr1| {
+            OwningPtr::make(TestComponent(84), |ptr2| {
+                let ids = [test_component_id, test_component_id];
+                // SAFETY: `ptr1` and `ptr2` match the component ids
+                let result = unsafe { entity.try_insert_by_ids(&ids, [ptr1, ptr2].into_iter()) };
+                assert_eq!(
+                    result.err(),
+                    Some(InsertByIdsError::DuplicateComponent(test_component_id))
+                );
+            });
+        });
+        assert_eq!(entity.archetype().id(), archetype);
+        assert_eq!(entity.get::<TestComponent>(), Some(&TestComponent(1)));
+        assert!(!entity.contains::<TestComponent2>());
+
+        OwningPtr::make(TestComponent(42), |ptr1| {
+            OwningPtr::make(TestComponent2(84), |ptr2| {
+                let ids = [test_component_id, test_component_2_id];
+                // SAFETY: `ptr1` and `ptr2` match the component ids
+                let result = unsafe { entity.try_insert_by_ids(&ids, [ptr1, ptr2].into_iter()) };
+                assert!(result.is_ok());
+            });
+        });
+        assert_eq!(entity.get::<TestComponent>(), Some(&TestComponent(42)));
+        assert_eq!(entity.get::<TestComponent2>(), Some(&TestComponent2(84)));
+    }
+
+    #[test]
+    fn filtered_entity_mut_get_mut_by_id() {
+        let mut world = World::new();
+        let test_component_id = world.init_component::<TestComponent>();
+        let test_component_2_id = world.init_component::<TestComponent2>();
+        let entity_id = world.spawn((TestComponent(0), TestComponent2(0))).id();
+
+        let mut query = QueryBuilder::<FilteredEntityMut>::new(&mut world)
+            .ref_id(test_component_id)
+            .mut_id(test_component_2_id)
+            .build();
+        world.clear_trackers();
+
+        let mut entity = query.single_mut(&mut world);
+        assert!(entity.get_mut_by_id(test_component_id).is_none());
+        let component = entity.get_mut_by_id(test_component_2_id).unwrap();
+        // SAFETY: the component at `test_component_2_id` is a `TestComponent2`
+        unsafe { component.into_inner().deref_mut::<TestComponent2>().0 = 42 };
+
+        assert_eq!(
+            world.get::<TestComponent2>(entity_id),
+            Some(&TestComponent2(42))
+        );
+        let mut changed = world.query_filtered::<(), Changed<TestComponent>>();
+        assert_eq!(changed.iter(&world).count(), 0);
+        let mut changed = world.query_filtered::<(), Changed<TestComponent2>>();
+        assert_eq!(changed.iter(&world).count(), 1);
+    }
+
+    #[test]
+    fn dynamic_component_drop() {
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+        /// # Safety
+        /// `ptr` must point to a `Box<u64>`
+        unsafe fn drop_box(ptr: OwningPtr<'_>) {
+            ptr.drop_as::<Box<u64>>();
+            DROPS.fetch_add(1, Ordering::Relaxed);
+        }
+
+        for storage_type in [StorageType::Table, StorageType::SparseSet] {
+            DROPS.store(0, Ordering::Relaxed);
+            let mut world = World::new();
+            // SAFETY: `Box<u64>` is `Send + Sync` and `drop_box` drops a `Box<u64>`
+            let descriptor = unsafe {
+                ComponentDescriptor::new_with_layout(
+                    "Boxed",
+                    storage_type,
+                    Layout::new::<Box<u64>>(),
+                    Some(drop_box),
+                )
+            };
+            let id = world.init_component_with_descriptor(descriptor);
+
+            // Enough entities for the storage to reallocate and move the boxes several times
+            let entities: Vec<_> = (0..100)
+                .map(|i| {
+                    let mut entity = world.spawn_empty();
+                    OwningPtr::make(Box::new(i as u64), |ptr| {
+                        // SAFETY: `ptr` points to a `Box<u64>`, matching the component id
+                        unsafe { entity.insert_by_ids(&[id], std::iter::once(ptr)) };
+                    });
+                    entity.id()
+                })
+                .collect();
+            assert_eq!(DROPS.load(Ordering::Relaxed), 0);
+
+            for &entity in &entities[..5] {
+                let mut entity = world.entity_mut(entity);
+                OwningPtr::make(Box::new(1000u64), |ptr| {
+                    // SAFETY: `ptr` points to a `Box<u64>`, matching the component id
+                    unsafe { entity.insert_by_ids(&[id], std::iter::once(ptr)) };
+                });
+            }
+            assert_eq!(DROPS.load(Ordering::Relaxed), 5);
+
+            for (i, &entity) in entities.iter().enumerate() {
+                let ptr = world.entity(entity).get_by_id(id).unwrap();
+                let expected = if i < 5 { 1000 } else { i as u64 };
+                // SAFETY: the component is a `Box<u64>`
+                assert_eq!(**unsafe { ptr.deref::<Box<u64>>() }, expected);
+            }
+
+            for &entity in &entities[..50] {
+                world.despawn(entity);
+            }
+            world.entity_mut(entities[50]).remove_by_id(id);
+            assert_eq!(DROPS.load(Ordering::Relaxed), 56);
+
+            drop(world);
+            assert_eq!(DROPS.load(Ordering::Relaxed), 105);
+        }
+    }
+
     #[test]
     fn entity_mut_insert_bundle_by_id() {
         let mut world = World::new();
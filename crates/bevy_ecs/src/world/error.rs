@@ -1,8 +1,10 @@
 //! Contains error types returned by bevy's schedule.
 
+use std::alloc::Layout;
+
 use thiserror::Error;
 
-use crate::schedule::InternedScheduleLabel;
+use crate::{component::ComponentId, entity::Entity, schedule::InternedScheduleLabel};
 
 /// The error type returned by [`World::try_run_schedule`] if the provided schedule does not exist.
 ///
@@ -10,3 +12,120 @@ use crate::schedule::InternedScheduleLabel;
 #[derive(Error, Debug)]
 #[error("The schedule with the label {0:?} was not found.")]
 pub struct TryRunScheduleError(pub InternedScheduleLabel);
+
+/// The error type returned by [`EntityWorldMut::insert_typed_by_id`] if the value cannot be
+/// stored as the given component.
+///
+/// [`EntityWorldMut::insert_typed_by_id`]: crate::world::EntityWorldMut::insert_typed_by_id
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertTypedError {
+    /// The component id has not been registered with this world.
+    #[error("The component {0:?} does not exist in this world.")]
+    UnknownComponent(ComponentId),
+    /// The component is a Rust type other than the value's type.
+    #[error("The component {0:?} is a different Rust type than the value.")]
+    TypeMismatch(ComponentId),
+    /// The component has a drop function which cannot be run on the value.
+    #[error("The component {0:?} needs to be dropped, but the value is `Copy`.")]
+    NeedsDrop(ComponentId),
+    /// The size or alignment of the value does not match the component's layout.
+    #[error("The component {id:?} has layout {expected:?}, but the value has layout {found:?}.")]
+    LayoutMismatch {
+        /// The component the value was inserted as.
+        id: ComponentId,
+        /// The layout registered for the component.
+        expected: Layout,
+        /// The layout of the value.
+        found: Layout,
+    },
+}
+
+/// The error type returned by [`World::spawn_batch_by_ids`] if the component ids cannot be
+/// spawned together.
+///
+/// [`World::spawn_batch_by_ids`]: crate::world::World::spawn_batch_by_ids
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpawnBatchByIdsError {
+    /// The component id has not been registered with this world.
+    #[error("The component {0:?} does not exist in this world.")]
+    UnknownComponent(ComponentId),
+    /// The component id appears more than once.
+    #[error("The component {0:?} appears more than once.")]
+    DuplicateComponent(ComponentId),
+}
+
+/// The error type returned by [`EntityWorldMut::try_insert_by_ids`] if the components cannot be
+/// inserted.
+///
+/// [`EntityWorldMut::try_insert_by_ids`]: crate::world::EntityWorldMut::try_insert_by_ids
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertByIdsError {
+    /// The component id has not been registered with this world.
+    #[error("The component {0:?} does not exist in this world.")]
+    UnknownComponent(ComponentId),
+    /// The component id appears more than once.
+    #[error("The component {0:?} appears more than once.")]
+    DuplicateComponent(ComponentId),
+    /// The number of values does not match the number of component ids.
+    #[error("Expected {expected} values, one for each component id, but found {found}.")]
+    LengthMismatch {
+        /// The number of component ids.
+        expected: usize,
+        /// The number of values.
+        found: usize,
+    },
+}
+
+/// The error type returned by [`World::serialize_dynamic`] and [`World::apply_dynamic_snapshot`]
+/// if a component cannot be copied as raw bytes.
+///
+/// [`World::serialize_dynamic`]: crate::world::World::serialize_dynamic
+/// [`World::apply_dynamic_snapshot`]: crate::world::World::apply_dynamic_snapshot
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DynamicSnapshotError {
+    /// The component id has not been registered with this world.
+    #[error("The component {0:?} does not exist in this world.")]
+    UnknownComponent(ComponentId),
+    /// The component has a drop function, so copying its bytes is not a valid clone.
+    #[error("The component {0} needs to be dropped, so its bytes cannot be copied.")]
+    NeedsDrop(String),
+    /// The layout of the component does not match the layout in the snapshot.
+    #[error("The component {0} does not have the layout of the snapshot.")]
+    LayoutMismatch(String),
+}
+
+/// The error type returned by [`World::try_clone_entity`] if the entity cannot be cloned.
+///
+/// [`World::try_clone_entity`]: crate::world::World::try_clone_entity
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum CloneEntityError {
+    /// The entity to clone does not exist.
+    #[error("The entity {0:?} does not exist.")]
+    NoSuchEntity(Entity),
+    /// The component has no clone function, and is not registered for reflection.
+    #[error("The component {0} has no clone function and no registered `ReflectComponent`.")]
+    Uncloneable(String),
+}
+
+/// The error type returned by [`World::restore_component_registry`] if a component cannot be
+/// restored.
+///
+/// [`World::restore_component_registry`]: crate::world::World::restore_component_registry
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum RestoreComponentRegistryError {
+    /// Several components of the registry have the same name, so they cannot be told apart.
+    #[error("Several components of the registry are named {0}.")]
+    DuplicateName(String),
+    /// The recorded size and alignment of the component are not a valid layout.
+    #[error("The component {0} does not have a valid layout.")]
+    InvalidLayout(String),
+    /// The component registered with the same name has a different layout.
+    #[error("The component {0} is registered with a different layout.")]
+    LayoutMismatch(String),
+    /// The component registered with the same name has a different storage type.
+    #[error("The component {0} is registered with a different storage type.")]
+    StorageTypeMismatch(String),
+    /// The component needs to be dropped, so it must be registered with its drop function first.
+    #[error("The component {0} needs to be dropped, but is not registered in this world.")]
+    NeedsDrop(String),
+}
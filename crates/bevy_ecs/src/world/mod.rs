@@ -1,5 +1,9 @@
 //! Defines the [`World`] and APIs for accessing it directly.
 
+mod component_registry;
+mod deferred_world;
+mod dynamic_snapshot;
+mod entity_clone;
 mod entity_ref;
 pub mod error;
 mod spawn_batch;
@@ -7,6 +11,10 @@ pub mod unsafe_world_cell;
 mod world_cell;
 
 pub use crate::change_detection::{Mut, Ref, CHECK_TICK_THRESHOLD};
+pub use component_registry::{DynamicComponentRegistration, DynamicComponentRegistry};
+pub(crate) use deferred_world::ComponentHookKind;
+pub use deferred_world::DeferredWorld;
+pub use dynamic_snapshot::*;
 pub use entity_ref::{
     EntityMut, EntityRef, EntityWorldMut, Entry, FilteredEntityMut, FilteredEntityRef,
     OccupiedEntry, VacantEntry,
@@ -16,17 +24,19 @@ pub use world_cell::*;
 
 use crate::{
     archetype::{ArchetypeComponentId, ArchetypeId, ArchetypeRow, Archetypes},
-    bundle::{Bundle, BundleInserter, BundleSpawner, Bundles},
+    bundle::{Bundle, BundleId, BundleInserter, BundleSpawner, Bundles, DynamicBundle},
     change_detection::{MutUntyped, TicksMut},
-    component::{Component, ComponentDescriptor, ComponentId, ComponentInfo, Components, Tick},
+    component::{
+        Component, ComponentDescriptor, ComponentId, ComponentInfo, Components, StorageType, Tick,
+    },
     entity::{AllocAtWithoutReplacement, Entities, Entity, EntityLocation},
-    event::{Event, EventId, Events, SendBatchIds},
+    event::{DynamicEvents, Event, EventId, Events, SendBatchIds},
     query::{DebugCheckedUnwrap, QueryData, QueryEntityError, QueryFilter, QueryState},
     removal_detection::RemovedComponentEvents,
     schedule::{Schedule, ScheduleLabel, Schedules},
     storage::{ResourceData, Storages},
-    system::Resource,
-    world::error::TryRunScheduleError,
+    system::{CommandQueue, Resource},
+    world::error::{SpawnBatchByIdsError, TryRunScheduleError},
 };
 use bevy_ptr::{OwningPtr, Ptr};
 use bevy_utils::tracing::warn;
@@ -806,6 +816,212 @@ impl World {
         SpawnBatchIter::new(self, iter.into_iter())
     }
 
+    /// Registers a dynamic bundle of the given components, in the same order, and returns its
+    /// [`BundleId`]. Registering the same component ids again returns the same [`BundleId`].
+    ///
+    /// The bundle can be used to spawn entities with [`World::spawn_with_bundle_id`] and
+    /// [`World::spawn_batch_with_bundle_id`] without looking it up again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `component_ids` contains an id which does not exist in this world, or contains
+    /// the same id more than once.
+    pub fn register_dynamic_bundle(&mut self, component_ids: &[ComponentId]) -> BundleId {
+        self.bundles
+            .init_dynamic_info(&self.components, component_ids)
+            .0
+            .id()
+    }
+
+    /// Spawns an entity with the components of a registered bundle, returning its [`Entity`] id.
+    /// `values` yields the value of each component, in the order of [`BundleInfo::components`](crate::bundle::BundleInfo::components).
+    ///
+    /// See [`World::spawn_batch_with_bundle_id`] to spawn many entities at once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bundle_id` was not registered in this world, or if `values` does not yield
+    /// exactly one value per component of the bundle.
+    ///
+    /// # Safety
+    ///
+    /// Each value must point to a valid value of the component at the same position in the
+    /// bundle. The values are moved into the world.
+    pub unsafe fn spawn_with_bundle_id<'a>(
+        &mut self,
+        bundle_id: BundleId,
+        values: impl IntoIterator<Item = OwningPtr<'a>>,
+    ) -> Entity {
+        self.spawn_batch_with_bundle_id(bundle_id, std::iter::once(values))[0]
+    }
+
+    /// Spawns a batch of entities with the components of a registered bundle. Each item of
+    /// `batch` yields the values of one entity, in the order of [`BundleInfo::components`](crate::bundle::BundleInfo::components).
+    /// Returns the spawned [`Entity`] ids in the same order as `batch`.
+    ///
+    /// The values are written straight into the archetype of the bundle, which is only looked up
+    /// once, and storage for the whole batch is reserved up front. An empty `batch` does not
+    /// create the archetype.
+    ///
+    /// The values of each entity are collected before it is spawned, so if `batch` panics the
+    /// entities spawned so far stay in the world and their components are dropped as usual.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bundle_id` was not registered in this world, or if an item of `batch` does not
+    /// yield exactly one value per component of the bundle. The values of that entity are leaked.
+    ///
+    /// # Safety
+    ///
+    /// Each value must point to a valid value of the component at the same position in the
+    /// bundle. The values are moved into the world.
+    pub unsafe fn spawn_batch_with_bundle_id<'a, I>(
+        &mut self,
+        bundle_id: BundleId,
+        batch: I,
+    ) -> Vec<Entity>
+    where
+        I: IntoIterator,
+        I::Item: IntoIterator<Item = OwningPtr<'a>>,
+    {
+        struct DynamicSpawnBundle<'a, I: Iterator<Item = (StorageType, OwningPtr<'a>)>> {
+            components: I,
+        }
+
+        impl<'a, I: Iterator<Item = (StorageType, OwningPtr<'a>)>> DynamicBundle
+            for DynamicSpawnBundle<'a, I>
+        {
+            fn get_components(self, func: &mut impl FnMut(StorageType, OwningPtr<'_>)) {
+                self.components.for_each(|(t, ptr)| func(t, ptr));
+            }
+        }
+
+        let Some(bundle_info) = self.bundles.get(bundle_id) else {
+            panic!("Bundle {bundle_id:?} does not exist in this world");
+        };
+        let storage_types: Vec<_> = bundle_info
+            .components()
+            .iter()
+            // SAFETY: the components of a registered bundle exist in this world
+            .map(|&id| unsafe { self.components.get_info_unchecked(id) }.storage_type())
+            .collect();
+
+        let mut batch = batch.into_iter().peekable();
+        if batch.peek().is_none() {
+            return Vec::new();
+        }
+
+        // Ensure all entity allocations are accounted for so `self.entities` can realloc if
+        // necessary
+        self.flush();
+
+        let change_tick = self.change_tick();
+
+        let (lower, upper) = batch.size_hint();
+        let length = upper.unwrap_or(lower);
+
+        // SAFETY: the bundle was checked to exist above
+        let bundle_info = unsafe { self.bundles.get(bundle_id).debug_checked_unwrap() };
+        self.entities.reserve(length as u32);
+        let mut spawner = bundle_info.get_bundle_spawner(
+            &mut self.entities,
+            &mut self.archetypes,
+            &self.components,
+            &mut self.storages,
+            change_tick,
+        );
+        spawner.reserve_storage(length);
+
+        let mut entities = Vec::with_capacity(length);
+        let mut values = Vec::with_capacity(storage_types.len());
+        for item in batch {
+            values.extend(item);
+            assert_eq!(
+                values.len(),
+                storage_types.len(),
+                "Each spawned entity needs exactly one value per component of the bundle."
+            );
+            let bundle = DynamicSpawnBundle {
+                components: storage_types.iter().cloned().zip(values.drain(..)),
+            };
+            let entity = spawner.entities.alloc();
+            // SAFETY:
+            // - `entity` was just allocated
+            // - the caller ensures the values match the components of the bundle, in the same order
+            spawner.spawn_non_existent(entity, bundle);
+            entities.push(entity);
+        }
+
+        // SAFETY: the bundle was checked to exist above
+        let component_ids =
+            unsafe { self.bundles.get(bundle_id).debug_checked_unwrap() }.components();
+        if self.has_component_hooks(component_ids.iter().copied()) {
+            let component_ids = component_ids.to_vec();
+            let mut queue = CommandQueue::default();
+            for &entity in &entities {
+                for kind in [ComponentHookKind::OnAdd, ComponentHookKind::OnInsert] {
+                    self.trigger_component_hooks(
+                        kind,
+                        entity,
+                        component_ids.iter().copied(),
+                        &mut queue,
+                    );
+                }
+            }
+            queue.apply(self);
+        }
+        entities
+    }
+
+    /// Spawns a batch of entities with the same dynamic components, given by `component_ids`.
+    /// Each item of `batch` yields the values of one entity, in the same order as `component_ids`.
+    /// Returns the spawned [`Entity`] ids in the same order as `batch`.
+    ///
+    /// This is more efficient than calling [`World::spawn_empty`] and
+    /// [`EntityWorldMut::insert_by_ids`] for each entity, as the archetype is only looked up once
+    /// and storage for the whole batch is reserved up front. An empty `batch` does not create
+    /// the archetype. It is the same as calling [`World::register_dynamic_bundle`] and
+    /// [`World::spawn_batch_with_bundle_id`], after validating `component_ids`.
+    ///
+    /// The values of each entity are collected before it is spawned, so if `batch` panics the
+    /// entities spawned so far stay in the world and their components are dropped as usual.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error without spawning anything if `component_ids` contains an id which does
+    /// not exist in this world, or contains the same id more than once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an item of `batch` does not yield exactly one value per component id. The values
+    /// of that entity are leaked.
+    ///
+    /// # Safety
+    ///
+    /// Each value must point to a valid value of the component at the same position in
+    /// `component_ids`. The values are moved into the world.
+    pub unsafe fn spawn_batch_by_ids<'a, I>(
+        &mut self,
+        component_ids: &[ComponentId],
+        batch: I,
+    ) -> Result<Vec<Entity>, SpawnBatchByIdsError>
+    where
+        I: IntoIterator,
+        I::Item: IntoIterator<Item = OwningPtr<'a>>,
+    {
+        for (i, &id) in component_ids.iter().enumerate() {
+            if self.components.get_info(id).is_none() {
+                return Err(SpawnBatchByIdsError::UnknownComponent(id));
+            }
+            if component_ids[..i].contains(&id) {
+                return Err(SpawnBatchByIdsError::DuplicateComponent(id));
+            }
+        }
+
+        let bundle_id = self.register_dynamic_bundle(component_ids);
+        Ok(self.spawn_batch_with_bundle_id(bundle_id, batch))
+    }
+
     /// Retrieves a reference to the given `entity`'s [`Component`] of the given type.
     /// Returns `None` if the `entity` does not have a [`Component`] of the given type.
     /// ```
@@ -1043,6 +1259,49 @@ impl World {
             .map(|e| e.into())
     }
 
+    /// Initializes a new resource from a [`ComponentDescriptor`] and returns the [`ComponentId`]
+    /// created for it.
+    ///
+    /// This is the resource equivalent of [`World::init_component_with_descriptor`], for resources
+    /// which are only known at runtime. Its value can be inserted with
+    /// [`World::insert_resource_by_id`] and accessed with [`World::get_resource_by_id`] and
+    /// [`World::get_resource_mut_by_id`]. The storage type of the descriptor is ignored.
+    pub fn init_resource_with_descriptor(
+        &mut self,
+        descriptor: ComponentDescriptor,
+    ) -> ComponentId {
+        let component_id = self.components.init_resource_with_descriptor(descriptor);
+        self.initialize_resource_internal(component_id);
+        component_id
+    }
+
+    /// Registers a new event type from a [`ComponentDescriptor`] and returns the [`ComponentId`]
+    /// created for it, inserting the [`DynamicEvents`] resource the events are stored in if needed.
+    ///
+    /// This is the equivalent of [`Events`](crate::event::Events) for event types which are only
+    /// known at runtime. A descriptor for a reflected type can be built with
+    /// `ReflectComponentDescriptor`. The storage type
+    /// of the descriptor is ignored, and a distinct event type is created on each call.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the descriptor is not `Send + Sync`.
+    pub fn init_dynamic_event(&mut self, descriptor: ComponentDescriptor) -> ComponentId {
+        let component_id = self.components.init_resource_with_descriptor(descriptor);
+        let info = self.components.get_info(component_id).unwrap();
+        assert!(
+            info.is_send_and_sync(),
+            "Dynamic event type {} must be Send + Sync",
+            info.name()
+        );
+        let (name, layout, drop) = (info.name().to_string(), info.layout(), info.drop());
+        let mut events = self.get_resource_or_insert_with(DynamicEvents::default);
+        // SAFETY: the descriptor ensures `drop` can be called on values of `layout`, and the
+        // event type was checked to be `Send + Sync`
+        unsafe { events.register(component_id, name, layout, drop) };
+        component_id
+    }
+
     /// Initializes a new resource and returns the [`ComponentId`] created for it.
     ///
     /// If the resource already exists, nothing happens.
@@ -1720,7 +1979,7 @@ impl World {
     /// # Panics
     /// Panics if `component_id` is not registered as a `Send` component type in this `World`
     #[inline]
-    fn initialize_resource_internal(
+    pub(crate) fn initialize_resource_internal(
         &mut self,
         component_id: ComponentId,
     ) -> &mut ResourceData<true> {
@@ -1818,6 +2077,51 @@ impl World {
         self.last_change_tick
     }
 
+    /// Runs `f` with `last_change_tick` as the world's [last change tick](Self::last_change_tick),
+    /// restoring the previous one afterwards, even if `f` panics.
+    ///
+    /// Change detection, such as the [`Changed`](crate::query::Changed) filters of queries run on
+    /// the world, then reports the changes made since `last_change_tick`.
+    ///
+    /// ```
+    /// # use bevy_ecs::prelude::*;
+    /// #[derive(Component)]
+    /// struct A;
+    ///
+    /// let mut world = World::new();
+    /// let before = world.last_change_tick();
+    /// world.spawn(A);
+    /// world.clear_trackers();
+    ///
+    /// let mut query = world.query_filtered::<(), Added<A>>();
+    /// assert_eq!(query.iter(&world).count(), 0);
+    /// let added = world.last_change_tick_scope(before, |world| query.iter(world).count());
+    /// assert_eq!(added, 1);
+    /// ```
+    pub fn last_change_tick_scope<T>(
+        &mut self,
+        last_change_tick: Tick,
+        f: impl FnOnce(&mut World) -> T,
+    ) -> T {
+        struct LastTickGuard<'a> {
+            world: &'a mut World,
+            last_tick: Tick,
+        }
+
+        impl Drop for LastTickGuard<'_> {
+            fn drop(&mut self) {
+                self.world.last_change_tick = self.last_tick;
+            }
+        }
+
+        let last_tick = std::mem::replace(&mut self.last_change_tick, last_change_tick);
+        let guard = LastTickGuard {
+            world: self,
+            last_tick,
+        };
+        f(guard.world)
+    }
+
     /// Iterates all component change ticks and clamps any older than [`MAX_CHANGE_AGE`](crate::change_detection::MAX_CHANGE_AGE).
     /// This prevents overflow and thus prevents false positives.
     ///
@@ -2197,14 +2501,17 @@ mod tests {
     use super::{FromWorld, World};
     use crate::{
         change_detection::DetectChangesMut,
-        component::{ComponentDescriptor, ComponentInfo, StorageType},
-        ptr::OwningPtr,
+        component::{ComponentDescriptor, ComponentId, ComponentInfo, StorageType},
+        ptr::{OwningPtr, PtrMut},
         system::Resource,
+        world::error::SpawnBatchByIdsError,
     };
     use bevy_ecs_macros::Component;
     use bevy_utils::{HashMap, HashSet};
     use std::{
+        alloc::Layout,
         any::TypeId,
+        mem::ManuallyDrop,
         panic,
         sync::{
             atomic::{AtomicBool, AtomicU32, Ordering},
@@ -2416,6 +2723,52 @@ mod tests {
         assert_eq!(DROP_COUNT.load(Ordering::SeqCst), 1);
     }
 
+    #[test]
+    fn init_resource_with_descriptor() {
+        static DROP_COUNT: AtomicU32 = AtomicU32::new(0);
+
+        let mut world = World::new();
+        // SAFETY: the drop function is valid for the layout and the data is safe to access from any thread
+        let descriptor = unsafe {
+            ComponentDescriptor::new_with_layout(
+                "Score",
+                StorageType::Table,
+                std::alloc::Layout::new::<u64>(),
+                Some(|ptr| {
+                    ptr.drop_as::<u64>();
+                    DROP_COUNT.fetch_add(1, Ordering::SeqCst);
+                }),
+            )
+        };
+        let component_id = world.init_resource_with_descriptor(descriptor);
+        assert_eq!(world.components().get_id_by_name("Score"), None);
+        assert!(world.get_resource_by_id(component_id).is_none());
+
+        OwningPtr::make(1u64, |ptr| {
+            // SAFETY: value is valid for the resource layout
+            unsafe { world.insert_resource_by_id(component_id, ptr) };
+        });
+        assert!(world.is_resource_added_by_id(component_id));
+        world.clear_trackers();
+
+        let mut score = world.get_resource_mut_by_id(component_id).unwrap();
+        // SAFETY: u64 is the correct type for the resource
+        unsafe { *score.as_mut().deref_mut::<u64>() += 1 };
+        assert!(world.is_resource_changed_by_id(component_id));
+
+        // SAFETY: u64 is the correct type for the resource
+        let score = unsafe {
+            world
+                .get_resource_by_id(component_id)
+                .unwrap()
+                .deref::<u64>()
+        };
+        assert_eq!(*score, 2);
+
+        assert!(world.remove_resource_by_id(component_id).is_some());
+        assert_eq!(DROP_COUNT.load(Ordering::SeqCst), 1);
+    }
+
     #[derive(Resource)]
     struct TestFromWorld(u32);
     impl FromWorld for TestFromWorld {
@@ -2633,4 +2986,169 @@ mod tests {
         let mut world = World::new();
         world.spawn(());
     }
+
+    fn init_u64_component(world: &mut World, name: &str, storage_type: StorageType) -> ComponentId {
+        // SAFETY: `u64` is `Send + Sync` and has no drop function
+        let descriptor = unsafe {
+            ComponentDescriptor::new_with_layout(
+                name.to_string(),
+                storage_type,
+                Layout::new::<u64>(),
+                None,
+            )
+        };
+        world.init_component_with_descriptor(descriptor)
+    }
+
+    #[test]
+    fn spawn_batch_by_ids() {
+        let mut world = World::new();
+        let a = init_u64_component(&mut world, "A", StorageType::Table);
+        let b = init_u64_component(&mut world, "B", StorageType::SparseSet);
+        let archetype_count = world.archetypes().len();
+
+        // SAFETY: the batch is empty
+        let entities = unsafe { world.spawn_batch_by_ids(&[a, b], Vec::<[OwningPtr; 2]>::new()) };
+        assert_eq!(entities, Ok(Vec::new()));
+        assert_eq!(world.archetypes().len(), archetype_count);
+
+        let mut values: Vec<[u64; 2]> = (0..100).map(|i| [i, i * 2]).collect();
+        let batch = values.iter_mut().map(|[x, y]| {
+            // SAFETY: the values are `u64`s which are not used again
+            unsafe { [PtrMut::from(x).promote(), PtrMut::from(y).promote()] }
+        });
+        // SAFETY: each entity yields a `u64` for `a` and `b`
+        let entities = unsafe { world.spawn_batch_by_ids(&[a, b], batch) }.unwrap();
+        assert_eq!(entities.len(), 100);
+        assert_eq!(world.archetypes().len(), archetype_count + 1);
+
+        for (i, &entity) in entities.iter().enumerate() {
+            let entity = world.entity(entity);
+            // SAFETY: both components are `u64`s
+            unsafe {
+                assert_eq!(*entity.get_by_id(a).unwrap().deref::<u64>(), i as u64);
+                assert_eq!(*entity.get_by_id(b).unwrap().deref::<u64>(), i as u64 * 2);
+            }
+        }
+    }
+
+    #[test]
+    fn spawn_with_bundle_id() {
+        let mut world = World::new();
+        let a = init_u64_component(&mut world, "A", StorageType::Table);
+        let b = init_u64_component(&mut world, "B", StorageType::SparseSet);
+        let bundle_id = world.register_dynamic_bundle(&[a, b]);
+        assert_eq!(world.register_dynamic_bundle(&[a, b]), bundle_id);
+        assert_ne!(world.register_dynamic_bundle(&[b, a]), bundle_id);
+        assert_eq!(
+            world.bundles().get(bundle_id).unwrap().components(),
+            &[a, b]
+        );
+
+        let mut values = [1u64, 2];
+        let [x, y] = &mut values;
+        // SAFETY: the values are `u64`s which are not used again
+        let first = unsafe {
+            world.spawn_with_bundle_id(
+                bundle_id,
+                [PtrMut::from(x).promote(), PtrMut::from(y).promote()],
+            )
+        };
+
+        let mut values: Vec<[u64; 2]> = (0..10).map(|i| [i, i * 2]).collect();
+        let batch = values.iter_mut().map(|[x, y]| {
+            // SAFETY: the values are `u64`s which are not used again
+            unsafe { [PtrMut::from(x).promote(), PtrMut::from(y).promote()] }
+        });
+        // SAFETY: each entity yields a `u64` for `a` and `b`
+        let entities = unsafe { world.spawn_batch_with_bundle_id(bundle_id, batch) };
+        assert_eq!(entities.len(), 10);
+
+        let archetype = world.entity(first).archetype().id();
+        for (i, &entity) in entities.iter().enumerate() {
+            let entity = world.entity(entity);
+            assert_eq!(entity.archetype().id(), archetype);
+            // SAFETY: both components are `u64`s
+            unsafe {
+                assert_eq!(*entity.get_by_id(a).unwrap().deref::<u64>(), i as u64);
+                assert_eq!(*entity.get_by_id(b).unwrap().deref::<u64>(), i as u64 * 2);
+            }
+        }
+        // SAFETY: both components are `u64`s
+        unsafe {
+            let first = world.entity(first);
+            assert_eq!(*first.get_by_id(a).unwrap().deref::<u64>(), 1);
+            assert_eq!(*first.get_by_id(b).unwrap().deref::<u64>(), 2);
+        }
+    }
+
+    #[test]
+    fn spawn_batch_by_ids_invalid_ids() {
+        let mut world = World::new();
+        let a = init_u64_component(&mut world, "A", StorageType::Table);
+        let unknown = ComponentId::new(world.components().len());
+
+        let mut values = [0u64; 2];
+        let [x, y] = &mut values;
+        // SAFETY: nothing is spawned
+        let result = unsafe {
+            world.spawn_batch_by_ids(
+                &[a, a],
+                [[PtrMut::from(x).promote(), PtrMut::from(y).promote()]],
+            )
+        };
+        assert_eq!(result, Err(SpawnBatchByIdsError::DuplicateComponent(a)));
+
+        let [x, y] = &mut values;
+        // SAFETY: nothing is spawned
+        let result = unsafe {
+            world.spawn_batch_by_ids(
+                &[a, unknown],
+                [[PtrMut::from(x).promote(), PtrMut::from(y).promote()]],
+            )
+        };
+        assert_eq!(result, Err(SpawnBatchByIdsError::UnknownComponent(unknown)));
+        assert_eq!(world.entities().len(), 0);
+    }
+
+    #[test]
+    fn spawn_batch_by_ids_panic() {
+        static DROP_COUNT: AtomicU32 = AtomicU32::new(0);
+
+        let mut world = World::new();
+        // SAFETY: `Box<u64>` is `Send + Sync` and the drop function drops a `Box<u64>`
+        let descriptor = unsafe {
+            ComponentDescriptor::new_with_layout(
+                "Boxed",
+                StorageType::Table,
+                Layout::new::<Box<u64>>(),
+                Some(|ptr| {
+                    ptr.drop_as::<Box<u64>>();
+                    DROP_COUNT.fetch_add(1, Ordering::SeqCst);
+                }),
+            )
+        };
+        let id = world.init_component_with_descriptor(descriptor);
+
+        let mut values: Vec<_> = (0..10).map(|i| ManuallyDrop::new(Box::new(i))).collect();
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            let batch = values.iter_mut().enumerate().map(|(i, value)| {
+                assert!(i < 5, "ran out of values");
+                // SAFETY: the value is moved into the world and not dropped by the test
+                [unsafe { PtrMut::from(&mut **value).promote() }]
+            });
+            // SAFETY: each entity yields a `Box<u64>`
+            unsafe { world.spawn_batch_by_ids(&[id], batch) }
+        }));
+        assert!(result.is_err());
+        assert_eq!(world.entities().len(), 5);
+        assert_eq!(DROP_COUNT.load(Ordering::SeqCst), 0);
+
+        drop(world);
+        assert_eq!(DROP_COUNT.load(Ordering::SeqCst), 5);
+        values[5..]
+            .iter_mut()
+            // SAFETY: these values were never moved into the world
+            .for_each(|value| unsafe { ManuallyDrop::drop(value) });
+    }
 }
@@ -193,3 +193,9 @@ pub mod dynamic_plugin {
     //! Dynamic linking of plugins
     pub use bevy_dynamic_plugin::*;
 }
+
+#[cfg(feature = "bevy_remote")]
+pub mod remote {
+    //! Inspecting and mutating the world from other processes
+    pub use bevy_remote::*;
+}
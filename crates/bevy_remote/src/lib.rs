@@ -0,0 +1,151 @@
+//! Provides [`RemotePlugin`], which lets other processes, such as editors and debuggers, inspect
+//! and mutate the world of an app without being compiled against its component types.
+//!
+//! Clients connect over TCP and send [`RemoteRequest`]s, one JSON object per line. Each request
+//! is answered with a [`RemoteResponse`] on its own line, in order:
+//!
+//! ```text
+//! > {"id": 1, "method": "query", "params": {"query": "&\"game::Health\", !\"game::Dead\""}}
+//! < {"id": 1, "result": [{"entity": 4294967296, "components": {"game::Health": [10]}}]}
+//! > {"id": 2, "method": "insert", "params": {"entity": 4294967296, "components": {"game::Health": [20]}}}
+//! < {"id": 2, "result": null}
+//! ```
+//!
+//! See [`RemoteMethod`] for the supported methods.
+
+#![warn(missing_docs)]
+
+mod protocol;
+
+pub use protocol::*;
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream},
+};
+
+use bevy_app::prelude::*;
+use bevy_ecs::{system::Resource, world::World};
+use bevy_log::{error, warn};
+use crossbeam_channel::{Receiver, Sender};
+
+/// The port [`RemotePlugin`] listens on by default.
+pub const DEFAULT_PORT: u16 = 15702;
+
+/// Plugin that listens for [`RemoteRequest`]s and runs them on the world of the [`App`].
+///
+/// Requests are run in [`PreUpdate`], so changes made by clients are seen by the systems of the
+/// same frame.
+///
+/// Anyone who can connect to the address can read and change the whole world, so it listens on
+/// the loopback address by default.
+pub struct RemotePlugin {
+    /// The address to listen on.
+    pub address: SocketAddr,
+}
+
+impl Default for RemotePlugin {
+    fn default() -> Self {
+        Self {
+            address: (Ipv4Addr::LOCALHOST, DEFAULT_PORT).into(),
+        }
+    }
+}
+
+impl Plugin for RemotePlugin {
+    fn build(&self, app: &mut App) {
+        let listener = match TcpListener::bind(self.address) {
+            Ok(listener) => listener,
+            Err(err) => {
+                error!(
+                    "Failed to listen for remote requests on {}: {err}",
+                    self.address
+                );
+                return;
+            }
+        };
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        std::thread::Builder::new()
+            .name("remote listener".to_string())
+            .spawn(move || listen(listener, sender))
+            .expect("Failed to spawn the remote listener thread");
+
+        app.insert_resource(RemoteRequests(receiver))
+            .add_systems(PreUpdate, process_remote_requests);
+    }
+}
+
+/// A [`RemoteRequest`] waiting to be run, with the channel its response is sent back on.
+struct PendingRequest {
+    request: RemoteRequest,
+    response: Sender<RemoteResponse>,
+}
+
+/// The requests received by [`RemotePlugin`] since the last time they were processed.
+#[derive(Resource)]
+struct RemoteRequests(Receiver<PendingRequest>);
+
+/// Runs the pending [`RemoteRequest`]s on the world, in the order they were received.
+fn process_remote_requests(world: &mut World) {
+    let requests: Vec<_> = world.resource::<RemoteRequests>().0.try_iter().collect();
+    for PendingRequest { request, response } in requests {
+        let result = request.method.run(world);
+        // The client may have disconnected since sending the request
+        let _ = response.send(RemoteResponse::new(request.id, result));
+    }
+}
+
+/// Accepts connections, and serves each of them on its own thread.
+fn listen(listener: TcpListener, requests: Sender<PendingRequest>) {
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                warn!("Failed to accept a remote connection: {err}");
+                continue;
+            }
+        };
+        let requests = requests.clone();
+        let spawned = std::thread::Builder::new()
+            .name("remote connection".to_string())
+            .spawn(move || {
+                if let Err(err) = serve(stream, &requests) {
+                    warn!("Remote connection closed: {err}");
+                }
+            });
+        if let Err(err) = spawned {
+            warn!("Failed to spawn a thread for a remote connection: {err}");
+        }
+    }
+}
+
+/// Answers the requests sent on `stream` until it is closed, or the app exits.
+fn serve(stream: TcpStream, requests: &Sender<PendingRequest>) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match RemoteRequest::parse(&line) {
+            Ok(request) => {
+                let (sender, receiver) = crossbeam_channel::bounded(1);
+                let pending = PendingRequest {
+                    request,
+                    response: sender,
+                };
+                if requests.send(pending).is_err() {
+                    return Ok(());
+                }
+                match receiver.recv() {
+                    Ok(response) => response,
+                    Err(_) => return Ok(()),
+                }
+            }
+            Err(response) => response,
+        };
+        serde_json::to_writer(&mut writer, &response)?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
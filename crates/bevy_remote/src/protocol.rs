@@ -0,0 +1,650 @@
+use std::{alloc::Layout, ptr::NonNull};
+
+use bevy_ecs::{
+    component::{ComponentId, ComponentInfo},
+    entity::Entity,
+    ptr::{OwningPtr, Ptr},
+    query::{QueryBuilder, QueryParseError},
+    reflect::{AppTypeRegistry, ReflectComponent},
+    world::{EntityWorldMut, FilteredEntityRef, World},
+};
+use bevy_reflect::{
+    serde::{TypedReflectDeserializer, TypedReflectSerializer},
+    Reflect, ReflectFromPtr, TypeRegistration, TypeRegistry,
+};
+use bevy_scene::{DynamicComponentSerializer, DynamicComponentSerializers};
+use serde::{de::DeserializeSeed, Deserialize, Serialize};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+/// A request sent to the [`RemotePlugin`](crate::RemotePlugin), as a single line of JSON.
+///
+/// ```json
+/// {"id": 1, "method": "query", "params": {"query": "&\"game::Health\", !\"game::Dead\""}}
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemoteRequest {
+    /// Any value chosen by the client, which is sent back in the [`RemoteResponse`].
+    #[serde(default)]
+    pub id: Value,
+    /// What the request does.
+    #[serde(flatten)]
+    pub method: RemoteMethod,
+}
+
+impl RemoteRequest {
+    /// Parses a request from a line of JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns the response to send back if the line is not a valid request, with the `id` of
+    /// the request if it could be read.
+    pub fn parse(json: &str) -> Result<Self, RemoteResponse> {
+        let value: Value = serde_json::from_str(json)
+            .map_err(|error| RemoteResponse::new(Value::Null, Err(error.into())))?;
+        let id = value.get("id").cloned().unwrap_or_default();
+        match serde_json::from_value(value) {
+            Ok(method) => Ok(Self { id, method }),
+            Err(error) => Err(RemoteResponse::new(id, Err(error.into()))),
+        }
+    }
+}
+
+/// The methods of a [`RemoteRequest`], with their `params`.
+///
+/// Components are named by the name they were registered with, see
+/// [`Components::get_id_by_name`](bevy_ecs::component::Components::get_id_by_name), which is the
+/// full path of the type for components defined in Rust. Their values are converted to and from
+/// JSON by reflection, so their types must be registered in the [`AppTypeRegistry`] with
+/// [`ReflectComponent`].
+///
+/// Runtime components, which have no type, are converted as a list of bytes by the
+/// [`DynamicComponentSerializer`] registered for their name in the
+/// [`DynamicComponentSerializers`] resource. Without one, a runtime component with no drop
+/// function and the layout of a `[u64]` array is assumed to be such an array, and converted as a
+/// list of its elements, read as little-endian words.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum RemoteMethod {
+    /// Returns the entities matching a query string, with the values of the components the query
+    /// reads or writes, as a list of [`RemoteEntity`].
+    ///
+    /// See [`QueryBuilder::parse`] for the syntax of query strings.
+    Query {
+        /// The query string.
+        query: String,
+    },
+    /// Spawns an entity with the given components, and returns it.
+    Spawn {
+        /// The values of the components, by name.
+        components: Map<String, Value>,
+    },
+    /// Inserts the given components into an entity, replacing the values it already has.
+    Insert {
+        /// The entity to insert the components into.
+        entity: Entity,
+        /// The values of the components, by name.
+        components: Map<String, Value>,
+    },
+    /// Removes the given components from an entity, if it has them.
+    Remove {
+        /// The entity to remove the components from.
+        entity: Entity,
+        /// The names of the components.
+        components: Vec<String>,
+    },
+    /// Despawns an entity.
+    Despawn {
+        /// The entity to despawn.
+        entity: Entity,
+    },
+}
+
+/// An entity returned by [`RemoteMethod::Query`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemoteEntity {
+    /// The entity.
+    pub entity: Entity,
+    /// The values of its components, by name.
+    pub components: Map<String, Value>,
+}
+
+/// The response to a [`RemoteRequest`], sent back as a single line of JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemoteResponse {
+    /// The `id` of the request.
+    pub id: Value,
+    /// The result of the request, if it succeeded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    /// A description of the error, if the request failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl RemoteResponse {
+    /// Creates the response to the request with the given `id`.
+    pub fn new(id: Value, result: Result<Value, RemoteError>) -> Self {
+        match result {
+            Ok(result) => Self {
+                id,
+                result: Some(result),
+                error: None,
+            },
+            Err(error) => Self {
+                id,
+                result: None,
+                error: Some(error.to_string()),
+            },
+        }
+    }
+}
+
+/// An error that occurs when running a [`RemoteRequest`].
+#[derive(Debug, Error)]
+pub enum RemoteError {
+    /// The request is not valid JSON, or does not match any [`RemoteMethod`].
+    #[error("Invalid request: {0}")]
+    InvalidRequest(#[from] serde_json::Error),
+    /// The query string of [`RemoteMethod::Query`] is not valid.
+    #[error("Invalid query: {0}")]
+    InvalidQuery(#[from] QueryParseError),
+    /// No component is registered with the name.
+    #[error("Unknown component `{0}`")]
+    UnknownComponent(String),
+    /// The component cannot be converted to and from JSON, as its type is not registered with
+    /// [`ReflectComponent`], or it is a runtime component which has no serializer and is not an
+    /// array of words.
+    #[error("The component `{0}` is not reflected")]
+    NotReflected(String),
+    /// The value of the component could not be converted to or from JSON.
+    #[error("Invalid value for component `{name}`: {error}")]
+    InvalidValue {
+        /// The name of the component.
+        name: String,
+        /// The error returned by the conversion.
+        error: serde_json::Error,
+    },
+    /// The [`DynamicComponentSerializer`] of a runtime component rejected the bytes of its value.
+    #[error("The serializer of component `{0}` rejected its value")]
+    RejectedValue(String),
+    /// The entity does not exist.
+    #[error("The entity {0:?} does not exist")]
+    NoSuchEntity(Entity),
+}
+
+impl RemoteMethod {
+    /// Runs the method on the world, returning its result.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RemoteError`] without changing the world if the request is invalid. The values
+    /// of runtime components with a serializer are only checked by inserting them, so if one is
+    /// rejected the components inserted before it stay on the entity, unless it was just spawned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the world does not have an [`AppTypeRegistry`].
+    pub fn run(&self, world: &mut World) -> Result<Value, RemoteError> {
+        match self {
+            RemoteMethod::Query { query } => {
+                let entities = run_query(world, query)?;
+                Ok(serde_json::to_value(entities)?)
+            }
+            RemoteMethod::Spawn { components } => {
+                let components = deserialize_components(world, components)?;
+                let mut entity = world.spawn_empty();
+                for value in &components {
+                    if let Err(error) = value.insert(&mut entity) {
+                        entity.despawn();
+                        return Err(error);
+                    }
+                }
+                Ok(serde_json::to_value(entity.id())?)
+            }
+            RemoteMethod::Insert { entity, components } => {
+                let components = deserialize_components(world, components)?;
+                let mut entity = world
+                    .get_entity_mut(*entity)
+                    .ok_or(RemoteError::NoSuchEntity(*entity))?;
+                for value in &components {
+                    value.insert(&mut entity)?;
+                }
+                Ok(Value::Null)
+            }
+            RemoteMethod::Remove { entity, components } => {
+                let ids = components
+                    .iter()
+                    .map(|name| {
+                        world
+                            .components()
+                            .get_id_by_name(name)
+                            .ok_or_else(|| RemoteError::UnknownComponent(name.clone()))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let mut entity = world
+                    .get_entity_mut(*entity)
+                    .ok_or(RemoteError::NoSuchEntity(*entity))?;
+                for id in ids {
+                    entity.remove_by_id(id);
+                }
+                Ok(Value::Null)
+            }
+            RemoteMethod::Despawn { entity } => {
+                world
+                    .get_entity_mut(*entity)
+                    .ok_or(RemoteError::NoSuchEntity(*entity))?
+                    .despawn();
+                Ok(Value::Null)
+            }
+        }
+    }
+}
+
+fn run_query(world: &mut World, query: &str) -> Result<Vec<RemoteEntity>, RemoteError> {
+    let mut builder = QueryBuilder::<FilteredEntityRef>::new(world);
+    builder.parse_registered(query)?;
+    let ids: Vec<_> = builder.access().access().reads_and_writes().collect();
+    let mut state = builder.build();
+
+    let registry = world.resource::<AppTypeRegistry>().read();
+    let serializers = world.get_resource::<DynamicComponentSerializers>();
+    state
+        .iter(world)
+        .map(|entity| {
+            let mut components = Map::new();
+            for &id in &ids {
+                let Some(ptr) = entity.get_by_id(id) else {
+                    continue;
+                };
+                let info = world.components().get_info(id).unwrap();
+                let format = ComponentFormat::new(info, &registry, serializers)?;
+                // SAFETY: `ptr` points to a value of the component described by `info`
+                let value = unsafe { serialize_component(ptr, info, format, &registry) }?;
+                components.insert(info.name().to_string(), value);
+            }
+            Ok(RemoteEntity {
+                entity: entity.id(),
+                components,
+            })
+        })
+        .collect()
+}
+
+/// How the values of a component are converted to and from JSON.
+enum ComponentFormat<'r> {
+    /// By reflection, with the registration of its type.
+    Reflected(&'r TypeRegistration),
+    /// As a list of bytes, by the serializer registered for its name.
+    Serialized(DynamicComponentSerializer),
+    /// As a list of little-endian words, for runtime components which are `[u64]` arrays.
+    Words(usize),
+}
+
+impl<'r> ComponentFormat<'r> {
+    fn new(
+        info: &ComponentInfo,
+        registry: &'r TypeRegistry,
+        serializers: Option<&DynamicComponentSerializers>,
+    ) -> Result<Self, RemoteError> {
+        let not_reflected = || RemoteError::NotReflected(info.name().to_string());
+        if let Some(type_id) = info.type_id() {
+            return registry
+                .get(type_id)
+                .map(ComponentFormat::Reflected)
+                .ok_or_else(not_reflected);
+        }
+        if let Some(serializer) = serializers.and_then(|serializers| serializers.get(info.name())) {
+            return Ok(ComponentFormat::Serialized(*serializer));
+        }
+        let len = info.layout().size() / std::mem::size_of::<u64>();
+        let words = Layout::array::<u64>(len).is_ok_and(|layout| layout == info.layout());
+        if words && info.drop().is_none() {
+            return Ok(ComponentFormat::Words(len));
+        }
+        Err(not_reflected())
+    }
+}
+
+/// # Safety
+/// `ptr` must point to a value of the component described by `info`, and `format` must have been
+/// created for it.
+unsafe fn serialize_component(
+    ptr: Ptr<'_>,
+    info: &ComponentInfo,
+    format: ComponentFormat<'_>,
+    registry: &TypeRegistry,
+) -> Result<Value, RemoteError> {
+    let invalid_value = |error| RemoteError::InvalidValue {
+        name: info.name().to_string(),
+        error,
+    };
+    match format {
+        ComponentFormat::Reflected(registration) => {
+            let from_ptr = registration
+                .data::<ReflectFromPtr>()
+                .ok_or_else(|| RemoteError::NotReflected(info.name().to_string()))?;
+            let value = from_ptr.as_reflect(ptr);
+            serde_json::to_value(TypedReflectSerializer::new(value, registry))
+                .map_err(invalid_value)
+        }
+        ComponentFormat::Serialized(serializer) => {
+            serde_json::to_value((serializer.serialize)(ptr, info)).map_err(invalid_value)
+        }
+        ComponentFormat::Words(len) => {
+            // SAFETY: the component is an array of `len` words
+            let words = std::slice::from_raw_parts(ptr.as_ptr().cast::<u64>(), len);
+            let words: Vec<_> = words.iter().map(|&word| u64::from_le(word)).collect();
+            serde_json::to_value(words).map_err(invalid_value)
+        }
+    }
+}
+
+/// The value of a component converted from JSON, ready to be inserted.
+enum ComponentValue {
+    Reflected(ReflectComponent, Box<dyn Reflect>),
+    Serialized {
+        id: ComponentId,
+        name: String,
+        serializer: DynamicComponentSerializer,
+        bytes: Vec<u8>,
+    },
+    Words(ComponentId, Vec<u64>),
+}
+
+impl ComponentValue {
+    fn insert(&self, entity: &mut EntityWorldMut) -> Result<(), RemoteError> {
+        match self {
+            ComponentValue::Reflected(reflect_component, value) => {
+                reflect_component.insert(entity, &**value);
+            }
+            ComponentValue::Serialized {
+                id,
+                name,
+                serializer,
+                bytes,
+            } => {
+                // SAFETY: the serializer was registered for components with this name
+                if !unsafe { (serializer.insert)(bytes, entity, *id) } {
+                    return Err(RemoteError::RejectedValue(name.clone()));
+                }
+            }
+            ComponentValue::Words(id, words) => {
+                let mut words: Vec<_> = words.iter().map(|word| word.to_le()).collect();
+                // SAFETY:
+                // - the component is an array of as many words, checked when converting them
+                // - it has no drop function, so the value can be copied out of the buffer
+                unsafe {
+                    let ptr = NonNull::from(words.as_mut_slice()).cast();
+                    entity.insert_by_id(*id, OwningPtr::new(ptr));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Converts the values of components from JSON, so they can be inserted once they are all valid.
+fn deserialize_components(
+    world: &World,
+    components: &Map<String, Value>,
+) -> Result<Vec<ComponentValue>, RemoteError> {
+    let registry = world.resource::<AppTypeRegistry>().read();
+    let serializers = world.get_resource::<DynamicComponentSerializers>();
+    components
+        .iter()
+        .map(|(name, value)| {
+            let info = world
+                .components()
+                .get_id_by_name(name)
+                .and_then(|id| world.components().get_info(id))
+                .ok_or_else(|| RemoteError::UnknownComponent(name.clone()))?;
+            let invalid_value = |error| RemoteError::InvalidValue {
+                name: name.clone(),
+                error,
+            };
+            match ComponentFormat::new(info, &registry, serializers)? {
+                ComponentFormat::Reflected(registration) => {
+                    let reflect_component = registration
+                        .data::<ReflectComponent>()
+                        .ok_or_else(|| RemoteError::NotReflected(name.clone()))?
+                        .clone();
+                    let value = TypedReflectDeserializer::new(registration, &registry)
+                        .deserialize(value.clone())
+                        .map_err(invalid_value)?;
+                    Ok(ComponentValue::Reflected(reflect_component, value))
+                }
+                ComponentFormat::Serialized(serializer) => Ok(ComponentValue::Serialized {
+                    id: info.id(),
+                    name: name.clone(),
+                    serializer,
+                    bytes: serde_json::from_value(value.clone()).map_err(invalid_value)?,
+                }),
+                ComponentFormat::Words(len) => {
+                    let words: Vec<u64> =
+                        serde_json::from_value(value.clone()).map_err(invalid_value)?;
+                    if words.len() != len {
+                        return Err(invalid_value(serde::de::Error::invalid_length(
+                            words.len(),
+                            &format!("{len} words").as_str(),
+                        )));
+                    }
+                    Ok(ComponentValue::Words(info.id(), words))
+                }
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::alloc::Layout;
+
+    use bevy_ecs::{
+        component::{ComponentDescriptor, StorageType},
+        prelude::*,
+        reflect::AppTypeRegistry,
+    };
+    use bevy_reflect::Reflect;
+    use bevy_scene::{DynamicComponentSerializer, DynamicComponentSerializers};
+    use serde_json::{json, Value};
+
+    use super::{RemoteEntity, RemoteRequest, RemoteResponse};
+
+    #[derive(Component, Reflect, Default, PartialEq, Debug)]
+    #[reflect(Component)]
+    struct Health(u32);
+
+    #[derive(Component, Reflect, Default, PartialEq, Debug)]
+    #[reflect(Component)]
+    struct Dead;
+
+    #[derive(Component)]
+    struct Hidden;
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        {
+            let mut registry = world.resource::<AppTypeRegistry>().write();
+            registry.register::<Health>();
+            registry.register::<Dead>();
+        }
+        world.init_component::<Health>();
+        world.init_component::<Dead>();
+        world.init_component::<Hidden>();
+        world
+    }
+
+    fn run(world: &mut World, request: Value) -> RemoteResponse {
+        let request = RemoteRequest::parse(&request.to_string()).unwrap();
+        let result = request.method.run(world);
+        RemoteResponse::new(request.id, result)
+    }
+
+    fn name<T>() -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    #[test]
+    fn remote_query() {
+        let mut world = world();
+        let alive = world.spawn(Health(10)).id();
+        world.spawn((Health(0), Dead));
+        world.spawn(Hidden);
+
+        let query = format!("&\"{}\", !\"{}\"", name::<Health>(), name::<Dead>());
+        let response = run(
+            &mut world,
+            json!({"id": 1, "method": "query", "params": {"query": query}}),
+        );
+        assert_eq!(response.id, json!(1));
+        assert_eq!(response.error, None);
+        let entities: Vec<RemoteEntity> = serde_json::from_value(response.result.unwrap()).unwrap();
+        assert_eq!(
+            entities,
+            vec![RemoteEntity {
+                entity: alive,
+                components: json!({ name::<Health>(): [10] })
+                    .as_object()
+                    .unwrap()
+                    .clone(),
+            }]
+        );
+
+        let query = format!("&\"{}\"", name::<Hidden>());
+        let response = run(
+            &mut world,
+            json!({"id": 2, "method": "query", "params": {"query": query}}),
+        );
+        assert!(response.error.unwrap().contains("is not reflected"));
+    }
+
+    #[test]
+    fn remote_mutations() {
+        let mut world = world();
+        let response = run(
+            &mut world,
+            json!({"method": "spawn", "params": {"components": {name::<Health>(): [5]}}}),
+        );
+        let entity: Entity = serde_json::from_value(response.result.unwrap()).unwrap();
+        assert_eq!(world.get::<Health>(entity), Some(&Health(5)));
+
+        let components = json!({name::<Health>(): [6], name::<Dead>(): {}});
+        let response = run(
+            &mut world,
+            json!({"method": "insert", "params": {"entity": entity, "components": components}}),
+        );
+        assert_eq!(response.result, Some(Value::Null));
+        assert_eq!(world.get::<Health>(entity), Some(&Health(6)));
+        assert!(world.entity(entity).contains::<Dead>());
+
+        let response = run(
+            &mut world,
+            json!({"method": "remove", "params": {"entity": entity, "components": [name::<Dead>()]}}),
+        );
+        assert_eq!(response.result, Some(Value::Null));
+        assert!(!world.entity(entity).contains::<Dead>());
+
+        run(
+            &mut world,
+            json!({"method": "despawn", "params": {"entity": entity}}),
+        );
+        assert!(world.get_entity(entity).is_none());
+        let response = run(
+            &mut world,
+            json!({"method": "despawn", "params": {"entity": entity}}),
+        );
+        assert!(response.error.unwrap().contains("does not exist"));
+    }
+
+    #[test]
+    fn remote_runtime_components() {
+        let mut world = world();
+        let runtime = |world: &mut World, name: &'static str, layout| {
+            // SAFETY: the component has no drop function, and is only used as plain data
+            world.init_component_with_descriptor(unsafe {
+                ComponentDescriptor::new_with_layout(name, StorageType::Table, layout, None)
+            })
+        };
+        let words = runtime(&mut world, "Words", Layout::array::<u64>(2).unwrap());
+        runtime(&mut world, "Bytes", Layout::array::<u8>(3).unwrap());
+        runtime(&mut world, "Opaque", Layout::new::<u32>());
+        let mut serializers = DynamicComponentSerializers::default();
+        // SAFETY: any 3 bytes are a valid value of `Bytes`
+        unsafe { serializers.insert("Bytes", DynamicComponentSerializer::RAW) };
+        world.insert_resource(serializers);
+
+        let components = json!({"Words": [1, 2], "Bytes": [3, 4, 5]});
+        let response = run(
+            &mut world,
+            json!({"method": "spawn", "params": {"components": components}}),
+        );
+        let entity: Entity = serde_json::from_value(response.result.unwrap()).unwrap();
+        let ptr = world.entity(entity).get_by_id(words).unwrap();
+        // SAFETY: `Words` is an array of 2 words
+        let value = unsafe { std::slice::from_raw_parts(ptr.as_ptr().cast::<u64>(), 2) };
+        assert_eq!(value, [1u64.to_le(), 2u64.to_le()]);
+
+        let response = run(
+            &mut world,
+            json!({"method": "insert", "params": {"entity": entity, "components": {"Words": [6, 7]}}}),
+        );
+        assert_eq!(response.result, Some(Value::Null));
+        let response = run(
+            &mut world,
+            json!({"method": "query", "params": {"query": "&Words, &Bytes"}}),
+        );
+        let entities: Vec<RemoteEntity> = serde_json::from_value(response.result.unwrap()).unwrap();
+        assert_eq!(
+            entities,
+            vec![RemoteEntity {
+                entity,
+                components: json!({"Words": [6, 7], "Bytes": [3, 4, 5]})
+                    .as_object()
+                    .unwrap()
+                    .clone(),
+            }]
+        );
+
+        let response = run(
+            &mut world,
+            json!({"method": "insert", "params": {"entity": entity, "components": {"Words": [8]}}}),
+        );
+        assert!(response.error.unwrap().starts_with("Invalid value"));
+        let response = run(
+            &mut world,
+            json!({"method": "spawn", "params": {"components": {"Opaque": 0}}}),
+        );
+        assert_eq!(
+            response.error.unwrap(),
+            "The component `Opaque` is not reflected"
+        );
+    }
+
+    #[test]
+    fn remote_invalid_values() {
+        let mut world = world();
+        let entity = world.spawn(Health(1)).id();
+
+        let components = json!({name::<Health>(): [2], "Unknown": 0});
+        let response = run(
+            &mut world,
+            json!({"method": "insert", "params": {"entity": entity, "components": components}}),
+        );
+        assert_eq!(response.error.unwrap(), "Unknown component `Unknown`");
+        assert_eq!(world.get::<Health>(entity), Some(&Health(1)));
+
+        let components = json!({name::<Health>(): ["two"]});
+        let response = run(
+            &mut world,
+            json!({"method": "insert", "params": {"entity": entity, "components": components}}),
+        );
+        assert!(response.error.unwrap().starts_with("Invalid value"));
+        assert_eq!(world.get::<Health>(entity), Some(&Health(1)));
+
+        let response = RemoteRequest::parse(r#"{"id": 3, "method": "explode"}"#).unwrap_err();
+        assert_eq!(response.id, json!(3));
+        assert!(response.error.unwrap().starts_with("Invalid request"));
+    }
+}
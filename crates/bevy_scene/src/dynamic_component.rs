@@ -0,0 +1,146 @@
+use std::{
+    alloc::{alloc, dealloc, handle_alloc_error},
+    num::NonZeroUsize,
+    ptr::NonNull,
+};
+
+use bevy_ecs::{
+    component::{ComponentId, ComponentInfo},
+    ptr::{dangling_with_align, OwningPtr, Ptr},
+    system::Resource,
+    world::EntityWorldMut,
+};
+use bevy_reflect::{Reflect, TypeRegistry};
+use bevy_utils::HashMap;
+
+/// The values of the runtime components of an entity in a [`DynamicScene`](crate::DynamicScene).
+///
+/// Components without a [`TypeId`](std::any::TypeId), such as those created with
+/// [`ComponentDescriptor::new_with_layout`](bevy_ecs::component::ComponentDescriptor::new_with_layout),
+/// cannot be reflected. Instead, [`DynamicSceneBuilder`](crate::DynamicSceneBuilder) converts them to
+/// bytes with the [`DynamicComponentSerializers`] of the world, and stores them together in this
+/// value. Writing the scene to a world turns them back into components, matched by name.
+///
+/// It is only used in scenes, and is never inserted into a world. Its types must be registered,
+/// which [`ScenePlugin`](crate::ScenePlugin) does, or with [`DynamicComponents::register_types`].
+#[derive(Reflect, Default, Clone, Debug, PartialEq, Eq)]
+pub struct DynamicComponents(pub Vec<DynamicComponentValue>);
+
+/// The value of a runtime component, see [`DynamicComponents`].
+#[derive(Reflect, Default, Clone, Debug, PartialEq, Eq)]
+pub struct DynamicComponentValue {
+    /// The name of the component.
+    pub name: String,
+    /// The value of the component, as written by its [`DynamicComponentSerializer`].
+    pub bytes: Vec<u8>,
+}
+
+impl DynamicComponents {
+    /// Registers [`DynamicComponents`] and the types it contains.
+    pub fn register_types(registry: &mut TypeRegistry) {
+        registry.register::<DynamicComponents>();
+        registry.register::<DynamicComponentValue>();
+        registry.register::<Vec<DynamicComponentValue>>();
+        registry.register::<Vec<u8>>();
+    }
+}
+
+/// Converts the values of a runtime component to and from bytes, so they can be stored in a
+/// [`DynamicScene`](crate::DynamicScene).
+#[derive(Clone, Copy)]
+pub struct DynamicComponentSerializer {
+    /// Converts the value of a component to bytes.
+    ///
+    /// # Safety
+    /// The pointer must point to a value of the component described by the [`ComponentInfo`].
+    pub serialize: unsafe fn(Ptr<'_>, &ComponentInfo) -> Vec<u8>,
+    /// Converts bytes back to a value of the component, and inserts it into the entity.
+    /// Returns `false` without inserting anything if the bytes are not a valid value.
+    ///
+    /// # Safety
+    /// The [`ComponentId`] must belong to a component the serializer was registered for.
+    pub insert: unsafe fn(&[u8], &mut EntityWorldMut, ComponentId) -> bool,
+}
+
+impl DynamicComponentSerializer {
+    /// A serializer which copies the bytes of the values as they are.
+    ///
+    /// It can only be registered for components without a drop function or padding bytes, for
+    /// which any bytes of the right length are a valid value.
+    pub const RAW: Self = Self {
+        serialize: serialize_raw,
+        insert: insert_raw,
+    };
+}
+
+/// # Safety
+/// The pointer must point to a value of the component described by `info`, without padding.
+unsafe fn serialize_raw(ptr: Ptr<'_>, info: &ComponentInfo) -> Vec<u8> {
+    // SAFETY: the value is `size` bytes long, and the caller ensures they are all initialized
+    std::slice::from_raw_parts(ptr.as_ptr(), info.layout().size()).to_vec()
+}
+
+/// # Safety
+/// Any bytes of the size of the component must be a valid value of it.
+unsafe fn insert_raw(bytes: &[u8], entity: &mut EntityWorldMut, id: ComponentId) -> bool {
+    let Some(layout) = entity
+        .world()
+        .components()
+        .get_info(id)
+        .map(ComponentInfo::layout)
+    else {
+        return false;
+    };
+    if bytes.len() != layout.size() {
+        return false;
+    }
+    // SAFETY:
+    // - the pointers are valid for values of the layout of the component
+    // - the caller ensures the bytes are a valid value, which is moved into the entity once
+    if layout.size() == 0 {
+        let ptr = dangling_with_align(NonZeroUsize::new_unchecked(layout.align()));
+        entity.insert_by_id(id, OwningPtr::new(ptr));
+        return true;
+    }
+    let ptr = NonNull::new(alloc(layout)).unwrap_or_else(|| handle_alloc_error(layout));
+    std::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr.as_ptr(), bytes.len());
+    entity.insert_by_id(id, OwningPtr::new(ptr));
+    dealloc(ptr.as_ptr(), layout);
+    true
+}
+
+/// The [`DynamicComponentSerializer`] of each runtime component which can be stored in a
+/// [`DynamicScene`](crate::DynamicScene), keyed by component name.
+///
+/// Runtime components without a serializer are skipped by
+/// [`DynamicSceneBuilder`](crate::DynamicSceneBuilder).
+#[derive(Resource, Default, Clone)]
+pub struct DynamicComponentSerializers {
+    serializers: HashMap<String, DynamicComponentSerializer>,
+}
+
+impl DynamicComponentSerializers {
+    /// Registers the serializer of the runtime components with the given name, replacing any
+    /// previous one.
+    ///
+    /// # Safety
+    /// The serializer must be valid for every runtime component registered with this name, in
+    /// every world the serializers are used with.
+    pub unsafe fn insert(
+        &mut self,
+        name: impl Into<String>,
+        serializer: DynamicComponentSerializer,
+    ) {
+        self.serializers.insert(name.into(), serializer);
+    }
+
+    /// Returns the serializer of the runtime components with the given name.
+    pub fn get(&self, name: &str) -> Option<&DynamicComponentSerializer> {
+        self.serializers.get(name)
+    }
+
+    /// Removes the serializer of the runtime components with the given name.
+    pub fn remove(&mut self, name: &str) -> Option<DynamicComponentSerializer> {
+        self.serializers.remove(name)
+    }
+}
@@ -1,10 +1,13 @@
-use crate::{ron, DynamicSceneBuilder, Scene, SceneSpawnError};
+use crate::{
+    ron, DynamicComponentSerializers, DynamicComponents, DynamicSceneBuilder, Scene,
+    SceneSpawnError,
+};
 use bevy_ecs::{
     entity::Entity,
     reflect::{AppTypeRegistry, ReflectComponent, ReflectMapEntities},
-    world::World,
+    world::{EntityWorldMut, World},
 };
-use bevy_reflect::{Reflect, TypePath, TypeRegistryArc};
+use bevy_reflect::{FromReflect, Reflect, TypePath, TypeRegistryArc};
 use bevy_utils::{EntityHashMap, HashMap};
 use std::any::TypeId;
 
@@ -99,6 +102,11 @@ impl DynamicScene {
         // of the actual entities in the world.
         let mut scene_mappings: HashMap<TypeId, Vec<Entity>> = HashMap::default();
 
+        let serializers = world
+            .get_resource::<DynamicComponentSerializers>()
+            .cloned()
+            .unwrap_or_default();
+
         for scene_entity in &self.entities {
             // Fetch the entity with the given entity id from the `entity_map`
             // or spawn a new entity with a transiently unique id if there is
@@ -115,6 +123,14 @@ impl DynamicScene {
                         type_path: component.reflect_type_path().to_string(),
                     }
                 })?;
+                if type_info.type_id() == TypeId::of::<DynamicComponents>() {
+                    let dynamic_components = DynamicComponents::from_reflect(&**component)
+                        .ok_or_else(|| SceneSpawnError::NoRepresentedType {
+                            type_path: type_info.type_path().to_string(),
+                        })?;
+                    write_dynamic_components(entity_mut, &dynamic_components, &serializers)?;
+                    continue;
+                }
                 let registration = type_registry.get(type_info.type_id()).ok_or_else(|| {
                     SceneSpawnError::UnregisteredButReflectedType {
                         type_path: type_info.type_path().to_string(),
@@ -178,6 +194,33 @@ impl DynamicScene {
     }
 }
 
+/// Inserts the runtime components of a scene entity, matching them to the components of the
+/// world by name.
+fn write_dynamic_components(
+    entity_mut: &mut EntityWorldMut,
+    dynamic_components: &DynamicComponents,
+    serializers: &DynamicComponentSerializers,
+) -> Result<(), SceneSpawnError> {
+    for value in &dynamic_components.0 {
+        let unregistered = || SceneSpawnError::UnregisteredDynamicComponent {
+            name: value.name.clone(),
+        };
+        let id = entity_mut
+            .world()
+            .components()
+            .get_id_by_name(&value.name)
+            .ok_or_else(unregistered)?;
+        let serializer = serializers.get(&value.name).ok_or_else(unregistered)?;
+        // SAFETY: the serializer was registered for components with this name
+        if !unsafe { (serializer.insert)(&value.bytes, entity_mut, id) } {
+            return Err(SceneSpawnError::InvalidDynamicComponent {
+                name: value.name.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
 /// Serialize a given Rust data structure into rust object notation (ron).
 #[cfg(feature = "serialize")]
 pub fn serialize_ron<S>(serialize: S) -> Result<String, ron::Error>
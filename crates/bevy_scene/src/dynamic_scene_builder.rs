@@ -1,4 +1,7 @@
-use crate::{DynamicEntity, DynamicScene, SceneFilter};
+use crate::{
+    DynamicComponentSerializers, DynamicComponentValue, DynamicComponents, DynamicEntity,
+    DynamicScene, SceneFilter,
+};
 use bevy_ecs::component::{Component, ComponentId};
 use bevy_ecs::system::Resource;
 use bevy_ecs::{
@@ -8,7 +11,7 @@ use bevy_ecs::{
 };
 use bevy_reflect::Reflect;
 use bevy_utils::default;
-use std::collections::BTreeMap;
+use std::{any::TypeId, collections::BTreeMap};
 
 /// A [`DynamicScene`] builder, used to build a scene from a [`World`] by extracting some entities and resources.
 ///
@@ -19,6 +22,10 @@ use std::collections::BTreeMap;
 /// This can be changed by [specifying a filter](DynamicSceneBuilder::with_filter) or by explicitly
 /// [allowing](DynamicSceneBuilder::allow)/[denying](DynamicSceneBuilder::deny) certain components.
 ///
+/// Runtime components, which do not have a Rust type, are extracted if the world's
+/// [`DynamicComponentSerializers`] resource has a serializer for their name. They are stored in a
+/// [`DynamicComponents`] value, so they can be denied together by denying that type.
+///
 /// Extraction happens immediately and uses the filter as it exists during the time of extraction.
 ///
 /// # Resource Extraction
@@ -245,6 +252,12 @@ impl<'w> DynamicSceneBuilder<'w> {
     #[must_use]
     pub fn extract_entities(mut self, entities: impl Iterator<Item = Entity>) -> Self {
         let type_registry = self.original_world.resource::<AppTypeRegistry>().read();
+        let serializers = self
+            .original_world
+            .get_resource::<DynamicComponentSerializers>();
+        let extract_dynamic = !self
+            .component_filter
+            .is_denied_by_id(TypeId::of::<DynamicComponents>());
 
         for entity in entities {
             if self.extracted_scene.contains_key(&entity) {
@@ -256,14 +269,25 @@ impl<'w> DynamicSceneBuilder<'w> {
                 components: Vec::new(),
             };
 
+            let mut dynamic_components = Vec::new();
             let original_entity = self.original_world.entity(entity);
             for component_id in original_entity.archetype().components() {
                 let mut extract_and_push = || {
-                    let type_id = self
-                        .original_world
-                        .components()
-                        .get_info(component_id)?
-                        .type_id()?;
+                    let info = self.original_world.components().get_info(component_id)?;
+                    let Some(type_id) = info.type_id() else {
+                        if !extract_dynamic {
+                            return None;
+                        }
+                        let serializer = serializers?.get(info.name())?;
+                        let ptr = original_entity.get_by_id(component_id)?;
+                        // SAFETY: the serializer was registered for components with this name
+                        let bytes = unsafe { (serializer.serialize)(ptr, info) };
+                        dynamic_components.push(DynamicComponentValue {
+                            name: info.name().to_string(),
+                            bytes,
+                        });
+                        return Some(());
+                    };
 
                     let is_denied = self.component_filter.is_denied_by_id(type_id);
 
@@ -281,6 +305,11 @@ impl<'w> DynamicSceneBuilder<'w> {
                 };
                 extract_and_push();
             }
+            if !dynamic_components.is_empty() {
+                entry
+                    .components
+                    .push(Box::new(DynamicComponents(dynamic_components)));
+            }
             self.extracted_scene.insert(entity, entry);
         }
 
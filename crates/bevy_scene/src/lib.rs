@@ -7,6 +7,7 @@
 #![warn(missing_docs)]
 
 mod bundle;
+mod dynamic_component;
 mod dynamic_scene;
 mod dynamic_scene_builder;
 mod scene;
@@ -22,6 +23,7 @@ pub use bevy_asset::ron;
 
 use bevy_ecs::schedule::IntoSystemConfigs;
 pub use bundle::*;
+pub use dynamic_component::*;
 pub use dynamic_scene::*;
 pub use dynamic_scene_builder::*;
 pub use scene::*;
@@ -48,8 +50,15 @@ pub struct ScenePlugin;
 #[cfg(feature = "serialize")]
 impl Plugin for ScenePlugin {
     fn build(&self, app: &mut App) {
+        DynamicComponents::register_types(
+            &mut app
+                .world
+                .resource::<bevy_ecs::reflect::AppTypeRegistry>()
+                .write(),
+        );
         app.init_asset::<DynamicScene>()
             .init_asset::<Scene>()
+            .init_resource::<DynamicComponentSerializers>()
             .init_asset_loader::<SceneLoader>()
             .add_event::<SceneInstanceReady>()
             .init_resource::<SceneSpawner>()
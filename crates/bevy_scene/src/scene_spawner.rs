@@ -105,6 +105,18 @@ pub enum SceneSpawnError {
         /// The unregistered type.
         type_path: String,
     },
+    /// Scene contains a runtime component which is not registered in the world, or has no serializer.
+    #[error("scene contains the runtime component `{name}`. consider registering it in the world and adding a serializer for it to `DynamicComponentSerializers`")]
+    UnregisteredDynamicComponent {
+        /// Name of the runtime component.
+        name: String,
+    },
+    /// Scene contains a value which is not valid for its runtime component.
+    #[error("scene contains an invalid value of the runtime component `{name}`")]
+    InvalidDynamicComponent {
+        /// Name of the runtime component.
+        name: String,
+    },
     /// Scene contains a proxy without a represented type.
     #[error("scene contains dynamic type `{type_path}` without a represented type. consider changing this using `set_represented_type`.")]
     NoRepresentedType {
@@ -498,9 +498,14 @@ impl<'a, 'de> Visitor<'de> for SceneMapVisitor<'a> {
 mod tests {
     use crate::ron;
     use crate::serde::{SceneDeserializer, SceneSerializer};
-    use crate::{DynamicScene, DynamicSceneBuilder};
+    use crate::{
+        DynamicComponentSerializer, DynamicComponentSerializers, DynamicComponents, DynamicScene,
+        DynamicSceneBuilder, SceneSpawnError,
+    };
+    use bevy_ecs::component::{ComponentDescriptor, ComponentId, StorageType};
     use bevy_ecs::entity::{Entity, EntityMapper, MapEntities};
     use bevy_ecs::prelude::{Component, ReflectComponent, ReflectResource, Resource, World};
+    use bevy_ecs::ptr::OwningPtr;
     use bevy_ecs::query::{With, Without};
     use bevy_ecs::reflect::{AppTypeRegistry, ReflectMapEntities};
     use bevy_ecs::world::FromWorld;
@@ -509,6 +514,7 @@ mod tests {
     use bincode::Options;
     use serde::de::DeserializeSeed;
     use serde::Serialize;
+    use std::alloc::Layout;
     use std::io::BufReader;
 
     #[derive(Component, Reflect, Default)]
@@ -867,6 +873,104 @@ mod tests {
         assert_scene_eq(&scene, &deserialized_scene);
     }
 
+    fn init_dynamic_component(world: &mut World, name: &str, layout: Layout) -> ComponentId {
+        // SAFETY: the components are plain integers, which are `Send + Sync` and have no drop function
+        let descriptor = unsafe {
+            ComponentDescriptor::new_with_layout(name.to_string(), StorageType::Table, layout, None)
+        };
+        world.init_component_with_descriptor(descriptor)
+    }
+
+    #[test]
+    fn should_roundtrip_dynamic_components() {
+        let mut world = create_world();
+        DynamicComponents::register_types(&mut world.resource::<AppTypeRegistry>().write());
+        let mut serializers = DynamicComponentSerializers::default();
+        // SAFETY: the components are plain integers without padding
+        unsafe {
+            serializers.insert("Health", DynamicComponentSerializer::RAW);
+            serializers.insert("Position", DynamicComponentSerializer::RAW);
+        }
+        world.insert_resource(serializers.clone());
+        let health = init_dynamic_component(&mut world, "Health", Layout::new::<u32>());
+        let position = init_dynamic_component(&mut world, "Position", Layout::new::<[i64; 2]>());
+        let hidden = init_dynamic_component(&mut world, "Hidden", Layout::new::<u8>());
+
+        let mut entity = world.spawn(Foo(123));
+        // SAFETY: each value has the layout of its component
+        unsafe {
+            OwningPtr::make(100u32, |ptr| {
+                entity.insert_by_id(health, ptr);
+            });
+            OwningPtr::make([-5i64, 7], |ptr| {
+                entity.insert_by_id(position, ptr);
+            });
+            OwningPtr::make(1u8, |ptr| {
+                entity.insert_by_id(hidden, ptr);
+            });
+        }
+        let entity = entity.id();
+
+        let scene = DynamicScene::from_world(&world);
+        let serialized = scene
+            .serialize_ron(&world.resource::<AppTypeRegistry>().0)
+            .unwrap();
+        let mut deserializer = ron::de::Deserializer::from_str(&serialized).unwrap();
+        let scene_deserializer = SceneDeserializer {
+            type_registry: &world.resource::<AppTypeRegistry>().read(),
+        };
+        let deserialized_scene = scene_deserializer.deserialize(&mut deserializer).unwrap();
+
+        let mut dst_world = create_world();
+        dst_world.insert_resource(serializers);
+        // Registered in a different order, so the ids differ from the original world
+        let dst_position =
+            init_dynamic_component(&mut dst_world, "Position", Layout::new::<[i64; 2]>());
+        let dst_health = init_dynamic_component(&mut dst_world, "Health", Layout::new::<u32>());
+
+        let mut map = EntityHashMap::default();
+        deserialized_scene
+            .write_to_world(&mut dst_world, &mut map)
+            .unwrap();
+
+        let dst_entity = dst_world.entity(map[&entity]);
+        assert_eq!(dst_entity.get::<Foo>().unwrap().0, 123);
+        // SAFETY: the components have the layout they were registered with
+        unsafe {
+            let health = dst_entity.get_by_id(dst_health).unwrap().deref::<u32>();
+            let position = dst_entity
+                .get_by_id(dst_position)
+                .unwrap()
+                .deref::<[i64; 2]>();
+            assert_eq!(*health, 100);
+            assert_eq!(*position, [-5, 7]);
+        }
+    }
+
+    #[test]
+    fn should_fail_on_unregistered_dynamic_component() {
+        let mut world = create_world();
+        let mut serializers = DynamicComponentSerializers::default();
+        // SAFETY: the component is a plain integer without padding
+        unsafe { serializers.insert("Health", DynamicComponentSerializer::RAW) };
+        world.insert_resource(serializers.clone());
+        let health = init_dynamic_component(&mut world, "Health", Layout::new::<u32>());
+        let mut entity = world.spawn_empty();
+        OwningPtr::make(100u32, |ptr| {
+            // SAFETY: the value has the layout of the component
+            unsafe { entity.insert_by_id(health, ptr) };
+        });
+
+        let scene = DynamicScene::from_world(&world);
+        let mut dst_world = create_world();
+        dst_world.insert_resource(serializers);
+        let result = scene.write_to_world(&mut dst_world, &mut EntityHashMap::default());
+        assert!(matches!(
+            result,
+            Err(SceneSpawnError::UnregisteredDynamicComponent { name }) if name == "Health"
+        ));
+    }
+
     /// A crude equality checker for [`DynamicScene`], used solely for testing purposes.
     fn assert_scene_eq(expected: &DynamicScene, received: &DynamicScene) {
         assert_eq!(
@@ -1,17 +1,32 @@
 //! This example show how you can create components dynamically, spawn entities with those components
 //! as well as query for entities with those components.
+//!
+//! Run with `--verbose` to print how long each command took, `--history N` to keep the last N
+//! query results for `recall` and `--elem-limit N` to only print the first N elements of each
+//! queried component. With `--autoreg`, spawning an unknown component creates it with as many
+//! elements as values were given. With `--script`, the first failed assertion exits with a non-zero
+//! status.
 
-use std::{alloc::Layout, io::Write, ptr::NonNull};
+use std::{
+    alloc::Layout,
+    collections::VecDeque,
+    io::{self, Write},
+    ops::Range,
+    ptr::NonNull,
+    time::Instant,
+};
 
 use bevy::prelude::*;
 use bevy::{
     ecs::{
-        component::{ComponentDescriptor, ComponentId, ComponentInfo, StorageType},
-        query::{QueryBuilder, QueryData},
-        world::FilteredEntityMut,
+        archetype::ArchetypeId,
+        component::{ComponentDescriptor, ComponentId, ComponentInfo, StorageType, Tick},
+        query::{FilteredAccess, QueryBuilder},
+        world::{FilteredEntityMut, FilteredEntityRef},
     },
     ptr::OwningPtr,
-    utils::HashMap,
+    reflect::{ReflectFromPtr, ReflectRef, TypeRegistry},
+    utils::{HashMap, HashSet},
 };
 
 const PROMPT: &str = "
@@ -19,17 +34,58 @@ Commands:
     comp, c   Create new components
     spawn, s  Spawn entities
     query, q  Query for entities
+    doc, d    Show component documentation
+    parse, p  Print a query in its canonical form
+    list, l   List all components
+    tick      Show the current change tick
+    display-base  Print entity indices and component ids in decimal or hex
+    edges     List the archetype transitions caused by inserting components
+    begin     Start a batch of commands applied together by end
+    recall    Reprint the k-th most recent query result, when run with --history N
+    get       Show all the values of an entity's component
+    set       Set the values of an entity's component
+    setbit    Set a bit of the first element of an entity's component
+    clearbit  Clear a bit of the first element of an entity's component
+    lerp      Interpolate a float component between two entities
+    table     Show the entities of an archetype as a grid
+    archstats Show statistics of a component across an archetype
+    freeze    Prevent components from being inserted, written or removed
+    unfreeze  Allow frozen components to change again
+    map       Write an expression over each entity's elements to an element
+    tree      Show the fields of a reflected component
+    format    Choose how the elements of a component are printed
+    alias-view  Define a view of some elements of a component
+    dup-graph Clone an entity and the entities it references
+    closure   List the entities reachable through a relationship
+    swapreg   Swap the names of two components
+    fuse      Merge two components into a new wider component
+    verify    Check the world for inconsistencies
+    export    Write a component's data to a file
+    import    Read a component's data from a file
+    snapshot-query  Save the entities matched by a query under a name
+    restore   Restore the entities of a saved snapshot
+    assert-count  Check how many entities a query matches
+    gc        Despawn entities which are not referenced or kept by a query
+    same-as   Find entities with the same components as an entity
+    entdiff   Compare the components of two entities
+    access-stats  Show how often queries read and wrote each component
+    help, h   Search the usage of all commands
 Enter a command with no parameters for usage.";
 
 const COMPONENT_PROMPT: &str = "
 comp, c   Create new components
-    Enter a comma seperated list of type names optionally followed by a size in u64s.
-    e.g. CompA 3, CompB, CompC 2";
+    Enter a comma seperated list of type names optionally followed by a size in u64s,
+    options and a quoted documentation string.
+    Options: 'be' store elements as big-endian bytes, 'f32' elements are floats,
+        'entity' elements reference entities
+    e.g. CompA 3 \"position xyz\", CompB, CompC 2 be, Parent 1 entity";
 
 const ENTITY_PROMPT: &str = "
 spawn, s  Spawn entities
     Enter a comma seperated list of components optionally followed by values.
-    e.g. CompA 0 1 0, CompB, CompC 1";
+    Prefix the list with a count to spawn many identical entities.
+    e.g. CompA 0 1 0, CompB, CompC 1
+    e.g. 100x CompA 1, CompB 2";
 
 const QUERY_PROMPT: &str = "
 query, q  Query for entities
@@ -37,21 +93,249 @@ query, q  Query for entities
     Components with read or write access will be displayed with their values
     Components with write access will have their fields incremented by one
 
-    Accesses: 'A' with, '&A' read, '&mut A' write
-    Operators: '||' or, ',' and, '?' optional
-    
+    Accesses: 'A' with, '!A' without, '&A' read, '&mut A' write
+    Filters: 'Added(A)' and 'Changed(A)' change detection filters
+    Operators: '||' or, ',' and, '?' optional, '(A, B)' group
+    Elements: '&mut A[0,2]' only increments the listed elements of a write
+    Comparisons: 'A[0] == B[1]' only matches entities where the elements are equal
+    Distinct: 'A distinct' only prints the first entity with each value of A
+    Changes: 'since:5 A' only matches entities where A was added or changed at or after tick 5,
+        every since: term of a query must use the same tick
+    Order: 'order<10' only matches the first 10 entities spawned this session, also
+        with '<=', '>', '>=' and '=='
+
+    Enter 'empty' to list the entities without any components.
+
+    e.g. &A || &B, &mut C, D, ?E";
+
+const PARSE_PROMPT: &str = "
+parse, p  Show how a query is understood
+    Enter a query to print it back in its canonical form, built from the query's access
     e.g. &A || &B, &mut C, D, ?E";
 
+const SAME_AS_PROMPT: &str = "
+same-as   Find entities with exactly the same components as an entity
+    Enter an entity id.
+    e.g. 3v1";
+
+const ENTDIFF_PROMPT: &str = "
+entdiff   Compare the components of two entities
+    Enter two entity ids. Components only on one of them are listed, followed by the
+    components on both whose values differ.
+    e.g. 3v1 7v1";
+
+const SET_PROMPT: &str = "
+set       Set the values of an entity's component
+    Enter an entity id, a component and its new values.
+    e.g. 3v1 CompA 4 5 6";
+
+const GET_PROMPT: &str = "
+get       Show all the values of an entity's component
+    Enter an entity id and a component. Every element is printed, even when queries
+    truncate them with --elem-limit.
+    e.g. 3v1 CompA";
+
+const BIT_PROMPT: &str = "
+setbit, clearbit  Set or clear a bit of the first element of an entity's component
+    Enter an entity id, a component and the index of the bit, from 0 to 63.
+    Use 'format CompA bits' to print the set bits.
+    e.g. 3v1 CompA 3";
+
+const LERP_PROMPT: &str = "
+lerp      Interpolate a float typed component between two entities
+    Enter a component, the entities to interpolate between, a factor and optionally
+    the entity to write the result to, which defaults to the first entity.
+    e.g. Position 3v1 7v1 0.5 9v1";
+
+const DOC_PROMPT: &str = "
+doc, d    Show component documentation
+    Enter a comma separated list of component names.
+    e.g. CompA, CompB";
+
+const FREEZE_PROMPT: &str = "
+freeze    Prevent components from being inserted, written or removed
+    Enter a comma separated list of components. Commands writing them fail and gc keeps
+    entities with them, until they are unfrozen with the same syntax.
+    e.g. CompA, CompB";
+
+const MAP_PROMPT: &str = "
+map       Write an expression over each entity's elements to an element
+    Enter an element, '=', an expression and optionally 'where' followed by a query.
+    Expressions use numbers, elements, '+', '-', '*', '/' and parentheses, and only
+    match entities with every component they use.
+    e.g. CompA[0] = CompA[0] * 2 + CompB[1] where &CompC";
+
+const TREE_PROMPT: &str = "
+tree      Show the fields of a reflected component
+    Enter an entity id and a reflected component. Reflected components such as Transform
+    can be spawned by name alongside dynamic components.
+    e.g. 3v1 Transform";
+
+const FORMAT_PROMPT: &str = "
+format    Choose how the elements of a component are printed
+    Enter a component and a formatter, or 'default' to print elements by their kind.
+    Formatters: 'hex' hexadecimal, 'color' #RRGGBB from the low 24 bits,
+        'bool-list' true for non-zero elements, 'bits' the indices of set bits
+    e.g. CompA color";
+
+const ALIAS_VIEW_PROMPT: &str = "
+alias-view  Define a view of some elements of a component
+    Enter a name, '=' and a component with the elements to expose. Queries can use the view
+    in place of the component, printing and writing only its elements.
+    e.g. PosXY = CompA[0,1]";
+
+const DUP_GRAPH_PROMPT: &str = "
+dup-graph Clone an entity and the entities it references
+    Enter an entity id. Every entity reachable through entity typed components is cloned
+    and references between them are rewritten to point at the clones.
+    e.g. 3v1";
+
+const CLOSURE_PROMPT: &str = "
+closure   List the entities reachable through a relationship
+    Enter an entity id and an entity typed component. Every entity reached by following
+    the component from entity to entity is listed once, even when references form a cycle.
+    e.g. 3v1 Parent";
+
+const SWAPREG_PROMPT: &str = "
+swapreg   Swap the names of two components
+    Enter two component names. Entities keep their data, but each name now refers to
+    the other component.
+    e.g. CompA CompB";
+
+const FUSE_PROMPT: &str = "
+fuse      Merge two components into a new wider component
+    Enter two components with the same options, '->' and the name of the new component.
+    Entities with both have their elements concatenated into the new component and the
+    originals removed. Entities with only one are skipped unless 'fill' is given, in
+    which case the missing elements take their default value.
+    e.g. CompA CompB -> CompAB fill";
+
+const TABLE_PROMPT: &str = "
+table     Show the entities of an archetype as a grid
+    Enter an archetype id, as printed when spawning.
+    e.g. 2";
+
+const ARCHSTATS_PROMPT: &str = "
+archstats Show statistics of a component across an archetype
+    Enter an archetype id and a component. The minimum, maximum and mean of the first
+    element are read directly from the archetype's table.
+    e.g. 2 CompA";
+
+const EXPORT_PROMPT: &str = "
+export    Write a component's data to a file
+    Enter a component and a path. The file records the entity count and element width
+    followed by the id and element values of every entity with the component, all as
+    little-endian u64s.
+    e.g. CompA comp_a.bin";
+
+const IMPORT_PROMPT: &str = "
+import    Read a component's data from a file
+    Enter a component and a path written by export. Entities which still exist are
+    updated and the rest are spawned.
+    e.g. CompA comp_a.bin";
+
+const SNAPSHOT_PROMPT: &str = "
+snapshot-query  Save the entities matched by a query under a name
+    Enter a query, 'into' and a name. The components of every matched entity are kept
+    in memory until restored with 'restore <name>', which updates entities that still
+    exist and spawns the rest.
+    e.g. &CompA, !CompB into saveA";
+
+const ASSERT_PROMPT: &str = "
+assert-count  Check how many entities a query matches
+    Enter a query, a comparison and a count. Comparisons: '==', '!=', '<', '<=', '>', '>='
+    'assert-empty' and 'assert-nonempty' take only a query. A failed assertion exits with
+    a non-zero status when run with --script.
+    e.g. &CompA, !CompB == 3";
+
+const BATCH_PROMPT: &str = "
+begin     Start a batch of commands applied together by end
+    Following spawn and set commands are buffered until 'end'. They are only run if
+    every one of them is valid, otherwise none are and the batch is rolled back.
+    Entities must exist before the batch to be referenced within it.
+    e.g. begin, spawn CompA 1, set 3v1 CompB 2, end";
+
+const DISPLAY_BASE_PROMPT: &str = "
+display-base  Print entity indices and component ids in decimal or hex
+    Enter 'dec' or 'hex'. Hexadecimal entity indices such as 0x1fv1 are accepted as input
+    in either base.
+    e.g. hex";
+
+const HELP_PROMPT: &str = "
+help, h   Search the usage of all commands
+    Enter a keyword to print every usage line containing it.
+    e.g. entity";
+
+/// Every usage prompt, searched by the `help` command.
+const PROMPTS: &[&str] = &[
+    PROMPT,
+    COMPONENT_PROMPT,
+    ENTITY_PROMPT,
+    QUERY_PROMPT,
+    PARSE_PROMPT,
+    SAME_AS_PROMPT,
+    ENTDIFF_PROMPT,
+    GET_PROMPT,
+    SET_PROMPT,
+    BIT_PROMPT,
+    LERP_PROMPT,
+    DOC_PROMPT,
+    DUP_GRAPH_PROMPT,
+    CLOSURE_PROMPT,
+    ALIAS_VIEW_PROMPT,
+    FORMAT_PROMPT,
+    MAP_PROMPT,
+    TREE_PROMPT,
+    FREEZE_PROMPT,
+    SWAPREG_PROMPT,
+    FUSE_PROMPT,
+    TABLE_PROMPT,
+    ARCHSTATS_PROMPT,
+    EXPORT_PROMPT,
+    IMPORT_PROMPT,
+    SNAPSHOT_PROMPT,
+    ASSERT_PROMPT,
+    BATCH_PROMPT,
+    DISPLAY_BASE_PROMPT,
+    HELP_PROMPT,
+];
+
 fn main() {
-    let mut world = World::new();
+    let mut session = Session::default();
+    session.type_registry.register::<Transform>();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--verbose" => session.verbose = true,
+            "--autoreg" => session.autoreg = true,
+            "--script" => session.script = true,
+            "--history" => match args.next().and_then(|len| len.parse().ok()) {
+                Some(len) => session.history_len = len,
+                None => {
+                    eprintln!("--history expects the number of query results to keep");
+                    return;
+                }
+            },
+            "--elem-limit" => match args.next().and_then(|limit| limit.parse().ok()) {
+                Some(limit) => session.elem_limit = Some(limit),
+                None => {
+                    eprintln!("--elem-limit expects the number of elements to print");
+                    return;
+                }
+            },
+            _ => {
+                eprintln!("Unknown argument: {}", arg);
+                return;
+            }
+        }
+    }
     let mut lines = std::io::stdin().lines();
-    let mut component_names = HashMap::<String, ComponentId>::new();
-    let mut component_info = HashMap::<ComponentId, ComponentInfo>::new();
+    let mut stdout = std::io::stdout();
 
     println!("{}", PROMPT);
     loop {
         print!("\n> ");
-        let _ = std::io::stdout().flush();
+        let _ = stdout.flush();
         let Some(Ok(line)) = lines.next() else {
             return;
         };
@@ -60,184 +344,3816 @@ fn main() {
             return;
         };
 
-        let Some((first, rest)) = line.trim().split_once(|c: char| c.is_whitespace()) else {
-            match &line.chars().next() {
-                Some('c') => println!("{}", COMPONENT_PROMPT),
-                Some('s') => println!("{}", ENTITY_PROMPT),
-                Some('q') => println!("{}", QUERY_PROMPT),
-                _ => println!("{}", PROMPT),
+        let _ = session.execute(&line, &mut stdout);
+        if let Some(code) = session.exit_code() {
+            std::process::exit(code);
+        }
+    }
+}
+
+/// A single element of a component, identified by its index.
+type Element = (ComponentId, usize);
+
+/// The decoded values of every component of an entity, as saved by `snapshot-query`.
+type EntitySnapshot = (Entity, Vec<(ComponentId, Vec<u64>)>);
+
+/// The state of a dynamic ECS session: the world and the components created at runtime.
+#[derive(Default)]
+struct Session {
+    world: World,
+    component_names: HashMap<String, ComponentId>,
+    component_info: HashMap<ComponentId, ComponentInfo>,
+    component_meta: HashMap<ComponentId, ComponentMeta>,
+    access_stats: HashMap<ComponentId, AccessStats>,
+    /// Components which can not be inserted, written or removed.
+    frozen: HashSet<ComponentId>,
+    /// Rust components which can be spawned by their short type path and inspected with `tree`.
+    type_registry: TypeRegistry,
+    /// Print how long each command took.
+    verbose: bool,
+    /// Create unknown components referenced by `spawn` instead of skipping them.
+    autoreg: bool,
+    /// Exit as soon as an assertion fails.
+    script: bool,
+    /// How many assertions have failed during the session.
+    failed_assertions: usize,
+    /// Virtual components exposing a subset of the elements of a backing component.
+    views: HashMap<String, (ComponentId, Vec<usize>)>,
+    /// The most recent query results, newest first, for `recall`.
+    history: VecDeque<String>,
+    /// How many query results to keep in the history.
+    history_len: usize,
+    /// The most elements of each component printed by a query.
+    elem_limit: Option<usize>,
+    /// Every distinct archetype move caused by inserting or removing components, in the order
+    /// they were first seen.
+    transitions: Vec<(ArchetypeId, ArchetypeId)>,
+    /// The commands buffered since `begin`, if a batch is open.
+    batch: Option<Vec<String>>,
+    /// Entities saved by `snapshot-query`, by the name they were saved under.
+    snapshots: HashMap<String, Vec<EntitySnapshot>>,
+    /// The base entity indices and component ids are printed in.
+    radix: Radix,
+    /// The order each living entity was spawned in during the session, starting from 0.
+    spawn_order: HashMap<Entity, usize>,
+    /// How many entities have been spawned during the session.
+    spawn_count: usize,
+}
+
+/// Metadata the session keeps for each component alongside its [`ComponentInfo`].
+#[derive(Default)]
+struct ComponentMeta {
+    /// The name the session refers to the component by, which may differ from its registered
+    /// name after `swapreg`.
+    name: String,
+    doc: Option<String>,
+    /// The number of elements the component was created with, checked against its stored layout
+    /// by `verify`.
+    len: usize,
+    kind: ElementKind,
+    /// Elements are stored as big-endian bytes, e.g. to mirror an external binary format.
+    big_endian: bool,
+    /// How elements are printed instead of by their kind, selected with `format`.
+    formatter: Option<Formatter>,
+}
+
+/// The base entity indices and component ids are printed in, chosen with `display-base`.
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+enum Radix {
+    #[default]
+    Decimal,
+    Hex,
+}
+
+impl Radix {
+    /// Formats an entity like its debug format, e.g. `3v1` or `0x3v1`.
+    fn entity(self, entity: Entity) -> String {
+        match self {
+            Radix::Decimal => format!("{:?}", entity),
+            Radix::Hex => format!("{:#x}v{}", entity.index(), entity.generation()),
+        }
+    }
+
+    /// Formats the index of a component id.
+    fn id(self, id: ComponentId) -> String {
+        match self {
+            Radix::Decimal => id.index().to_string(),
+            Radix::Hex => format!("{:#x}", id.index()),
+        }
+    }
+}
+
+/// How the u64 elements of a component are interpreted.
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+enum ElementKind {
+    #[default]
+    U64,
+    /// Each element holds the bits of an `f32`.
+    F32,
+    /// Each element references another entity, making the component a relationship.
+    Entity,
+}
+
+/// A built-in way of printing the elements of a component.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Formatter {
+    /// Each element in hexadecimal.
+    Hex,
+    /// The low 24 bits of each element as an `#RRGGBB` color.
+    Color,
+    /// Each element as `true` if it is non-zero.
+    BoolList,
+    /// The indices of the set bits of each element, for elements packing flags.
+    Bits,
+}
+
+impl Formatter {
+    /// Every formatter with the name `format` selects it by.
+    const ALL: [(&'static str, Formatter); 4] = [
+        ("hex", Formatter::Hex),
+        ("color", Formatter::Color),
+        ("bool-list", Formatter::BoolList),
+        ("bits", Formatter::Bits),
+    ];
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .find(|(formatter, _)| *formatter == name)
+            .map(|&(_, formatter)| formatter)
+    }
+
+    /// Formats the decoded value of a single element.
+    fn format(self, value: u64) -> String {
+        match self {
+            Formatter::Hex => format!("{:#x}", value),
+            Formatter::Color => format!("#{:06X}", value & 0xFF_FFFF),
+            Formatter::BoolList => (value != 0).to_string(),
+            Formatter::Bits => {
+                let bits: Vec<_> = (0..u64::BITS)
+                    .filter(|bit| value & (1 << bit) != 0)
+                    .map(|bit| bit.to_string())
+                    .collect();
+                format!("bits: {}", bits.join(","))
             }
-            continue;
+        }
+    }
+}
+
+impl ComponentMeta {
+    /// Converts a stored element into the value it represents.
+    fn decode(&self, element: u64) -> u64 {
+        if self.big_endian {
+            u64::from_be(element)
+        } else {
+            element
+        }
+    }
+
+    /// Converts a value into the element to store.
+    fn encode(&self, value: u64) -> u64 {
+        if self.big_endian {
+            value.to_be()
+        } else {
+            value
+        }
+    }
+
+    /// The value of an element which has not been given one.
+    fn default_value(&self) -> u64 {
+        match self.kind {
+            ElementKind::U64 => 0,
+            ElementKind::F32 => 0f32.to_bits() as u64,
+            ElementKind::Entity => Entity::PLACEHOLDER.to_bits(),
+        }
+    }
+
+    /// Increments a stored element by one, leaving entity references untouched.
+    fn increment(&self, data: &mut u64) {
+        let value = self.decode(*data);
+        let value = match self.kind {
+            ElementKind::U64 => value + 1,
+            ElementKind::F32 => (f32::from_bits(value as u32) + 1.0).to_bits() as u64,
+            ElementKind::Entity => value,
         };
+        *data = self.encode(value);
+    }
 
-        match &first[0..1] {
-            "c" => {
-                rest.split(',').for_each(|component| {
-                    let mut component = component.split_whitespace();
-                    let Some(name) = component.next() else {
-                        return;
-                    };
-                    let size = match component.next().map(|s| s.parse::<usize>()) {
-                        Some(Ok(size)) => size,
-                        _ => 0,
-                    };
-                    // SAFETY: [u64] is Send + Sync
-                    let id = world.init_component_with_descriptor(unsafe {
-                        ComponentDescriptor::new_with_layout(
-                            name.to_string(),
-                            StorageType::Table,
-                            Layout::array::<u64>(size).unwrap(),
-                            None,
-                        )
-                    });
-                    let Some(info) = world.components().get_info(id) else {
-                        return;
-                    };
-                    component_names.insert(name.to_string(), id);
-                    component_info.insert(id, info.clone());
-                    println!("Component {} created with id: {:?}", name, id.index());
-                });
-            }
-            "s" => {
-                let mut to_insert_ids = Vec::new();
-                let mut to_insert_ptr = Vec::new();
-                rest.split(',').for_each(|component| {
-                    let mut component = component.split_whitespace();
-                    let Some(name) = component.next() else {
-                        return;
-                    };
-                    let Some(&id) = component_names.get(name) else {
-                        println!("Component {} does not exist", name);
-                        return;
-                    };
-                    let info = world.components().get_info(id).unwrap();
-                    let len = info.layout().size() / std::mem::size_of::<u64>();
-                    let mut values: Vec<u64> = component
-                        .take(len)
-                        .filter_map(|value| value.parse::<u64>().ok())
-                        .collect();
-
-                    // SAFETY:
-                    // - All components will be interpreted as [u64]
-                    // - len and layout are taken directly from the component descriptor
-                    let ptr = unsafe {
-                        let data = std::alloc::alloc_zeroed(info.layout()).cast::<u64>();
-                        data.copy_from(values.as_mut_ptr(), values.len());
-                        let non_null = NonNull::new_unchecked(data.cast());
-                        OwningPtr::new(non_null)
-                    };
+    /// Converts a value into a number for evaluating expressions.
+    fn value_to_number(&self, value: u64) -> f64 {
+        match self.kind {
+            ElementKind::F32 => f32::from_bits(value as u32) as f64,
+            ElementKind::U64 | ElementKind::Entity => value as f64,
+        }
+    }
 
-                    to_insert_ids.push(id);
-                    to_insert_ptr.push(ptr);
-                });
+    /// Converts the result of an expression into a value, saturating integers.
+    fn number_to_value(&self, number: f64) -> u64 {
+        match self.kind {
+            ElementKind::F32 => (number as f32).to_bits() as u64,
+            ElementKind::U64 | ElementKind::Entity => number as u64,
+        }
+    }
 
-                let mut entity = world.spawn_empty();
-                // SAFETY:
-                // - Component ids have been taken from the same world
-                // - The pointer with the correct layout
-                unsafe {
-                    entity.insert_by_ids(&to_insert_ids, to_insert_ptr.into_iter());
-                }
-                println!("Entity spawned with id: {:?}", entity.id());
-            }
-            "q" => {
-                let mut builder = QueryBuilder::<FilteredEntityMut>::new(&mut world);
-                parse_query(rest, &mut builder, &component_names);
-                let mut query = builder.build();
-
-                query.iter_mut(&mut world).for_each(|filtered_entity| {
-                    let terms = filtered_entity
-                        .components()
-                        .map(|id| {
-                            let ptr = filtered_entity.get_by_id(id).unwrap();
-                            let info = component_info.get(&id).unwrap();
-                            let len = info.layout().size() / std::mem::size_of::<u64>();
+    /// Returns the entities referenced by the stored elements of an entity typed component.
+    fn entities<'a>(&'a self, data: &'a [u64]) -> impl Iterator<Item = Entity> + 'a {
+        data.iter()
+            .filter(|_| self.kind == ElementKind::Entity)
+            .filter_map(|&data| Entity::try_from_bits(self.decode(data)).ok())
+            .filter(|&entity| entity != Entity::PLACEHOLDER)
+    }
 
-                            // SAFETY:
-                            // - All components are created with layout [u64]
-                            // - len is calculated from the component descriptor
-                            let data = unsafe {
-                                std::slice::from_raw_parts_mut(
-                                    ptr.assert_unique().as_ptr().cast::<u64>(),
-                                    len,
-                                )
-                            };
-                            if filtered_entity.access().has_write(id) {
-                                data.iter_mut().for_each(|data| {
-                                    *data += 1;
-                                });
-                            }
+    /// Formats the stored elements of a component.
+    fn format(&self, data: &[u64], radix: Radix) -> String {
+        let values: Vec<_> = data
+            .iter()
+            .map(|&data| {
+                let value = self.decode(data);
+                if let Some(formatter) = self.formatter {
+                    return formatter.format(value);
+                }
+                match self.kind {
+                    ElementKind::U64 => value.to_string(),
+                    ElementKind::F32 => f32::from_bits(value as u32).to_string(),
+                    ElementKind::Entity => match Entity::try_from_bits(value) {
+                        Ok(entity) if entity != Entity::PLACEHOLDER => radix.entity(entity),
+                        _ => "none".to_string(),
+                    },
+                }
+            })
+            .collect();
+        format!("[{}]", values.join(", "))
+    }
 
-                            format!("{}: {:?}", info.name(), data[0..len].to_vec())
-                        })
-                        .collect::<Vec<_>>()
-                        .join(", ");
+    /// Formats the stored elements of a component, leaving out those past `limit`.
+    fn format_limited(&self, data: &[u64], limit: Option<usize>, radix: Radix) -> String {
+        match limit {
+            Some(limit) if data.len() > limit => format!(
+                "{} … (+{} more)",
+                self.format(&data[..limit], radix),
+                data.len() - limit
+            ),
+            _ => self.format(data, radix),
+        }
+    }
+}
 
-                    println!("{:?}: {}", filtered_entity.id(), terms);
-                });
+/// An entity spawned by a `spawn` command, with the initial values of its dynamic components,
+/// for code driving a [`Session`] to react to.
+#[derive(Clone, PartialEq, Debug)]
+struct SpawnOutcome {
+    entity: Entity,
+    components: Vec<(ComponentId, Vec<u64>)>,
+}
+
+/// How many times a component has been read and written by queries during a session.
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+struct AccessStats {
+    reads: usize,
+    writes: usize,
+}
+
+/// An arithmetic expression over the elements of an entity's components, used by `map`.
+enum Expr {
+    Number(f64),
+    Element(Element),
+    Neg(Box<Expr>),
+    Binary(Box<Expr>, char, Box<Expr>),
+}
+
+impl Expr {
+    /// Evaluates the expression, reading elements with `element`.
+    fn eval(&self, element: &impl Fn(Element) -> f64) -> f64 {
+        match self {
+            Expr::Number(number) => *number,
+            Expr::Element(e) => element(*e),
+            Expr::Neg(expr) => -expr.eval(element),
+            Expr::Binary(lhs, op, rhs) => {
+                let (lhs, rhs) = (lhs.eval(element), rhs.eval(element));
+                match op {
+                    '+' => lhs + rhs,
+                    '-' => lhs - rhs,
+                    '*' => lhs * rhs,
+                    _ => lhs / rhs,
+                }
             }
-            _ => continue,
         }
     }
-}
 
-fn parse_term<Q: QueryData>(
-    str: &str,
-    builder: &mut QueryBuilder<Q>,
-    components: &HashMap<String, ComponentId>,
-) {
-    let mut matched = false;
-    let str = str.trim();
-    match str.chars().next() {
-        Some('?') => {
-            builder.optional(|b| parse_term(&str[1..], b, components));
-            matched = true;
+    /// Returns every element read by the expression.
+    fn elements(&self, elements: &mut Vec<Element>) {
+        match self {
+            Expr::Number(_) => {}
+            Expr::Element(e) => elements.push(*e),
+            Expr::Neg(expr) => expr.elements(elements),
+            Expr::Binary(lhs, _, rhs) => {
+                lhs.elements(elements);
+                rhs.elements(elements);
+            }
         }
-        Some('&') => {
-            let mut parts = str.split_whitespace();
-            let first = parts.next().unwrap();
-            if first == "&mut" {
-                if let Some(str) = parts.next() {
-                    if let Some(&id) = components.get(str) {
-                        builder.mut_id(id);
-                        matched = true;
+    }
+}
+
+/// A recursive descent parser for [`Expr`], resolving elements against the session.
+struct ExprParser<'a> {
+    session: &'a Session,
+    tokens: Vec<String>,
+    pos: usize,
+}
+
+impl<'a> ExprParser<'a> {
+    fn new(session: &'a Session, str: &str) -> Self {
+        let mut tokens = Vec::new();
+        let mut chars = str.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c.is_whitespace() {
+                continue;
+            }
+            let mut token = c.to_string();
+            if c.is_alphanumeric() || c == '_' || c == '.' {
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_' || c == '.') {
+                        break;
                     }
-                };
-            } else if let Some(&id) = components.get(&first[1..]) {
-                builder.ref_id(id);
-                matched = true;
+                    token.push(c);
+                    chars.next();
+                }
+                if token.starts_with(|c: char| c.is_alphabetic()) && chars.peek() == Some(&'[') {
+                    for c in chars.by_ref() {
+                        token.push(c);
+                        if c == ']' {
+                            break;
+                        }
+                    }
+                }
             }
+            tokens.push(token);
         }
-        Some(_) => {
-            if let Some(&id) = components.get(str) {
-                builder.with_id(id);
-                matched = true;
-            }
+        Self {
+            session,
+            tokens,
+            pos: 0,
         }
-        None => {}
-    };
+    }
+
+    fn parse(mut self) -> Result<Expr, String> {
+        let expr = self.expr()?;
+        match self.tokens.get(self.pos) {
+            Some(token) => Err(format!("Unexpected {} in expression", token)),
+            None => Ok(expr),
+        }
+    }
+
+    fn next_if(&mut self, ops: &str) -> Option<char> {
+        let token = self.tokens.get(self.pos)?;
+        let op = token
+            .chars()
+            .next()
+            .filter(|op| token.len() == 1 && ops.contains(*op))?;
+        self.pos += 1;
+        Some(op)
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut expr = self.term()?;
+        while let Some(op) = self.next_if("+-") {
+            expr = Expr::Binary(Box::new(expr), op, Box::new(self.term()?));
+        }
+        Ok(expr)
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut expr = self.factor()?;
+        while let Some(op) = self.next_if("*/") {
+            expr = Expr::Binary(Box::new(expr), op, Box::new(self.factor()?));
+        }
+        Ok(expr)
+    }
 
-    if !matched {
-        println!("Unable to find component: {}", str);
+    fn factor(&mut self) -> Result<Expr, String> {
+        if self.next_if("-").is_some() {
+            return Ok(Expr::Neg(Box::new(self.factor()?)));
+        }
+        if self.next_if("(").is_some() {
+            let expr = self.expr()?;
+            return match self.next_if(")") {
+                Some(_) => Ok(expr),
+                None => Err("Expected ) in expression".to_string()),
+            };
+        }
+        let Some(token) = self.tokens.get(self.pos) else {
+            return Err("Unexpected end of expression".to_string());
+        };
+        self.pos += 1;
+        if let Ok(number) = token.parse::<f64>() {
+            return Ok(Expr::Number(number));
+        }
+        let element = self.session.parse_element(token)?;
+        if self.session.component_meta[&element.0].kind == ElementKind::Entity {
+            return Err(format!("Component elements {} reference entities", token));
+        }
+        Ok(Expr::Element(element))
     }
 }
 
-fn parse_query<Q: QueryData>(
-    str: &str,
-    builder: &mut QueryBuilder<Q>,
-    components: &HashMap<String, ComponentId>,
-) {
-    let str = str.split(',');
-    str.for_each(|term| {
-        let sub_terms: Vec<_> = term.split("||").collect();
-        if sub_terms.len() == 1 {
-            parse_term(sub_terms[0], builder, components);
+impl Session {
+    /// Parses and runs a single command, writing any output to `out`.
+    fn execute(&mut self, line: &str, out: &mut impl Write) -> io::Result<()> {
+        let (command, rest) = split_command(line);
+
+        // Every command runs at its own tick so changes can be told apart with `since:`
+        self.world.increment_change_tick();
+        let start = Instant::now();
+        self.dispatch(command, rest, out)?;
+        if self.verbose {
+            writeln!(out, "(took {:.1?})", start.elapsed())?;
+        }
+        Ok(())
+    }
+
+    fn dispatch(&mut self, command: &str, rest: &str, out: &mut impl Write) -> io::Result<()> {
+        match command {
+            "begin" if self.batch.is_some() => writeln!(out, "A batch is already open"),
+            "begin" => {
+                self.batch = Some(Vec::new());
+                writeln!(out, "Batch started, enter end to apply it")
+            }
+            "end" => self.end_batch(out),
+            _ if self.batch.is_some() => {
+                let batch = self.batch.as_mut().unwrap();
+                batch.push(format!("{} {}", command, rest));
+                writeln!(out, "Batched command {}", batch.len())
+            }
+            "list" | "l" => self.list_components(out),
+            "edges" => self.show_edges(out),
+            "display-base" => match rest.trim() {
+                "dec" => {
+                    self.radix = Radix::Decimal;
+                    writeln!(out, "Printing ids in decimal")
+                }
+                "hex" => {
+                    self.radix = Radix::Hex;
+                    writeln!(out, "Printing ids in hex")
+                }
+                _ => writeln!(out, "{}", DISPLAY_BASE_PROMPT),
+            },
+            "tick" => writeln!(out, "Current tick: {}", self.world.read_change_tick().get()),
+            "access-stats" => self.show_access_stats(out),
+            "same-as" if rest.is_empty() => writeln!(out, "{}", SAME_AS_PROMPT),
+            "same-as" => self.same_as(rest, out),
+            "entdiff" if rest.is_empty() => writeln!(out, "{}", ENTDIFF_PROMPT),
+            "entdiff" => self.entity_diff(rest, out),
+            "comp" | "c" if rest.is_empty() => writeln!(out, "{}", COMPONENT_PROMPT),
+            "spawn" | "s" if rest.is_empty() => writeln!(out, "{}", ENTITY_PROMPT),
+            "query" | "q" if rest.is_empty() => writeln!(out, "{}", QUERY_PROMPT),
+            "parse" | "p" if rest.is_empty() => writeln!(out, "{}", PARSE_PROMPT),
+            "doc" | "d" if rest.is_empty() => writeln!(out, "{}", DOC_PROMPT),
+            "get" if rest.is_empty() => writeln!(out, "{}", GET_PROMPT),
+            "set" if rest.is_empty() => writeln!(out, "{}", SET_PROMPT),
+            "help" | "h" if rest.is_empty() => writeln!(out, "{}", HELP_PROMPT),
+            "comp" | "c" => self.create_components(rest, out),
+            "spawn" | "s" => self.spawn_entity(rest, out).map(|_| ()),
+            "query" | "q" => {
+                let mut result = Vec::new();
+                self.query(rest, &mut result)?;
+                out.write_all(&result)?;
+                if self.history_len > 0 {
+                    self.history.truncate(self.history_len - 1);
+                    self.history
+                        .push_front(String::from_utf8_lossy(&result).into_owned());
+                }
+                Ok(())
+            }
+            "recall" => self.recall(rest, out),
+            "parse" | "p" => self.parse(rest, out),
+            "doc" | "d" => self.show_docs(rest, out),
+            "get" => self.get(rest, out),
+            "set" => self.set(rest, out),
+            "setbit" | "clearbit" if rest.is_empty() => writeln!(out, "{}", BIT_PROMPT),
+            "setbit" => self.set_bit(rest, true, out),
+            "clearbit" => self.set_bit(rest, false, out),
+            "gc" => self.gc(rest, out),
+            "lerp" => self.lerp(rest, out),
+            "verify" => self.verify(out),
+            "swapreg" => self.swap_registrations(rest, out),
+            "fuse" if rest.is_empty() => writeln!(out, "{}", FUSE_PROMPT),
+            "fuse" => self.fuse(rest, out),
+            "dup-graph" if rest.is_empty() => writeln!(out, "{}", DUP_GRAPH_PROMPT),
+            "dup-graph" => self.dup_graph(rest, out),
+            "closure" if rest.is_empty() => writeln!(out, "{}", CLOSURE_PROMPT),
+            "closure" => self.closure(rest, out),
+            "format" if rest.is_empty() => writeln!(out, "{}", FORMAT_PROMPT),
+            "format" => self.set_formatter(rest, out),
+            "alias-view" if rest.is_empty() => writeln!(out, "{}", ALIAS_VIEW_PROMPT),
+            "alias-view" => self.alias_view(rest, out),
+            "map" if rest.is_empty() => writeln!(out, "{}", MAP_PROMPT),
+            "map" => self.map(rest, out),
+            "tree" if rest.is_empty() => writeln!(out, "{}", TREE_PROMPT),
+            "tree" => self.tree(rest, out),
+            "freeze" | "unfreeze" if rest.is_empty() => writeln!(out, "{}", FREEZE_PROMPT),
+            "freeze" => self.freeze(rest, true, out),
+            "unfreeze" => self.freeze(rest, false, out),
+            "table" if rest.is_empty() => writeln!(out, "{}", TABLE_PROMPT),
+            "table" => self.table(rest, out),
+            "archstats" if rest.is_empty() => writeln!(out, "{}", ARCHSTATS_PROMPT),
+            "archstats" => self.archetype_stats(rest, out),
+            "export" if rest.is_empty() => writeln!(out, "{}", EXPORT_PROMPT),
+            "import" if rest.is_empty() => writeln!(out, "{}", IMPORT_PROMPT),
+            "snapshot-query" | "restore" if rest.is_empty() => writeln!(out, "{}", SNAPSHOT_PROMPT),
+            "assert-count" | "assert-empty" | "assert-nonempty" if rest.is_empty() => {
+                writeln!(out, "{}", ASSERT_PROMPT)
+            }
+            "assert-count" => self.assert_count(rest, out),
+            "assert-empty" => self.assert_count(&format!("{} == 0", rest), out),
+            "assert-nonempty" => self.assert_count(&format!("{} > 0", rest), out),
+            "snapshot-query" => self.snapshot_query(rest, out),
+            "restore" => self.restore(rest, out),
+            "export" => self.export(rest, out),
+            "import" => self.import(rest, out),
+            "help" | "h" => help(rest, out),
+            _ => writeln!(out, "{}", PROMPT),
+        }
+    }
+
+    /// Runs the commands buffered since `begin` if every one of them is valid.
+    fn end_batch(&mut self, out: &mut impl Write) -> io::Result<()> {
+        let Some(batch) = self.batch.take() else {
+            return writeln!(out, "No batch is open, start one with begin");
+        };
+        for (i, line) in batch.iter().enumerate() {
+            if let Err(err) = self.validate(line) {
+                return writeln!(out, "Batch rolled back, command {} failed: {}", i + 1, err);
+            }
+        }
+        for line in &batch {
+            let (command, rest) = split_command(line);
+            self.dispatch(command, rest, out)?;
+        }
+        writeln!(out, "Batch of {} commands applied", batch.len())
+    }
+
+    /// Checks that a batched command would succeed without running it.
+    fn validate(&self, line: &str) -> Result<(), String> {
+        let (command, rest) = split_command(line);
+        match command {
+            "spawn" | "s" => self.validate_spawn(rest),
+            "set" => self.validate_set(rest),
+            _ => Err(format!("{} can not be batched", command)),
+        }
+    }
+
+    fn validate_spawn(&self, rest: &str) -> Result<(), String> {
+        let rest = match rest.split_once(char::is_whitespace) {
+            Some((count, components)) if count.ends_with('x') => {
+                if count[..count.len() - 1].parse::<usize>().is_err() {
+                    return Err(format!("Invalid entity count: {}", count));
+                }
+                components
+            }
+            _ => rest,
+        };
+        for component in rest.split(',') {
+            let mut component = component.split_whitespace();
+            let Some(name) = component.next() else {
+                continue;
+            };
+            let Some(&id) = self.component_names.get(name) else {
+                if !self.autoreg && self.type_registry.get_with_short_type_path(name).is_none() {
+                    return Err(format!("Component {} does not exist", name));
+                }
+                continue;
+            };
+            self.check_frozen([id])?;
+            self.validate_values(id, component)?;
+        }
+        Ok(())
+    }
+
+    fn validate_set(&self, rest: &str) -> Result<(), String> {
+        let mut args = rest.split_whitespace();
+        let entity = args.next().unwrap_or_default();
+        let Some(entity) = self.parse_entity(entity) else {
+            return Err(format!("Entity {} does not exist", entity));
+        };
+        let name = args.next().unwrap_or_default();
+        let Some(&id) = self.component_names.get(name) else {
+            return Err(format!("Component {} does not exist", name));
+        };
+        self.check_frozen([id])?;
+        if !self.world.entity(entity).contains_id(id) {
+            return Err(format!(
+                "Entity {} does not have {}",
+                self.radix.entity(entity),
+                name
+            ));
+        }
+        self.validate_values(id, args)
+    }
+
+    /// Checks that every value parses and fits within the component's elements.
+    fn validate_values<'a>(
+        &self,
+        id: ComponentId,
+        values: impl Iterator<Item = &'a str>,
+    ) -> Result<(), String> {
+        let len = self.component_info[&id].layout().size() / std::mem::size_of::<u64>();
+        let meta = &self.component_meta[&id];
+        for (i, value) in values.enumerate() {
+            if i >= len {
+                return Err(format!("{} only has {} elements", meta.name, len));
+            }
+            if self.parse_value(meta.kind, value).is_none() {
+                return Err(format!("Invalid value {} for {}", value, meta.name));
+            }
+        }
+        Ok(())
+    }
+
+    fn recall(&self, rest: &str, out: &mut impl Write) -> io::Result<()> {
+        if self.history_len == 0 {
+            return writeln!(
+                out,
+                "Query history is disabled, run with --history N to keep results"
+            );
+        }
+        let k = if rest.is_empty() {
+            Ok(1)
         } else {
-            builder.or(|b| {
-                sub_terms
-                    .iter()
-                    .for_each(|term| parse_term(term, b, components));
+            rest.trim().parse::<usize>()
+        };
+        match k.ok().and_then(|k| self.history.get(k.checked_sub(1)?)) {
+            Some(result) => write!(out, "{}", result),
+            None => writeln!(
+                out,
+                "Enter a number between 1 and {} to recall a query result",
+                self.history.len()
+            ),
+        }
+    }
+
+    /// Returns the documentation string attached to a component when it was created.
+    fn doc(&self, name: &str) -> Option<&str> {
+        let id = self.component_names.get(name)?;
+        self.component_meta[id].doc.as_deref()
+    }
+
+    fn create_components(&mut self, rest: &str, out: &mut impl Write) -> io::Result<()> {
+        for component in split_terms(rest) {
+            let (component, doc) = match component.split_once('"') {
+                Some((component, doc)) => (component, Some(doc.trim_end().trim_end_matches('"'))),
+                None => (component, None),
+            };
+            let mut component = component.split_whitespace();
+            let Some(name) = component.next() else {
+                continue;
+            };
+            let mut meta = ComponentMeta {
+                name: name.to_string(),
+                doc: doc.map(str::to_string),
+                ..Default::default()
+            };
+            let mut size = 0;
+            for option in component {
+                match option {
+                    "be" => meta.big_endian = true,
+                    "entity" => meta.kind = ElementKind::Entity,
+                    "f32" => meta.kind = ElementKind::F32,
+                    option => match option.parse::<usize>() {
+                        Ok(option) => size = option,
+                        Err(_) => writeln!(out, "Unknown option {} for {}", option, name)?,
+                    },
+                }
+            }
+            let id = self.register_component(size, meta);
+            writeln!(
+                out,
+                "Component {} created with id: {}",
+                name,
+                self.radix.id(id)
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Creates a component of `size` u64 elements, named after its metadata.
+    fn register_component(&mut self, size: usize, mut meta: ComponentMeta) -> ComponentId {
+        meta.len = size;
+        // SAFETY: [u64] is Send + Sync
+        let id = self.world.init_component_with_descriptor(unsafe {
+            ComponentDescriptor::new_with_layout(
+                meta.name.clone(),
+                StorageType::Table,
+                Layout::array::<u64>(size).unwrap(),
+                None,
+            )
+        });
+        let info = self.world.components().get_info(id).unwrap();
+        self.component_names.insert(meta.name.clone(), id);
+        self.component_info.insert(id, info.clone());
+        self.component_meta.insert(id, meta);
+        id
+    }
+
+    /// Spawns entities as described by a `spawn` command, returning what was spawned.
+    fn spawn_entity(&mut self, rest: &str, out: &mut impl Write) -> io::Result<Vec<SpawnOutcome>> {
+        let (count, rest) = match rest.split_once(|c: char| c.is_whitespace()) {
+            Some((count, components)) if count.ends_with('x') => {
+                match count[..count.len() - 1].parse::<usize>() {
+                    Ok(count) => (count, components),
+                    Err(_) => {
+                        writeln!(out, "Invalid entity count: {}", count)?;
+                        return Ok(Vec::new());
+                    }
+                }
+            }
+            _ => (1, rest),
+        };
+
+        let mut to_insert_ids = Vec::new();
+        let mut to_insert_values = Vec::new();
+        let mut to_insert_reflected = Vec::new();
+        for component in rest.split(',') {
+            let mut component = component.split_whitespace();
+            let Some(name) = component.next() else {
+                continue;
+            };
+            let id = match self.component_names.get(name).copied() {
+                Some(id) => id,
+                None => {
+                    let reflected = self.type_registry.get_with_short_type_path(name).and_then(
+                        |registration| {
+                            let component = registration.data::<ReflectComponent>()?.clone();
+                            Some((component, registration.data::<ReflectDefault>()?.default()))
+                        },
+                    );
+                    if let Some(reflected) = reflected {
+                        to_insert_reflected.push(reflected);
+                        continue;
+                    }
+                    if !self.autoreg {
+                        writeln!(out, "Component {} does not exist", name)?;
+                        continue;
+                    }
+                    let size = component.clone().count().max(1);
+                    let meta = ComponentMeta {
+                        name: name.to_string(),
+                        ..Default::default()
+                    };
+                    let id = self.register_component(size, meta);
+                    writeln!(out, "Component {} registered with {} elements", name, size)?;
+                    id
+                }
+            };
+            if let Err(err) = self.check_frozen([id]) {
+                writeln!(out, "{}", err)?;
+                return Ok(Vec::new());
+            }
+            let info = self.world.components().get_info(id).unwrap();
+            let len = info.layout().size() / std::mem::size_of::<u64>();
+            let meta = &self.component_meta[&id];
+            let mut values = vec![meta.encode(meta.default_value()); len];
+            values
+                .iter_mut()
+                .zip(component.filter_map(|value| self.parse_value(meta.kind, value)))
+                .for_each(|(slot, value)| *slot = meta.encode(value));
+
+            to_insert_ids.push(id);
+            to_insert_values.push(values);
+        }
+
+        // All components are interpreted as [u64] and have no drop function, so every entity
+        // can be initialized by copying out of the same buffers
+        let to_insert_ptrs: Vec<_> = to_insert_values
+            .iter_mut()
+            .map(|values| NonNull::new(values.as_mut_ptr()).unwrap().cast::<u8>())
+            .collect();
+        let batch = (0..count).map(|_| {
+            to_insert_ptrs.iter().map(|&ptr| {
+                // SAFETY: The buffers outlive the spawn and are only read from
+                unsafe { OwningPtr::new(ptr) }
+            })
+        });
+        // SAFETY:
+        // - The component ids have been taken from the same world
+        // - The length of each buffer is taken directly from the component descriptor
+        let spawned = match unsafe { self.world.spawn_batch_by_ids(&to_insert_ids, batch) } {
+            Ok(spawned) => spawned,
+            Err(err) => {
+                writeln!(out, "{}", err)?;
+                return Ok(Vec::new());
+            }
+        };
+        let mut archetype = None;
+        for &entity in &spawned {
+            self.record_spawn(entity);
+            // Reflected components can only be inserted one entity at a time
+            let mut entity = self.world.entity_mut(entity);
+            for (component, value) in &to_insert_reflected {
+                component.insert(&mut entity, value.as_ref());
+            }
+            archetype = Some(entity.archetype().id());
+        }
+        let archetype = archetype.map(|archetype| {
+            self.record_transition(ArchetypeId::EMPTY, archetype);
+            archetype.index()
+        });
+
+        match (spawned.as_slice(), archetype) {
+            ([entity], Some(archetype)) => writeln!(
+                out,
+                "Entity spawned with id: {} in archetype {}",
+                self.radix.entity(*entity),
+                archetype
+            )?,
+            ([first, .., last], Some(archetype)) => writeln!(
+                out,
+                "{} entities spawned with ids: {}..{} in archetype {}",
+                spawned.len(),
+                self.radix.entity(*first),
+                self.radix.entity(*last),
+                archetype
+            )?,
+            _ => writeln!(out, "No entities spawned")?,
+        }
+
+        let components: Vec<_> = to_insert_ids
+            .iter()
+            .zip(&to_insert_values)
+            .map(|(id, values)| {
+                let meta = &self.component_meta[id];
+                (*id, values.iter().map(|&data| meta.decode(data)).collect())
+            })
+            .collect();
+        Ok(spawned
+            .into_iter()
+            .map(|entity| SpawnOutcome {
+                entity,
+                components: components.clone(),
+            })
+            .collect())
+    }
+
+    fn query(&mut self, rest: &str, out: &mut impl Write) -> io::Result<()> {
+        // A query can't require the absence of every component, so look through all entities
+        if rest.trim() == "empty" {
+            for entity in self.world.iter_entities() {
+                if entity.archetype().components().next().is_none() {
+                    writeln!(out, "{}", self.radix.entity(entity.id()))?;
+                }
+            }
+            return Ok(());
+        }
+        let (rest, views) = self.parse_views(rest);
+        let (rest, comparisons) = match self.parse_comparisons(&rest) {
+            Ok(parsed) => parsed,
+            Err(err) => return writeln!(out, "{}", err),
+        };
+        let (rest, since, since_last_run) = match self.parse_since(&rest) {
+            Ok(parsed) => parsed,
+            Err(err) => return writeln!(out, "{}", err),
+        };
+        let (rest, order) = match parse_order(&rest) {
+            Ok(parsed) => parsed,
+            Err(err) => return writeln!(out, "{}", err),
+        };
+        let (rest, distinct) = match self.parse_distinct(&rest) {
+            Ok(parsed) => parsed,
+            Err(err) => return writeln!(out, "{}", err),
+        };
+        let (rest, write_elements) = match self.parse_write_elements(&rest) {
+            Ok(parsed) => parsed,
+            Err(err) => return writeln!(out, "{}", err),
+        };
+        let mut builder = QueryBuilder::<FilteredEntityMut>::new(&mut self.world);
+        if let Err(err) = builder.parse(&rest, &self.component_names) {
+            return writeln!(out, "{}", err);
+        }
+        // Change filters read their component, which is only printed if the query accesses it
+        let printed = builder.access().access().clone();
+        since.iter().for_each(|&id| {
+            builder.changed_id(id);
+        });
+        let mut query = builder.build();
+        // The change filters report changes made after the tick the query is run with
+        let last_run = since_last_run.unwrap_or_else(|| self.world.last_change_tick());
+        let writes = self.component_meta.keys().copied();
+        let writes = writes.filter(|&id| query.component_access().access().has_write(id));
+        if let Err(err) = self.check_frozen(writes) {
+            return writeln!(out, "{}", err);
+        }
+
+        // Comparisons and order terms are only checked for the entities the query matches
+        let mut excluded = HashSet::new();
+        if !comparisons.is_empty() || order.is_some() {
+            let matched: Vec<_> = self.world.last_change_tick_scope(last_run, |world| {
+                query.iter_mut(world).map(|entity| entity.id()).collect()
+            });
+            excluded.extend(matched.into_iter().filter(|&entity| {
+                let equal = comparisons.iter().all(|&((a, i), (b, j))| {
+                    match (self.read_values(entity, a), self.read_values(entity, b)) {
+                        (Some(a), Some(b)) => a[i] == b[j],
+                        _ => false,
+                    }
+                });
+                let ordered = order.as_ref().map_or(true, |order| {
+                    self.spawn_order
+                        .get(&entity)
+                        .is_some_and(|i| order.contains(i))
+                });
+                !(equal && ordered)
+            }));
+        }
+
+        // Only the first entity with each distinct value is printed, and so incremented
+        if !distinct.is_empty() {
+            let matched: Vec<_> = self.world.last_change_tick_scope(last_run, |world| {
+                query
+                    .iter_mut(world)
+                    .map(|entity| entity.id())
+                    .filter(|entity| !excluded.contains(entity))
+                    .collect()
             });
+            let mut seen = HashSet::new();
+            for entity in matched {
+                let values: Vec<u64> = distinct
+                    .iter()
+                    .flat_map(|&id| self.read_values(entity, id).unwrap_or_default())
+                    .collect();
+                if !seen.insert(values) {
+                    excluded.insert(entity);
+                }
+            }
         }
-    });
+
+        let (elem_limit, radix) = (self.elem_limit, self.radix);
+        self.world.last_change_tick_scope(last_run, |world| {
+            for mut filtered_entity in query.iter_mut(world) {
+                if excluded.contains(&filtered_entity.id()) {
+                    continue;
+                }
+                // Print components in registration order regardless of how the access stores them.
+                let mut ids: Vec<_> = filtered_entity
+                    .components()
+                    .filter(|&id| printed.has_read(id))
+                    .collect();
+                ids.sort_by_key(|id| id.index());
+                let terms = ids
+                    .into_iter()
+                    .map(|id| {
+                        let info = self.component_info.get(&id).unwrap();
+                        let meta = &self.component_meta[&id];
+                        let len = info.layout().size() / std::mem::size_of::<u64>();
+
+                        let stats = self.access_stats.entry(id).or_default();
+                        let data: &[u64] = if filtered_entity.access().has_write(id) {
+                            stats.writes += 1;
+                            let component = filtered_entity.get_mut_by_id(id).unwrap();
+                            // SAFETY:
+                            // - All components are created with layout [u64]
+                            // - len is calculated from the component descriptor
+                            let data = unsafe {
+                                std::slice::from_raw_parts_mut(
+                                    component.into_inner().as_ptr().cast::<u64>(),
+                                    len,
+                                )
+                            };
+                            match write_elements.get(&id) {
+                                Some(elements) => {
+                                    elements.iter().for_each(|&i| meta.increment(&mut data[i]));
+                                }
+                                None => data.iter_mut().for_each(|data| meta.increment(data)),
+                            }
+                            data
+                        } else {
+                            stats.reads += 1;
+                            let ptr = filtered_entity.get_by_id(id).unwrap();
+                            // SAFETY:
+                            // - All components are created with layout [u64]
+                            // - len is calculated from the component descriptor
+                            unsafe { std::slice::from_raw_parts(ptr.as_ptr().cast::<u64>(), len) }
+                        };
+
+                        match views.get(&id) {
+                            Some((name, elements)) => {
+                                let data: Vec<_> = elements.iter().map(|&i| data[i]).collect();
+                                format!(
+                                    "{}: {}",
+                                    name,
+                                    meta.format_limited(&data, elem_limit, radix)
+                                )
+                            }
+                            None => format!(
+                                "{}: {}",
+                                meta.name,
+                                meta.format_limited(data, elem_limit, radix)
+                            ),
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                writeln!(out, "{}: {}", radix.entity(filtered_entity.id()), terms)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn get(&self, rest: &str, out: &mut impl Write) -> io::Result<()> {
+        let Some((entity, name)) = rest.split_once(char::is_whitespace) else {
+            return writeln!(out, "{}", GET_PROMPT);
+        };
+        let Some(entity) = self.parse_entity(entity) else {
+            return writeln!(out, "Entity {} does not exist", entity);
+        };
+        let Some(&id) = self.component_names.get(name.trim()) else {
+            return writeln!(out, "Component {} does not exist", name.trim());
+        };
+        if !self.world.entity(entity).contains_id(id) {
+            let entity = self.radix.entity(entity);
+            return writeln!(out, "Entity {} does not have {}", entity, name.trim());
+        }
+        self.print_component(entity, id, out)
+    }
+
+    fn set(&mut self, rest: &str, out: &mut impl Write) -> io::Result<()> {
+        let mut args = rest.split_whitespace();
+        let Some(entity) = args.next().and_then(|entity| self.parse_entity(entity)) else {
+            return writeln!(out, "Entity {} does not exist", rest);
+        };
+        let Some(&id) = args.next().and_then(|name| self.component_names.get(name)) else {
+            return writeln!(out, "Component does not exist");
+        };
+        if let Err(err) = self.check_frozen([id]) {
+            return writeln!(out, "{}", err);
+        }
+        let kind = self.component_meta[&id].kind;
+        let values: Vec<_> = args
+            .filter_map(|value| self.parse_value(kind, value))
+            .collect();
+
+        if !self.write_values(entity, id, &values) {
+            let name = &self.component_meta[&id].name;
+            return writeln!(
+                out,
+                "Entity {} does not have {}",
+                self.radix.entity(entity),
+                name
+            );
+        }
+        self.print_component(entity, id, out)
+    }
+
+    /// Sets or clears a single bit of the first element of an entity's component.
+    fn set_bit(&mut self, rest: &str, set: bool, out: &mut impl Write) -> io::Result<()> {
+        let args: Vec<_> = rest.split_whitespace().collect();
+        let [entity, name, bit] = args[..] else {
+            return writeln!(out, "{}", BIT_PROMPT);
+        };
+        let Some(entity) = self.parse_entity(entity) else {
+            return writeln!(out, "Entity {} does not exist", entity);
+        };
+        let Some(&id) = self.component_names.get(name) else {
+            return writeln!(out, "Component {} does not exist", name);
+        };
+        if self.component_meta[&id].kind != ElementKind::U64 {
+            return writeln!(out, "Component {} does not hold integers", name);
+        }
+        if let Err(err) = self.check_frozen([id]) {
+            return writeln!(out, "{}", err);
+        }
+        let Some(bit) = bit.parse::<u32>().ok().filter(|&bit| bit < u64::BITS) else {
+            return writeln!(out, "Invalid bit: {}", bit);
+        };
+
+        let Some(value) = self
+            .read_values(entity, id)
+            .and_then(|values| values.first().copied())
+        else {
+            return writeln!(
+                out,
+                "Entity {} does not have an element of {}",
+                self.radix.entity(entity),
+                name
+            );
+        };
+        let value = if set {
+            value | (1 << bit)
+        } else {
+            value & !(1 << bit)
+        };
+        self.write_values(entity, id, &[value]);
+        self.print_component(entity, id, out)
+    }
+
+    /// Writes the values of an entity's component, returning `false` if the entity does not
+    /// have the component. Elements without a value are left unchanged.
+    fn write_values(&mut self, entity: Entity, id: ComponentId, values: &[u64]) -> bool {
+        let len = self.component_info[&id].layout().size() / std::mem::size_of::<u64>();
+        let meta = &self.component_meta[&id];
+        let Some(mut entity_mut) = self.world.get_entity_mut(entity) else {
+            return false;
+        };
+        let Some(mut component) = entity_mut.get_mut_by_id(id) else {
+            return false;
+        };
+        // SAFETY:
+        // - All components are created with layout [u64]
+        // - len is calculated from the component descriptor
+        let data = unsafe {
+            std::slice::from_raw_parts_mut(component.as_mut().as_ptr().cast::<u64>(), len)
+        };
+        data.iter_mut()
+            .zip(values)
+            .for_each(|(data, &value)| *data = meta.encode(value));
+        true
+    }
+
+    /// Inserts a component with the given values into an entity, replacing any previous values.
+    /// Elements without a value are given the default value.
+    fn insert_values(&mut self, entity: Entity, id: ComponentId, values: &[u64]) {
+        let len = self.component_info[&id].layout().size() / std::mem::size_of::<u64>();
+        let meta = &self.component_meta[&id];
+        let mut data = vec![meta.encode(meta.default_value()); len];
+        data.iter_mut()
+            .zip(values)
+            .for_each(|(data, &value)| *data = meta.encode(value));
+        let mut entity = self.world.entity_mut(entity);
+        let from = entity.archetype().id();
+        // SAFETY:
+        // - The component id has been taken from the same world
+        // - The data has the length given by the component descriptor
+        unsafe {
+            entity.insert_by_id(
+                id,
+                OwningPtr::new(NonNull::new_unchecked(data.as_mut_ptr().cast())),
+            );
+        }
+        let to = entity.archetype().id();
+        self.record_transition(from, to);
+    }
+
+    /// Respawns an entity under the same id with all of its components except `removed`.
+    ///
+    /// Components created at runtime can't be removed by type, so the entity is rebuilt from the
+    /// values of its remaining components instead. All of its components must be session
+    /// components.
+    fn rebuild_entity(&mut self, entity: Entity, removed: &[ComponentId]) {
+        let from = self.world.entity(entity).archetype().id();
+        let kept: Vec<_> = self
+            .world
+            .entity(entity)
+            .archetype()
+            .components()
+            .filter(|id| !removed.contains(id))
+            .map(|id| (id, self.read_values(entity, id).unwrap()))
+            .collect();
+        self.world.despawn(entity);
+        // A despawned entity can be spawned again with the same generation.
+        self.world.get_or_spawn(entity).unwrap();
+        // Only the overall move is an observed transition, not the steps of the rebuild.
+        let transitions = self.transitions.len();
+        for (id, values) in kept {
+            self.insert_values(entity, id, &values);
+        }
+        self.transitions.truncate(transitions);
+        let to = self.world.entity(entity).archetype().id();
+        self.record_transition(from, to);
+    }
+
+    /// Spawns an empty entity, remembering the order it was spawned in.
+    fn spawn_empty(&mut self) -> Entity {
+        let entity = self.world.spawn_empty().id();
+        self.record_spawn(entity);
+        entity
+    }
+
+    /// Remembers the order a newly spawned entity was spawned in.
+    fn record_spawn(&mut self, entity: Entity) {
+        self.spawn_order.insert(entity, self.spawn_count);
+        self.spawn_count += 1;
+    }
+
+    /// Remembers an entity moving between archetypes.
+    fn record_transition(&mut self, from: ArchetypeId, to: ArchetypeId) {
+        if from != to && !self.transitions.contains(&(from, to)) {
+            self.transitions.push((from, to));
+        }
+    }
+
+    /// Prints each archetype transition with the components added and removed by it.
+    fn show_edges(&self, out: &mut impl Write) -> io::Result<()> {
+        let archetypes = self.world.archetypes();
+        for &(from, to) in &self.transitions {
+            let from_components: HashSet<_> = archetypes[from].components().collect();
+            let to_components: HashSet<_> = archetypes[to].components().collect();
+            let mut changes: Vec<_> = to_components
+                .difference(&from_components)
+                .map(|&id| (id, '+'))
+                .chain(
+                    from_components
+                        .difference(&to_components)
+                        .map(|&id| (id, '-')),
+                )
+                .collect();
+            changes.sort_by_key(|(id, _)| id.index());
+            let changes: Vec<_> = changes
+                .into_iter()
+                .map(|(id, change)| format!("{}{}", change, self.component_name(id)))
+                .collect();
+            writeln!(
+                out,
+                "Archetype {} -> {}: {}",
+                from.index(),
+                to.index(),
+                changes.join(", ")
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Reads the decoded values of an entity's component.
+    fn read_values(&self, entity: Entity, id: ComponentId) -> Option<Vec<u64>> {
+        let len = self.component_info[&id].layout().size() / std::mem::size_of::<u64>();
+        let meta = &self.component_meta[&id];
+        let ptr = self.world.get_entity(entity)?.get_by_id(id)?;
+        // SAFETY:
+        // - All components are created with layout [u64]
+        // - len is calculated from the component descriptor
+        let data = unsafe { std::slice::from_raw_parts(ptr.as_ptr().cast::<u64>(), len) };
+        Some(data.iter().map(|&data| meta.decode(data)).collect())
+    }
+
+    fn print_component(
+        &self,
+        entity: Entity,
+        id: ComponentId,
+        out: &mut impl Write,
+    ) -> io::Result<()> {
+        let meta = &self.component_meta[&id];
+        let Some(values) = self.read_values(entity, id) else {
+            return Ok(());
+        };
+        let data: Vec<_> = values.into_iter().map(|value| meta.encode(value)).collect();
+        writeln!(
+            out,
+            "{}: {}: {}",
+            self.radix.entity(entity),
+            meta.name,
+            meta.format(&data, self.radix)
+        )
+    }
+
+    fn lerp(&mut self, rest: &str, out: &mut impl Write) -> io::Result<()> {
+        let args: Vec<_> = rest.split_whitespace().collect();
+        let (name, from, to, t, target) = match args.as_slice() {
+            [name, from, to, t] => (name, from, to, t, from),
+            [name, from, to, t, target] => (name, from, to, t, target),
+            _ => return writeln!(out, "{}", LERP_PROMPT),
+        };
+        let Some(&id) = self.component_names.get(*name) else {
+            return writeln!(out, "Component {} does not exist", name);
+        };
+        if self.component_meta[&id].kind != ElementKind::F32 {
+            return writeln!(out, "Component {} is not float typed", name);
+        }
+        if let Err(err) = self.check_frozen([id]) {
+            return writeln!(out, "{}", err);
+        }
+        let Ok(t) = t.parse::<f32>() else {
+            return writeln!(out, "Invalid interpolation factor: {}", t);
+        };
+        let mut entities = Vec::new();
+        for entity in [from, to, target] {
+            let Some(entity) = self.parse_entity(entity) else {
+                return writeln!(out, "Entity {} does not exist", entity);
+            };
+            entities.push(entity);
+        }
+        let [from, to, target] = entities[..] else {
+            unreachable!()
+        };
+
+        let (Some(from_values), Some(to_values)) =
+            (self.read_values(from, id), self.read_values(to, id))
+        else {
+            return writeln!(out, "Both entities must have {}", name);
+        };
+        let values: Vec<_> = from_values
+            .into_iter()
+            .zip(to_values)
+            .map(|(from, to)| {
+                let from = f32::from_bits(from as u32);
+                let to = f32::from_bits(to as u32);
+                (from * (1.0 - t) + to * t).to_bits() as u64
+            })
+            .collect();
+
+        if !self.write_values(target, id, &values) {
+            let target = self.radix.entity(target);
+            return writeln!(out, "Entity {} does not have {}", target, name);
+        }
+        self.print_component(target, id, out)
+    }
+
+    /// Parses the value of a single element.
+    fn parse_value(&self, kind: ElementKind, str: &str) -> Option<u64> {
+        match kind {
+            ElementKind::U64 => str.parse::<u64>().ok(),
+            ElementKind::F32 => str.parse::<f32>().ok().map(|value| value.to_bits() as u64),
+            ElementKind::Entity => self.parse_entity(str).map(Entity::to_bits),
+        }
+    }
+
+    /// Returns every entity referenced by an entity typed component.
+    fn referenced_entities(&mut self) -> HashSet<Entity> {
+        let mut referenced = HashSet::new();
+        for (&id, meta) in &self.component_meta {
+            if meta.kind != ElementKind::Entity {
+                continue;
+            }
+            let len = self.component_info[&id].layout().size() / std::mem::size_of::<u64>();
+            let mut query = QueryBuilder::<FilteredEntityRef>::new(&mut self.world)
+                .ref_id(id)
+                .build();
+            for entity in query.iter(&self.world) {
+                let ptr = entity.get_by_id(id).unwrap();
+                // SAFETY:
+                // - All components are created with layout [u64]
+                // - len is calculated from the component descriptor
+                let data = unsafe { std::slice::from_raw_parts(ptr.as_ptr().cast::<u64>(), len) };
+                referenced.extend(meta.entities(data));
+            }
+        }
+        referenced
+    }
+
+    fn freeze(&mut self, rest: &str, freeze: bool, out: &mut impl Write) -> io::Result<()> {
+        for name in rest.split(',').map(str::trim) {
+            let Some(&id) = self.component_names.get(name) else {
+                writeln!(out, "Component {} does not exist", name)?;
+                continue;
+            };
+            if freeze {
+                self.frozen.insert(id);
+                writeln!(out, "Froze {}", name)?;
+            } else {
+                self.frozen.remove(&id);
+                writeln!(out, "Unfroze {}", name)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns an error naming the first of the components which is frozen.
+    fn check_frozen(&self, ids: impl IntoIterator<Item = ComponentId>) -> Result<(), String> {
+        match ids.into_iter().find(|id| self.frozen.contains(id)) {
+            Some(id) => Err(format!("Component {} is frozen", self.component_name(id))),
+            None => Ok(()),
+        }
+    }
+
+    /// Evaluates an expression for every entity matched by a query, writing it to an element.
+    fn map(&mut self, rest: &str, out: &mut impl Write) -> io::Result<()> {
+        let (assignment, query) = rest.split_once(" where ").unwrap_or((rest, ""));
+        let Some((target, expr)) = assignment.split_once('=') else {
+            return writeln!(out, "{}", MAP_PROMPT);
+        };
+        let parsed = self.parse_element(target).and_then(|element| {
+            if self.component_meta[&element.0].kind == ElementKind::Entity {
+                return Err(format!(
+                    "Component elements {} reference entities",
+                    target.trim()
+                ));
+            }
+            self.check_frozen([element.0])?;
+            Ok((element, ExprParser::new(self, expr).parse()?))
+        });
+        let ((id, element), expr) = match parsed {
+            Ok(parsed) => parsed,
+            Err(err) => return writeln!(out, "{}", err),
+        };
+
+        let mut reads = Vec::new();
+        expr.elements(&mut reads);
+        let mut builder = QueryBuilder::<Entity>::new(&mut self.world);
+        if let Err(err) = builder.parse(query, &self.component_names) {
+            return writeln!(out, "{}", err);
+        }
+        builder.with_id(id);
+        reads.iter().for_each(|&(id, _)| {
+            builder.with_id(id);
+        });
+        let entities: Vec<_> = builder.build().iter(&self.world).collect();
+
+        for &entity in &entities {
+            let value = expr.eval(&|(id, i)| {
+                let value = self.read_values(entity, id).unwrap()[i];
+                self.component_meta[&id].value_to_number(value)
+            });
+            let mut values = self.read_values(entity, id).unwrap();
+            values[element] = self.component_meta[&id].number_to_value(value);
+            self.write_values(entity, id, &values);
+        }
+        writeln!(out, "Mapped {} entities", entities.len())
+    }
+
+    /// Prints the fields of a reflected component as an indented tree.
+    fn tree(&self, rest: &str, out: &mut impl Write) -> io::Result<()> {
+        let Some((entity, name)) = rest.split_once(char::is_whitespace) else {
+            return writeln!(out, "{}", TREE_PROMPT);
+        };
+        let Some(entity) = self.parse_entity(entity) else {
+            return writeln!(out, "Entity {} does not exist", entity);
+        };
+        let registration = self.type_registry.get_with_short_type_path(name.trim());
+        let Some((registration, from_ptr)) = registration
+            .and_then(|registration| Some((registration, registration.data::<ReflectFromPtr>()?)))
+        else {
+            return writeln!(out, "Component {} is not reflected", name.trim());
+        };
+        let ptr = self
+            .world
+            .components()
+            .get_id(registration.type_id())
+            .and_then(|id| self.world.entity(entity).get_by_id(id));
+        let Some(ptr) = ptr else {
+            let entity = self.radix.entity(entity);
+            return writeln!(out, "Entity {} does not have {}", entity, name.trim());
+        };
+
+        // SAFETY: The pointer was fetched for the component of the registration's type
+        let value = unsafe { from_ptr.as_reflect(ptr) };
+        writeln!(out, "{}", self.radix.entity(entity))?;
+        write_tree(name.trim(), value, 1, out)
+    }
+
+    fn alias_view(&mut self, rest: &str, out: &mut impl Write) -> io::Result<()> {
+        let Some((name, backing)) = rest.split_once('=') else {
+            return writeln!(out, "{}", ALIAS_VIEW_PROMPT);
+        };
+        let name = name.trim();
+        if self.component_names.contains_key(name) {
+            return writeln!(out, "Component {} already exists", name);
+        }
+        let Some((component, elements)) = backing.trim().strip_suffix(']').and_then(|backing| {
+            let (component, elements) = backing.split_once('[')?;
+            let id = *self.component_names.get(component.trim())?;
+            Some((id, elements))
+        }) else {
+            return writeln!(
+                out,
+                "Expected a component with elements such as A[0,1]: {}",
+                backing.trim()
+            );
+        };
+        let len = self.component_info[&component].layout().size() / std::mem::size_of::<u64>();
+        let elements: Option<Vec<_>> = elements
+            .split(',')
+            .map(|element| element.trim().parse::<usize>().ok().filter(|&i| i < len))
+            .collect();
+        let Some(elements) = elements else {
+            return writeln!(
+                out,
+                "Invalid elements for {} with {} elements",
+                backing.trim(),
+                len
+            );
+        };
+
+        let backing = &self.component_meta[&component].name;
+        writeln!(out, "View {} created for {}{:?}", name, backing, elements)?;
+        self.views.insert(name.to_string(), (component, elements));
+        Ok(())
+    }
+
+    fn set_formatter(&mut self, rest: &str, out: &mut impl Write) -> io::Result<()> {
+        let args: Vec<_> = rest.split_whitespace().collect();
+        let [name, formatter] = args[..] else {
+            return writeln!(out, "{}", FORMAT_PROMPT);
+        };
+        let Some(id) = self.component_names.get(name) else {
+            return writeln!(out, "Component {} does not exist", name);
+        };
+        let formatter = match formatter {
+            "default" => None,
+            formatter => match Formatter::from_name(formatter) {
+                Some(formatter) => Some(formatter),
+                None => return writeln!(out, "Unknown formatter: {}", formatter),
+            },
+        };
+        self.component_meta.get_mut(id).unwrap().formatter = formatter;
+        writeln!(
+            out,
+            "{} is now printed with the {} formatter",
+            name, args[1]
+        )
+    }
+
+    /// Replaces views in a query with their backing components, returning the remaining query
+    /// and the view each read component should be printed as. Writes to a view only write its
+    /// elements.
+    fn parse_views(&self, query: &str) -> (String, HashMap<ComponentId, (String, Vec<usize>)>) {
+        let mut views = HashMap::new();
+        let terms: Vec<_> = split_terms(query)
+            .into_iter()
+            .map(|term| {
+                let sub_terms: Vec<_> = term
+                    .split("||")
+                    .map(|sub_term| {
+                        let (prefix, name) = term_name(sub_term);
+                        let Some((id, elements)) = self.views.get(name) else {
+                            return sub_term.to_string();
+                        };
+                        let backing = &self.component_meta[id].name;
+                        if prefix.contains('&') {
+                            views.insert(*id, (name.to_string(), elements.clone()));
+                        }
+                        if prefix.contains("mut") {
+                            let elements: Vec<_> = elements.iter().map(usize::to_string).collect();
+                            format!("{}{}[{}]", prefix, backing, elements.join(","))
+                        } else {
+                            format!("{}{}", prefix, backing)
+                        }
+                    })
+                    .collect();
+                sub_terms.join("||")
+            })
+            .collect();
+        (terms.join(","), views)
+    }
+
+    /// Clones an entity and every entity it transitively references through entity typed
+    /// components, pointing references between the cloned entities at the clones.
+    fn dup_graph(&mut self, rest: &str, out: &mut impl Write) -> io::Result<()> {
+        let Some(root) = self.parse_entity(rest) else {
+            return writeln!(out, "Entity {} does not exist", rest.trim());
+        };
+
+        let mut graph = vec![root];
+        let mut components = HashMap::new();
+        let mut i = 0;
+        while let Some(&entity) = graph.get(i) {
+            i += 1;
+            let ids: Vec<_> = self
+                .world
+                .entity(entity)
+                .archetype()
+                .components()
+                .filter(|id| self.component_meta.contains_key(id))
+                .collect();
+            let values: Vec<_> = ids
+                .into_iter()
+                .map(|id| (id, self.read_values(entity, id).unwrap()))
+                .collect();
+            for (id, values) in &values {
+                let meta = &self.component_meta[id];
+                let encoded: Vec<_> = values.iter().map(|&value| meta.encode(value)).collect();
+                for target in meta.entities(&encoded) {
+                    if self.world.entities().contains(target) && !graph.contains(&target) {
+                        graph.push(target);
+                    }
+                }
+            }
+            components.insert(entity, values);
+        }
+
+        let ids = components.values().flatten().map(|(id, _)| *id);
+        if let Err(err) = self.check_frozen(ids) {
+            return writeln!(out, "{}", err);
+        }
+
+        let clones: HashMap<_, _> = graph
+            .iter()
+            .map(|&entity| (entity, self.spawn_empty()))
+            .collect();
+        for &entity in &graph {
+            for (id, mut values) in components.remove(&entity).unwrap() {
+                if self.component_meta[&id].kind == ElementKind::Entity {
+                    for value in &mut values {
+                        let target = Entity::try_from_bits(*value).ok();
+                        if let Some(clone) = target.and_then(|target| clones.get(&target)) {
+                            *value = clone.to_bits();
+                        }
+                    }
+                }
+                self.insert_values(clones[&entity], id, &values);
+            }
+        }
+
+        let pairs: Vec<_> = graph
+            .iter()
+            .map(|&entity| {
+                let clone = clones[&entity];
+                format!(
+                    "{} -> {}",
+                    self.radix.entity(entity),
+                    self.radix.entity(clone)
+                )
+            })
+            .collect();
+        writeln!(out, "Cloned {} entities: {}", graph.len(), pairs.join(", "))
+    }
+
+    /// Prints every entity transitively reachable from an entity by following an entity typed
+    /// component, visiting each entity once so cycles terminate.
+    fn closure(&self, rest: &str, out: &mut impl Write) -> io::Result<()> {
+        let args: Vec<_> = rest.split_whitespace().collect();
+        let [root, name] = args[..] else {
+            return writeln!(out, "{}", CLOSURE_PROMPT);
+        };
+        let Some(root) = self.parse_entity(root) else {
+            return writeln!(out, "Entity {} does not exist", root);
+        };
+        let Some(&id) = self.component_names.get(name) else {
+            return writeln!(out, "Component {} does not exist", name);
+        };
+        let meta = &self.component_meta[&id];
+        if meta.kind != ElementKind::Entity {
+            return writeln!(out, "Component {} does not reference entities", name);
+        }
+
+        let mut reachable = Vec::new();
+        let mut next = Some(root);
+        let mut i = 0;
+        while let Some(entity) = next {
+            if let Some(values) = self.read_values(entity, id) {
+                let encoded: Vec<_> = values.iter().map(|&value| meta.encode(value)).collect();
+                for target in meta.entities(&encoded) {
+                    if self.world.entities().contains(target) && !reachable.contains(&target) {
+                        reachable.push(target);
+                    }
+                }
+            }
+            next = reachable.get(i).copied();
+            i += 1;
+        }
+
+        let reachable: Vec<_> = reachable
+            .iter()
+            .map(|&entity| self.radix.entity(entity))
+            .collect();
+        writeln!(
+            out,
+            "{} entities reachable from {} through {}: {}",
+            reachable.len(),
+            self.radix.entity(root),
+            name,
+            reachable.join(", ")
+        )
+    }
+
+    /// Swaps the names of two components, leaving their data on the same entities.
+    fn swap_registrations(&mut self, rest: &str, out: &mut impl Write) -> io::Result<()> {
+        let names: Vec<_> = rest.split_whitespace().collect();
+        let [a, b] = names[..] else {
+            return writeln!(out, "{}", SWAPREG_PROMPT);
+        };
+        let (Some(&a_id), Some(&b_id)) = (self.component_names.get(a), self.component_names.get(b))
+        else {
+            return writeln!(out, "Both components must exist: {} {}", a, b);
+        };
+        self.component_names.insert(a.to_string(), b_id);
+        self.component_names.insert(b.to_string(), a_id);
+        self.component_meta.get_mut(&a_id).unwrap().name = b.to_string();
+        self.component_meta.get_mut(&b_id).unwrap().name = a.to_string();
+        writeln!(
+            out,
+            "{} now refers to id {} and {} to id {}",
+            a,
+            self.radix.id(b_id),
+            b,
+            self.radix.id(a_id)
+        )
+    }
+
+    /// Creates a component concatenating the elements of two others and moves every entity's
+    /// data into it, removing the originals.
+    fn fuse(&mut self, rest: &str, out: &mut impl Write) -> io::Result<()> {
+        let Some((sources, target)) = rest.split_once("->") else {
+            return writeln!(out, "{}", FUSE_PROMPT);
+        };
+        let sources: Vec<_> = sources.split_whitespace().collect();
+        let target: Vec<_> = target.split_whitespace().collect();
+        let [a, b] = sources[..] else {
+            return writeln!(out, "{}", FUSE_PROMPT);
+        };
+        let (name, fill) = match target[..] {
+            [name] => (name, false),
+            [name, "fill"] => (name, true),
+            _ => return writeln!(out, "{}", FUSE_PROMPT),
+        };
+        let (Some(&a_id), Some(&b_id)) = (self.component_names.get(a), self.component_names.get(b))
+        else {
+            return writeln!(out, "Both components must exist: {} {}", a, b);
+        };
+        if a_id == b_id {
+            return writeln!(out, "Unable to fuse {} with itself", a);
+        }
+        if self.component_names.contains_key(name) {
+            return writeln!(out, "Component {} already exists", name);
+        }
+        if let Err(err) = self.check_frozen([a_id, b_id]) {
+            return writeln!(out, "{}", err);
+        }
+        let (a_meta, b_meta) = (&self.component_meta[&a_id], &self.component_meta[&b_id]);
+        if a_meta.kind != b_meta.kind || a_meta.big_endian != b_meta.big_endian {
+            return writeln!(out, "Components {} and {} have different options", a, b);
+        }
+
+        let meta = ComponentMeta {
+            name: name.to_string(),
+            kind: a_meta.kind,
+            big_endian: a_meta.big_endian,
+            ..Default::default()
+        };
+        let default_value = meta.default_value();
+
+        let entities: Vec<_> = self
+            .world
+            .iter_entities()
+            .filter(|entity| {
+                let (has_a, has_b) = (entity.contains_id(a_id), entity.contains_id(b_id));
+                (has_a && has_b) || (fill && (has_a || has_b))
+            })
+            .map(|entity| entity.id())
+            .collect();
+        // Entities are rebuilt without the originals, which needs all their values to be known.
+        for &entity in &entities {
+            let entity = self.world.entity(entity);
+            if let Some(id) = entity
+                .archetype()
+                .components()
+                .find(|id| !self.component_meta.contains_key(id))
+            {
+                return writeln!(
+                    out,
+                    "Unable to fuse entity {:?} with component {}",
+                    entity.id(),
+                    self.component_name(id)
+                );
+            }
+        }
+
+        let [a_len, b_len] = [a_id, b_id]
+            .map(|id| self.component_info[&id].layout().size() / std::mem::size_of::<u64>());
+        let id = self.register_component(a_len + b_len, meta);
+        for &entity in &entities {
+            let a_values = self.read_values(entity, a_id);
+            let b_values = self.read_values(entity, b_id);
+            let values: Vec<_> = a_values
+                .unwrap_or_else(|| vec![default_value; a_len])
+                .into_iter()
+                .chain(b_values.unwrap_or_else(|| vec![default_value; b_len]))
+                .collect();
+            self.rebuild_entity(entity, &[a_id, b_id]);
+            self.insert_values(entity, id, &values);
+        }
+        writeln!(
+            out,
+            "Fused {} and {} into {} for {} entities",
+            a,
+            b,
+            name,
+            entities.len()
+        )
+    }
+
+    /// Prints the entities of an archetype as rows and its components as columns.
+    fn table(&self, rest: &str, out: &mut impl Write) -> io::Result<()> {
+        let archetype = rest
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| self.world.archetypes().get(ArchetypeId::new(index)));
+        let Some(archetype) = archetype else {
+            return writeln!(out, "Archetype {} does not exist", rest.trim());
+        };
+
+        let mut ids: Vec<_> = archetype.components().collect();
+        ids.sort_by_key(|id| id.index());
+        let mut rows = vec![std::iter::once("entity".to_string())
+            .chain(ids.iter().map(|id| self.component_name(*id)))
+            .collect::<Vec<_>>()];
+        for entity in archetype.entities() {
+            let entity = entity.id();
+            let cells = ids.iter().map(|&id| {
+                let Some(meta) = self.component_meta.get(&id) else {
+                    return "?".to_string();
+                };
+                let Some(values) = self.read_values(entity, id) else {
+                    return "?".to_string();
+                };
+                let data: Vec<_> = values.into_iter().map(|value| meta.encode(value)).collect();
+                meta.format(&data, self.radix)
+            });
+            rows.push(
+                std::iter::once(self.radix.entity(entity))
+                    .chain(cells)
+                    .collect(),
+            );
+        }
+
+        let widths: Vec<_> = (0..=ids.len())
+            .map(|column| rows.iter().map(|row| row[column].len()).max().unwrap())
+            .collect();
+        for row in rows {
+            let row = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, &width)| format!("{:<width$}", cell))
+                .collect::<Vec<_>>()
+                .join("  ");
+            writeln!(out, "{}", row.trim_end())?;
+        }
+        Ok(())
+    }
+
+    /// Prints the minimum, maximum and mean of a component's first element over the entities of
+    /// an archetype, reading the archetype's table column directly.
+    fn archetype_stats(&self, rest: &str, out: &mut impl Write) -> io::Result<()> {
+        let Some((index, name)) = rest.split_once(char::is_whitespace) else {
+            return writeln!(out, "{}", ARCHSTATS_PROMPT);
+        };
+        let archetype = index
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| self.world.archetypes().get(ArchetypeId::new(index)));
+        let Some(archetype) = archetype else {
+            return writeln!(out, "Archetype {} does not exist", index);
+        };
+        let name = name.trim();
+        let Some(&id) = self.component_names.get(name) else {
+            return writeln!(out, "Component {} does not exist", name);
+        };
+        let column = self.world.storages().tables[archetype.table_id()].get_column(id);
+        let Some(column) = column.filter(|_| archetype.contains(id)) else {
+            return writeln!(out, "Archetype {} does not have {}", index, name);
+        };
+        if self.component_info[&id].layout().size() == 0 {
+            return writeln!(out, "Component {} has no elements", name);
+        }
+
+        let meta = &self.component_meta[&id];
+        // Tables can be shared between archetypes, so only visit the rows of this archetype
+        let values: Vec<_> = archetype
+            .entities()
+            .iter()
+            .map(|entity| {
+                // SAFETY:
+                // - The row belongs to an entity of the archetype stored in this table
+                // - All components are created with layout [u64] and have at least one element
+                let data = unsafe { *column.get_data_unchecked(entity.table_row()).deref::<u64>() };
+                meta.value_to_number(meta.decode(data))
+            })
+            .collect();
+        if values.is_empty() {
+            return writeln!(out, "Archetype {} has no entities", index);
+        }
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        writeln!(
+            out,
+            "{}[0] over {} entities: min {}, max {}, mean {}",
+            name,
+            values.len(),
+            min,
+            max,
+            mean
+        )
+    }
+
+    /// Returns the name of a component, whether or not it was created by the session.
+    fn component_name(&self, id: ComponentId) -> String {
+        if let Some(meta) = self.component_meta.get(&id) {
+            return meta.name.clone();
+        }
+        self.world
+            .components()
+            .get_info(id)
+            .map_or_else(|| format!("{:?}", id), |info| info.name().to_string())
+    }
+
+    /// Checks that every stored component was created by the session with a matching layout
+    /// and that no relationship points at a despawned entity.
+    fn verify(&self, out: &mut impl Write) -> io::Result<()> {
+        let mut violations = Vec::new();
+        for info in self.world.components().iter() {
+            let Some(meta) = self.component_meta.get(&info.id()) else {
+                continue;
+            };
+            let expected = Layout::array::<u64>(meta.len).unwrap();
+            if info.layout() != expected {
+                violations.push(format!(
+                    "Component {} is stored with {} bytes but was created with {}",
+                    self.component_name(info.id()),
+                    info.layout().size(),
+                    expected.size()
+                ));
+            }
+        }
+
+        for entity in self.world.iter_entities() {
+            for id in entity.archetype().components() {
+                let reflected = self
+                    .world
+                    .components()
+                    .get_info(id)
+                    .and_then(|info| self.type_registry.get(info.type_id()?));
+                if reflected.is_some() {
+                    continue;
+                }
+                let (Some(info), Some(meta)) =
+                    (self.component_info.get(&id), self.component_meta.get(&id))
+                else {
+                    violations.push(format!(
+                        "Entity {} has unregistered component {:?}",
+                        self.radix.entity(entity.id()),
+                        id
+                    ));
+                    continue;
+                };
+                let len = info.layout().size() / std::mem::size_of::<u64>();
+                let ptr = entity.get_by_id(id).unwrap();
+                // SAFETY:
+                // - All components are created with layout [u64]
+                // - len is calculated from the component descriptor
+                let data = unsafe { std::slice::from_raw_parts(ptr.as_ptr().cast::<u64>(), len) };
+                for target in meta.entities(data) {
+                    if target != Entity::PLACEHOLDER && !self.world.entities().contains(target) {
+                        violations.push(format!(
+                            "Entity {} has {} referencing despawned entity {}",
+                            self.radix.entity(entity.id()),
+                            meta.name,
+                            self.radix.entity(target)
+                        ));
+                    }
+                }
+            }
+        }
+
+        if violations.is_empty() {
+            return writeln!(out, "World is consistent");
+        }
+        for violation in &violations {
+            writeln!(out, "{}", violation)?;
+        }
+        writeln!(out, "{} violations found", violations.len())
+    }
+
+    /// Writes the data of a component to a file: a header of the entity count and element width,
+    /// followed by each entity's bits and element values, all as little-endian `u64`s.
+    fn export(&mut self, rest: &str, out: &mut impl Write) -> io::Result<()> {
+        let Some((name, path)) = rest.split_once(char::is_whitespace) else {
+            return writeln!(out, "{}", EXPORT_PROMPT);
+        };
+        let Some(&id) = self.component_names.get(name) else {
+            return writeln!(out, "Component {} does not exist", name);
+        };
+        let len = self.component_info[&id].layout().size() / std::mem::size_of::<u64>();
+
+        let mut records = Vec::new();
+        let mut query = QueryBuilder::<FilteredEntityRef>::new(&mut self.world)
+            .ref_id(id)
+            .build();
+        for entity in query.iter(&self.world) {
+            let ptr = entity.get_by_id(id).unwrap();
+            // SAFETY:
+            // - All components are created with layout [u64]
+            // - len is calculated from the component descriptor
+            let data = unsafe { std::slice::from_raw_parts(ptr.as_ptr().cast::<u64>(), len) };
+            let meta = &self.component_meta[&id];
+            records.push(entity.id().to_bits());
+            records.extend(data.iter().map(|&data| meta.decode(data)));
+        }
+
+        let count = records.len() / (len + 1);
+        let mut bytes = Vec::with_capacity((records.len() + 2) * std::mem::size_of::<u64>());
+        bytes.extend_from_slice(&(count as u64).to_le_bytes());
+        bytes.extend_from_slice(&(len as u64).to_le_bytes());
+        bytes.extend(records.iter().flat_map(|data| data.to_le_bytes()));
+        if let Err(err) = std::fs::write(path.trim(), bytes) {
+            return writeln!(out, "Unable to write {}: {}", path.trim(), err);
+        }
+        writeln!(
+            out,
+            "Exported {} entities of {} to {}",
+            count,
+            name,
+            path.trim()
+        )
+    }
+
+    /// Reads a file written by `export`, updating the entities which still exist and spawning
+    /// new entities for the rest.
+    fn import(&mut self, rest: &str, out: &mut impl Write) -> io::Result<()> {
+        let Some((name, path)) = rest.split_once(char::is_whitespace) else {
+            return writeln!(out, "{}", IMPORT_PROMPT);
+        };
+        let Some(&id) = self.component_names.get(name) else {
+            return writeln!(out, "Component {} does not exist", name);
+        };
+        if let Err(err) = self.check_frozen([id]) {
+            return writeln!(out, "{}", err);
+        }
+        let bytes = match std::fs::read(path.trim()) {
+            Ok(bytes) => bytes,
+            Err(err) => return writeln!(out, "Unable to read {}: {}", path.trim(), err),
+        };
+        let len = self.component_info[&id].layout().size() / std::mem::size_of::<u64>();
+
+        let mut words = bytes
+            .chunks_exact(std::mem::size_of::<u64>())
+            .map(|chunk| chunk.try_into().unwrap());
+        let (Some(count), Some(width)) = (words.next(), words.next()) else {
+            return writeln!(out, "{} is missing its header", path.trim());
+        };
+        let (count, width) = (u64::from_le_bytes(count), u64::from_le_bytes(width));
+        if width != len as u64 {
+            return writeln!(
+                out,
+                "{} has {} elements per entity but {} has {}",
+                path.trim(),
+                width,
+                name,
+                len
+            );
+        }
+        let words: Vec<u64> = words.map(u64::from_le_bytes).collect();
+        let expected = width
+            .checked_add(1)
+            .and_then(|record| record.checked_mul(count));
+        if bytes.len() % std::mem::size_of::<u64>() != 0 || Some(words.len() as u64) != expected {
+            return writeln!(out, "{} does not contain {} entities", path.trim(), count);
+        }
+
+        let mut spawned = 0;
+        for record in words.chunks_exact(len + 1) {
+            let values = &record[1..];
+            let entity = Entity::try_from_bits(record[0])
+                .ok()
+                .filter(|&entity| self.world.entities().contains(entity));
+            match entity {
+                Some(entity) if self.write_values(entity, id, values) => {}
+                entity => {
+                    let entity = entity.unwrap_or_else(|| {
+                        spawned += 1;
+                        self.spawn_empty()
+                    });
+                    self.insert_values(entity, id, values);
+                }
+            }
+        }
+        writeln!(
+            out,
+            "Imported {} entities of {} ({} spawned)",
+            count, name, spawned
+        )
+    }
+
+    /// Saves the session components of every entity matched by a query under a name.
+    fn snapshot_query(&mut self, rest: &str, out: &mut impl Write) -> io::Result<()> {
+        let Some((query, name)) = rest.rsplit_once(" into ") else {
+            return writeln!(out, "{}", SNAPSHOT_PROMPT);
+        };
+        let mut builder = QueryBuilder::<Entity>::new(&mut self.world);
+        if let Err(err) = builder.parse(query, &self.component_names) {
+            return writeln!(out, "{}", err);
+        }
+        let entities: Vec<_> = builder.build().iter(&self.world).collect();
+
+        let snapshot: Vec<_> = entities
+            .into_iter()
+            .map(|entity| {
+                let mut ids: Vec<_> = self
+                    .world
+                    .entity(entity)
+                    .archetype()
+                    .components()
+                    .filter(|id| self.component_meta.contains_key(id))
+                    .collect();
+                ids.sort_by_key(|id| id.index());
+                let components = ids
+                    .into_iter()
+                    .map(|id| (id, self.read_values(entity, id).unwrap()))
+                    .collect();
+                (entity, components)
+            })
+            .collect();
+        let name = name.trim();
+        writeln!(out, "Saved {} entities into {}", snapshot.len(), name)?;
+        self.snapshots.insert(name.to_string(), snapshot);
+        Ok(())
+    }
+
+    /// Writes a snapshot back into the world, updating the saved entities which still exist and
+    /// spawning new entities for the rest.
+    fn restore(&mut self, rest: &str, out: &mut impl Write) -> io::Result<()> {
+        let name = rest.trim();
+        let Some(snapshot) = self.snapshots.get(name) else {
+            return writeln!(out, "Snapshot {} does not exist", name);
+        };
+        let ids = snapshot
+            .iter()
+            .flat_map(|(_, components)| components.iter());
+        if let Err(err) = self.check_frozen(ids.map(|(id, _)| *id)) {
+            return writeln!(out, "{}", err);
+        }
+
+        let snapshot = snapshot.clone();
+        let mut spawned = 0;
+        for (entity, components) in &snapshot {
+            let entity = if self.world.entities().contains(*entity) {
+                *entity
+            } else {
+                spawned += 1;
+                self.spawn_empty()
+            };
+            for (id, values) in components {
+                self.insert_values(entity, *id, values);
+            }
+        }
+        writeln!(
+            out,
+            "Restored {} entities from {} ({} spawned)",
+            snapshot.len(),
+            name,
+            spawned
+        )
+    }
+
+    /// Counts the entities matched by a query and compares the count, recording a failure if the
+    /// comparison does not hold.
+    fn assert_count(&mut self, rest: &str, out: &mut impl Write) -> io::Result<()> {
+        let mut parts = rest.trim().rsplitn(3, char::is_whitespace);
+        let (Some(expected), Some(op), Some(query)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return writeln!(out, "{}", ASSERT_PROMPT);
+        };
+        let Ok(expected) = expected.parse::<usize>() else {
+            return writeln!(out, "Invalid count: {}", expected);
+        };
+        let mut builder = QueryBuilder::<Entity>::new(&mut self.world);
+        if let Err(err) = builder.parse(query, &self.component_names) {
+            return writeln!(out, "{}", err);
+        }
+        let count = builder.build().iter(&self.world).count();
+
+        let passed = match op {
+            "==" => count == expected,
+            "!=" => count != expected,
+            "<" => count < expected,
+            "<=" => count <= expected,
+            ">" => count > expected,
+            ">=" => count >= expected,
+            _ => return writeln!(out, "Invalid comparison: {}", op),
+        };
+        if passed {
+            return writeln!(out, "Assertion passed: {} entities match", count);
+        }
+        self.failed_assertions += 1;
+        writeln!(
+            out,
+            "Assertion failed: expected {} {} entities but {} match",
+            op, expected, count
+        )
+    }
+
+    /// Returns the status to exit with, once an assertion has failed in script mode.
+    fn exit_code(&self) -> Option<i32> {
+        (self.script && self.failed_assertions > 0).then_some(1)
+    }
+
+    fn gc(&mut self, rest: &str, out: &mut impl Write) -> io::Result<()> {
+        let mut keep = HashSet::new();
+        if !rest.is_empty() {
+            let mut builder = QueryBuilder::<Entity>::new(&mut self.world);
+            if let Err(err) = builder.parse(rest, &self.component_names) {
+                return writeln!(out, "{}", err);
+            }
+            keep.extend(builder.build().iter(&self.world));
+        }
+        let referenced = self.referenced_entities();
+
+        let orphans: Vec<_> = self
+            .world
+            .iter_entities()
+            // Despawning would remove frozen components
+            .filter(|entity| self.check_frozen(entity.archetype().components()).is_ok())
+            .map(|entity| entity.id())
+            .filter(|entity| !referenced.contains(entity) && !keep.contains(entity))
+            .collect();
+        for &entity in &orphans {
+            self.world.despawn(entity);
+            self.spawn_order.remove(&entity);
+        }
+
+        let orphans: Vec<_> = orphans
+            .iter()
+            .map(|&entity| self.radix.entity(entity))
+            .collect();
+        writeln!(
+            out,
+            "Collected {} entities: {}",
+            orphans.len(),
+            orphans.join(", ")
+        )
+    }
+
+    /// Parses an entity written either as its index or in its debug format, e.g. `3v1`. Indices
+    /// prefixed with `0x` are read as hexadecimal.
+    fn parse_entity(&self, str: &str) -> Option<Entity> {
+        let (index, generation) = match str.trim().split_once('v') {
+            Some((index, generation)) => (index, Some(generation.parse::<u32>().ok()?)),
+            None => (str.trim(), None),
+        };
+        let index = match index.strip_prefix("0x") {
+            Some(index) => u32::from_str_radix(index, 16).ok()?,
+            None => index.parse::<u32>().ok()?,
+        };
+        let entity = self.world.entities().resolve_from_id(index)?;
+        if generation.is_some_and(|generation| generation != entity.generation()) {
+            return None;
+        }
+        self.world.get_entity(entity).map(|entity| entity.id())
+    }
+
+    fn same_as(&mut self, rest: &str, out: &mut impl Write) -> io::Result<()> {
+        let Some(entity) = self.parse_entity(rest) else {
+            return writeln!(out, "Entity {} does not exist", rest);
+        };
+        let components: Vec<_> = self.world.entity(entity).archetype().components().collect();
+
+        let mut builder = QueryBuilder::<EntityRef>::new(&mut self.world);
+        for &id in &components {
+            builder.with_id(id);
+        }
+        let mut query = builder.build();
+
+        // Entities with extra components also match the query, so only keep exact matches
+        let matches: Vec<_> = query
+            .iter(&self.world)
+            .filter(|other| other.id() != entity)
+            .filter(|other| other.archetype().components().count() == components.len())
+            .map(|other| self.radix.entity(other.id()))
+            .collect();
+
+        writeln!(
+            out,
+            "{} entities share the components of {}: {}",
+            matches.len(),
+            self.radix.entity(entity),
+            matches.join(", ")
+        )
+    }
+
+    /// Prints the components only on one of two entities and those on both with different
+    /// values. Only the values of components created by the session are compared.
+    fn entity_diff(&self, rest: &str, out: &mut impl Write) -> io::Result<()> {
+        let args: Vec<_> = rest.split_whitespace().collect();
+        let [a, b] = args[..] else {
+            return writeln!(out, "{}", ENTDIFF_PROMPT);
+        };
+        let mut entities = Vec::new();
+        for entity in [a, b] {
+            let Some(entity) = self.parse_entity(entity) else {
+                return writeln!(out, "Entity {} does not exist", entity);
+            };
+            entities.push(entity);
+        }
+        let [a, b] = entities[..] else { unreachable!() };
+
+        let components = |entity| {
+            let mut ids: Vec<_> = self.world.entity(entity).archetype().components().collect();
+            ids.sort_by_key(|id| id.index());
+            ids
+        };
+        let (a_ids, b_ids) = (components(a), components(b));
+        let only = |ids: &[ComponentId], other: &[ComponentId]| {
+            ids.iter()
+                .filter(|id| !other.contains(id))
+                .map(|&id| self.component_name(id))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        for (entity, ids, other) in [(a, &a_ids[..], &b_ids[..]), (b, &b_ids[..], &a_ids[..])] {
+            let entity = self.radix.entity(entity);
+            writeln!(out, "Only on {}: {}", entity, only(ids, other))?;
+        }
+
+        let differences: Vec<_> = a_ids
+            .iter()
+            .filter(|id| b_ids.contains(id) && self.component_meta.contains_key(id))
+            .filter_map(|&id| {
+                let meta = &self.component_meta[&id];
+                let a_values = self.read_values(a, id)?;
+                let b_values = self.read_values(b, id)?;
+                if a_values == b_values {
+                    return None;
+                }
+                let [a_data, b_data] = [a_values, b_values].map(|values| {
+                    values
+                        .into_iter()
+                        .map(|value| meta.encode(value))
+                        .collect::<Vec<_>>()
+                });
+                Some(format!(
+                    "{} {} != {}",
+                    meta.name,
+                    meta.format(&a_data, self.radix),
+                    meta.format(&b_data, self.radix)
+                ))
+            })
+            .collect();
+        writeln!(out, "Different: {}", differences.join(", "))
+    }
+
+    /// Replaces comparisons such as `A[0] == B[1]` with filters requiring both components,
+    /// returning the remaining query and the pairs of elements which must be equal.
+    fn parse_comparisons(&self, query: &str) -> Result<(String, Vec<(Element, Element)>), String> {
+        let mut comparisons = Vec::new();
+        let mut terms = Vec::new();
+        for term in split_terms(query) {
+            let Some((a, b)) = term.split_once("==") else {
+                terms.push(term.to_string());
+                continue;
+            };
+            let (a, b) = (self.parse_element(a)?, self.parse_element(b)?);
+            let names = [a, b].map(|(id, _)| self.component_meta[&id].name.clone());
+            terms.push(names.join(", "));
+            comparisons.push((a, b));
+        }
+        Ok((terms.join(","), comparisons))
+    }
+
+    /// Strips the `since:N` prefix from terms, returning the remaining query, the components
+    /// which must have been added or changed at or after the tick and the tick before it.
+    ///
+    /// Change filters are checked against a single tick per query, so every term must use the
+    /// same one.
+    fn parse_since(&self, query: &str) -> Result<(String, Vec<ComponentId>, Option<Tick>), String> {
+        let mut since = Vec::new();
+        let mut since_tick = None;
+        let mut terms = Vec::new();
+        for term in split_terms(query) {
+            let Some(rest) = term.trim().strip_prefix("since:") else {
+                terms.push(term.to_string());
+                continue;
+            };
+            let (tick, term) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            let Ok(tick) = tick.parse::<u32>() else {
+                return Err(format!("Invalid tick: {}", tick));
+            };
+            if since_tick.is_some_and(|since_tick| since_tick != tick) {
+                return Err("Every since: term of a query must use the same tick".to_string());
+            }
+            since_tick = Some(tick);
+            since.push(self.term_component(term)?);
+            terms.push(term.to_string());
+        }
+        // Change ticks are compared exclusively, so look for changes newer than the tick before
+        let last_run = since_tick.map(|tick| Tick::new(tick.saturating_sub(1)));
+        Ok((terms.join(","), since, last_run))
+    }
+
+    /// Strips the `distinct` suffix from terms, returning the remaining query and the components
+    /// whose values must differ between printed entities.
+    fn parse_distinct(&self, query: &str) -> Result<(String, Vec<ComponentId>), String> {
+        let mut distinct = Vec::new();
+        let mut terms = Vec::new();
+        for term in split_terms(query) {
+            let Some(term) = term.trim().strip_suffix(" distinct") else {
+                terms.push(term.to_string());
+                continue;
+            };
+            distinct.push(self.term_component(term)?);
+            terms.push(term.to_string());
+        }
+        Ok((terms.join(","), distinct))
+    }
+
+    /// Finds the component of a single query term such as `&mut A`.
+    fn term_component(&self, term: &str) -> Result<ComponentId, String> {
+        let (_, name) = term_name(term);
+        match self.component_names.get(name) {
+            Some(&id) => Ok(id),
+            None => Err(format!("Unable to find component: {}", name)),
+        }
+    }
+
+    /// Parses a single component element such as `A[2]`.
+    fn parse_element(&self, str: &str) -> Result<Element, String> {
+        let str = str.trim();
+        let Some((name, element)) = str.strip_suffix(']').and_then(|str| str.split_once('['))
+        else {
+            return Err(format!(
+                "Expected a component element such as A[0]: {}",
+                str
+            ));
+        };
+        let Some(&id) = self.component_names.get(name.trim()) else {
+            return Err(format!("Unable to find component: {}", name.trim()));
+        };
+        let len = self.component_info[&id].layout().size() / std::mem::size_of::<u64>();
+        match element.trim().parse::<usize>() {
+            Ok(element) if element < len => Ok((id, element)),
+            _ => Err(format!(
+                "Invalid element {} for {} with {} elements",
+                element.trim(),
+                name.trim(),
+                len
+            )),
+        }
+    }
+
+    /// Strips element subsets such as `&mut A[0,2]` from the write terms of a query, returning the
+    /// remaining query and the elements to write for each component.
+    fn parse_write_elements(
+        &self,
+        query: &str,
+    ) -> Result<(String, HashMap<ComponentId, Vec<usize>>), String> {
+        let mut write_elements = HashMap::new();
+        let mut terms = Vec::new();
+        for term in split_terms(query) {
+            let mut sub_terms = Vec::new();
+            for sub_term in term.split("||") {
+                let Some((sub_term, elements)) = sub_term.split_once('[') else {
+                    sub_terms.push(sub_term.to_string());
+                    continue;
+                };
+                let Some(name) = sub_term.trim().strip_prefix("&mut ") else {
+                    return Err(format!(
+                        "Element subsets are only supported on write terms: {}",
+                        term.trim()
+                    ));
+                };
+                let name = name.trim();
+                let Some(&id) = self.component_names.get(name) else {
+                    return Err(format!("Unable to find component: {}", name));
+                };
+                let info = &self.component_info[&id];
+                let len = info.layout().size() / std::mem::size_of::<u64>();
+                let elements = elements
+                    .trim_end()
+                    .trim_end_matches(']')
+                    .split(',')
+                    .map(|element| match element.trim().parse::<usize>() {
+                        Ok(element) if element < len => Ok(element),
+                        _ => Err(format!(
+                            "Invalid element {} for {} with {} elements",
+                            element.trim(),
+                            name,
+                            len
+                        )),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                write_elements.insert(id, elements);
+                sub_terms.push(sub_term.to_string());
+            }
+            terms.push(sub_terms.join("||"));
+        }
+        Ok((terms.join(","), write_elements))
+    }
+
+    fn parse(&mut self, rest: &str, out: &mut impl Write) -> io::Result<()> {
+        let mut builder = QueryBuilder::<FilteredEntityMut>::new(&mut self.world);
+        if let Err(err) = builder.parse(rest, &self.component_names) {
+            return writeln!(out, "{}", err);
+        }
+        let query = builder.build();
+
+        writeln!(
+            out,
+            "{}",
+            render_query(query.component_access(), &self.component_meta)
+        )
+    }
+
+    fn show_docs(&self, rest: &str, out: &mut impl Write) -> io::Result<()> {
+        for name in rest.split(',').map(str::trim) {
+            if !self.component_names.contains_key(name) {
+                writeln!(out, "Component {} does not exist", name)?;
+                continue;
+            }
+            match self.doc(name) {
+                Some(doc) => writeln!(out, "{}: {}", name, doc)?,
+                None => writeln!(out, "{}: no documentation", name)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn list_components(&self, out: &mut impl Write) -> io::Result<()> {
+        let mut ids: Vec<_> = self.component_info.keys().copied().collect();
+        ids.sort();
+        for id in ids {
+            let info = &self.component_info[&id];
+            let len = info.layout().size() / std::mem::size_of::<u64>();
+            let name = &self.component_meta[&id].name;
+            write!(out, "{} (id: {}, size: {})", name, self.radix.id(id), len)?;
+            match &self.component_meta[&id].doc {
+                Some(doc) => writeln!(out, " {}", doc)?,
+                None => writeln!(out)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn show_access_stats(&self, out: &mut impl Write) -> io::Result<()> {
+        let mut ids: Vec<_> = self.access_stats.keys().copied().collect();
+        ids.sort();
+        writeln!(out, "{:<16} {:>8} {:>8}", "component", "reads", "writes")?;
+        for id in ids {
+            let stats = self.access_stats[&id];
+            let name = &self.component_meta[&id].name;
+            writeln!(out, "{:<16} {:>8} {:>8}", name, stats.reads, stats.writes)?;
+        }
+        Ok(())
+    }
+}
+
+/// Prints every line of the usage prompts containing the keyword, ignoring case.
+fn help(keyword: &str, out: &mut impl Write) -> io::Result<()> {
+    let keyword = keyword.trim().to_lowercase();
+    let mut lines = HashSet::new();
+    for line in PROMPTS.iter().flat_map(|prompt| prompt.lines()) {
+        if line.to_lowercase().contains(&keyword) && lines.insert(line.trim()) {
+            writeln!(out, "{}", line.trim())?;
+        }
+    }
+    if lines.is_empty() {
+        writeln!(out, "No help found for {}", keyword)?;
+    }
+    Ok(())
+}
+
+/// Writes a reflected value and, recursively, its fields with one level of indentation each.
+fn write_tree(
+    name: &str,
+    value: &dyn Reflect,
+    depth: usize,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let indent = "  ".repeat(depth);
+    let type_path = value.reflect_short_type_path();
+    match value.reflect_ref() {
+        ReflectRef::Struct(value) => {
+            writeln!(out, "{}{}: {}", indent, name, type_path)?;
+            for i in 0..value.field_len() {
+                let field = value.name_at(i).unwrap();
+                write_tree(field, value.field_at(i).unwrap(), depth + 1, out)?;
+            }
+        }
+        ReflectRef::TupleStruct(value) => {
+            writeln!(out, "{}{}: {}", indent, name, type_path)?;
+            for (i, field) in value.iter_fields().enumerate() {
+                write_tree(&i.to_string(), field, depth + 1, out)?;
+            }
+        }
+        ReflectRef::Tuple(value) => {
+            writeln!(out, "{}{}: {}", indent, name, type_path)?;
+            for (i, field) in value.iter_fields().enumerate() {
+                write_tree(&i.to_string(), field, depth + 1, out)?;
+            }
+        }
+        ReflectRef::List(value) => {
+            writeln!(out, "{}{}: {}", indent, name, type_path)?;
+            for (i, item) in value.iter().enumerate() {
+                write_tree(&format!("[{}]", i), item, depth + 1, out)?;
+            }
+        }
+        ReflectRef::Array(value) => {
+            writeln!(out, "{}{}: {}", indent, name, type_path)?;
+            for (i, item) in value.iter().enumerate() {
+                write_tree(&format!("[{}]", i), item, depth + 1, out)?;
+            }
+        }
+        ReflectRef::Map(value) => {
+            writeln!(out, "{}{}: {}", indent, name, type_path)?;
+            for (key, item) in value.iter() {
+                write_tree(&format!("[{:?}]", key), item, depth + 1, out)?;
+            }
+        }
+        ReflectRef::Enum(value) => {
+            writeln!(
+                out,
+                "{}{}: {}::{}",
+                indent,
+                name,
+                type_path,
+                value.variant_name()
+            )?;
+            for (i, field) in value.iter_fields().enumerate() {
+                let field_name = field.name().map_or_else(|| i.to_string(), str::to_string);
+                write_tree(&field_name, field.value(), depth + 1, out)?;
+            }
+        }
+        ReflectRef::Value(value) => writeln!(out, "{}{}: {:?}", indent, name, value)?,
+    }
+    Ok(())
+}
+
+/// Strips `order` terms such as `order<10` from a query, returning the remaining query and the
+/// range of spawn orders matched entities must be in.
+fn parse_order(query: &str) -> Result<(String, Option<Range<usize>>), String> {
+    let mut order: Option<Range<usize>> = None;
+    let mut terms = Vec::new();
+    for term in split_terms(query) {
+        let comparison = term.trim().strip_prefix("order");
+        let Some(comparison) = comparison.filter(|c| c.starts_with(['<', '>', '='])) else {
+            terms.push(term);
+            continue;
+        };
+        let op_len = comparison.find(|c: char| !"<>=".contains(c)).unwrap_or(0);
+        let (op, n) = comparison.split_at(op_len);
+        let Ok(n) = n.trim().parse::<usize>() else {
+            return Err(format!("Invalid spawn order: {}", n.trim()));
+        };
+        let range = match op {
+            "<" => 0..n,
+            "<=" => 0..n + 1,
+            ">" => n + 1..usize::MAX,
+            ">=" => n..usize::MAX,
+            "==" => n..n + 1,
+            _ => return Err(format!("Invalid order comparison: {}", term.trim())),
+        };
+        order = Some(match order {
+            Some(order) => order.start.max(range.start)..order.end.min(range.end),
+            None => range,
+        });
+    }
+    Ok((terms.join(","), order))
+}
+
+/// Splits a line into its command and the rest of the line.
+fn split_command(line: &str) -> (&str, &str) {
+    let line = line.trim();
+    line.split_once(char::is_whitespace).unwrap_or((line, ""))
+}
+
+/// Splits a query term such as `?&mut A` into its prefix and component name.
+fn term_name(term: &str) -> (&str, &str) {
+    let term = term.trim();
+    let name = term
+        .trim_start_matches(['?', '!', '&'])
+        .trim_start_matches("mut ")
+        .trim_start();
+    (&term[..term.len() - name.len()], name)
+}
+
+/// Splits a comma separated list, ignoring commas inside double quotes, parentheses or square
+/// brackets.
+fn split_terms(str: &str) -> Vec<&str> {
+    let mut terms = Vec::new();
+    let mut quoted = false;
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in str.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '[' | '(' if !quoted => depth += 1,
+            ']' | ')' if !quoted => depth -= 1,
+            ',' if !quoted && depth == 0 => {
+                terms.push(&str[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    terms.push(&str[start..]);
+    terms
+}
+
+/// Renders the access of a query back into the syntax understood by [`QueryBuilder::parse`].
+///
+/// Accessed components are listed first, followed by the filters shared by every
+/// filter set and finally any remaining filters as an or expression.
+fn render_query(
+    access: &FilteredAccess<ComponentId>,
+    components: &HashMap<ComponentId, ComponentMeta>,
+) -> String {
+    let name = |id: ComponentId| match components.get(&id) {
+        Some(meta) => meta.name.clone(),
+        None => format!("{:?}", id),
+    };
+    let required: Vec<_> = access.required().collect();
+
+    let mut terms: Vec<String> = access
+        .access()
+        .reads_and_writes()
+        .map(|id| {
+            let optional = if required.contains(&id) { "" } else { "?" };
+            let access = if access.access().has_write(id) {
+                "&mut "
+            } else {
+                "&"
+            };
+            format!("{}{}{}", optional, access, name(id))
+        })
+        .collect();
+
+    // Filters on required components are implied by their access
+    let filter_sets: Vec<Vec<String>> = access
+        .filter_sets()
+        .iter()
+        .map(|filters| {
+            filters
+                .with()
+                .filter(|id| !required.contains(id))
+                .map(name)
+                .chain(filters.without().map(|id| format!("!{}", name(id))))
+                .collect()
+        })
+        .collect();
+
+    let shared: Vec<String> = filter_sets[0]
+        .iter()
+        .filter(|term| filter_sets.iter().all(|filters| filters.contains(term)))
+        .cloned()
+        .collect();
+    let alternatives: Vec<String> = filter_sets
+        .iter()
+        .map(|filters| {
+            let terms: Vec<_> = filters
+                .iter()
+                .filter(|term| !shared.contains(term))
+                .cloned()
+                .collect();
+            // A branch of several filters is grouped so its terms stay together
+            match terms.len() {
+                0 | 1 => terms.join(""),
+                _ => format!("({})", terms.join(", ")),
+            }
+        })
+        .collect();
+
+    terms.extend(shared);
+    // An empty alternative always matches, making the whole or expression redundant
+    if alternatives.len() > 1 && alternatives.iter().all(|terms| !terms.is_empty()) {
+        terms.push(alternatives.join(" || "));
+    }
+    terms.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(session: &mut Session, line: &str) -> String {
+        let mut out = Vec::new();
+        session.execute(line, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn component_docs() {
+        let mut session = Session::default();
+        run(&mut session, "comp CompA 3 \"position, xyz\", CompB 1");
+
+        assert_eq!(Some("position, xyz"), session.doc("CompA"));
+        assert_eq!(None, session.doc("CompB"));
+
+        assert_eq!("CompA: position, xyz\n", run(&mut session, "doc CompA"));
+        assert_eq!("CompB: no documentation\n", run(&mut session, "doc CompB"));
+
+        let list = run(&mut session, "list");
+        assert!(list.contains("CompA (id: 0, size: 3) position, xyz"));
+        assert!(list.contains("CompB (id: 1, size: 1)\n"));
+    }
+
+    #[test]
+    fn unknown_query_component() {
+        let mut session = Session::default();
+        run(&mut session, "comp CompA");
+        run(&mut session, "spawn CompA");
+
+        assert_eq!(
+            "Unknown component `CompB` at position 14\n",
+            run(&mut session, "query &CompA, ?&mut CompB")
+        );
+        assert_eq!(
+            "Unknown component `CompC` at position 9\n",
+            run(&mut session, "query CompA || CompC")
+        );
+    }
+
+    #[test]
+    fn partial_writes() {
+        let mut session = Session::default();
+        run(&mut session, "comp CompA 3, CompB 2");
+        run(&mut session, "spawn CompA 1 2 3, CompB 1 2");
+
+        let output = run(&mut session, "query &mut CompA[0,2], &mut CompB");
+        assert!(output.contains("CompA: [2, 2, 4]"));
+        assert!(output.contains("CompB: [2, 3]"));
+
+        let output = run(&mut session, "query &mut CompA[1, 3]");
+        assert_eq!("Invalid element 3 for CompA with 3 elements\n", output);
+
+        let output = run(&mut session, "query &CompA[1]");
+        assert!(output.starts_with("Element subsets are only supported on write terms"));
+
+        let output = run(&mut session, "query &CompA");
+        assert!(output.contains("CompA: [2, 2, 4]"));
+    }
+
+    #[test]
+    fn access_stats() {
+        let mut session = Session::default();
+        run(&mut session, "comp CompA 1, CompB 1, CompC 1");
+        run(&mut session, "spawn CompA, CompB");
+        run(&mut session, "spawn CompA, CompB");
+        run(&mut session, "spawn CompA");
+
+        run(&mut session, "query &CompA, &mut CompB");
+        run(&mut session, "query &mut CompA");
+        run(&mut session, "query CompC");
+
+        let stats = |name: &str| session.access_stats.get(&session.component_names[name]);
+        assert_eq!(
+            Some(&AccessStats {
+                reads: 2,
+                writes: 3
+            }),
+            stats("CompA")
+        );
+        assert_eq!(
+            Some(&AccessStats {
+                reads: 0,
+                writes: 2
+            }),
+            stats("CompB")
+        );
+        assert_eq!(None, stats("CompC"));
+
+        let table = run(&mut session, "access-stats");
+        assert_eq!(3, table.lines().count());
+        assert!(table.contains("CompB                   0        2"));
+    }
+
+    #[test]
+    fn spawn_many() {
+        let mut session = Session::default();
+        run(&mut session, "comp CompA 2, CompB 1");
+
+        let output = run(&mut session, "spawn 100x CompA 1 2, CompB 3");
+        assert!(output.starts_with("100 entities spawned with ids:"));
+        assert_eq!(100, session.world.entities().len());
+
+        let output = run(&mut session, "query &CompA, &CompB");
+        assert_eq!(100, output.lines().count());
+
+        let entity = session.world.iter_entities().nth(42).unwrap();
+        let id_a = session.component_names["CompA"];
+        let id_b = session.component_names["CompB"];
+        // SAFETY: Both components have the layout [u64]
+        unsafe {
+            assert_eq!(&[1, 2], entity.get_by_id(id_a).unwrap().deref::<[u64; 2]>());
+            assert_eq!(&[3], entity.get_by_id(id_b).unwrap().deref::<[u64; 1]>());
+        }
+    }
+
+    #[test]
+    fn spawn_duplicate_component() {
+        let mut session = Session::default();
+        run(&mut session, "comp CompA 1");
+
+        let output = run(&mut session, "spawn 3x CompA 1, CompA 2");
+        assert!(output.contains("appears more than once"));
+        assert_eq!(0, session.world.entities().len());
+    }
+
+    #[test]
+    fn same_as() {
+        let mut session = Session::default();
+        run(&mut session, "comp CompA 1, CompB 1, CompC 1");
+        run(&mut session, "spawn CompA 1, CompB 2");
+        run(&mut session, "spawn CompA 3, CompB 4");
+        run(&mut session, "spawn CompA 5, CompB 6, CompC 7");
+        run(&mut session, "spawn CompA 8");
+
+        assert_eq!(
+            "1 entities share the components of 0v1: 1v1\n",
+            run(&mut session, "same-as 0v1")
+        );
+        assert_eq!(
+            "0 entities share the components of 3v1: \n",
+            run(&mut session, "same-as 3")
+        );
+        assert_eq!("Entity 9 does not exist\n", run(&mut session, "same-as 9"));
+        assert_eq!(
+            "Entity 0v2 does not exist\n",
+            run(&mut session, "same-as 0v2")
+        );
+    }
+
+    #[test]
+    fn big_endian_components() {
+        let mut session = Session::default();
+        run(&mut session, "comp CompA 2 be, CompB 2");
+        run(&mut session, "spawn CompA 1 258, CompB 1 258");
+
+        let stored = |session: &Session, name: &str| {
+            let entity = session.world.iter_entities().next().unwrap();
+            let ptr = entity.get_by_id(session.component_names[name]).unwrap();
+            // SAFETY: Both components have the layout [u64; 2]
+            unsafe { *ptr.deref::<[u64; 2]>() }
+        };
+        assert_eq!([1u64.to_be(), 258u64.to_be()], stored(&session, "CompA"));
+        assert_eq!([1, 258], stored(&session, "CompB"));
+
+        let output = run(&mut session, "query &mut CompA, &CompB");
+        assert!(output.contains("CompA: [2, 259], CompB: [1, 258]"));
+        assert_eq!([2u64.to_be(), 259u64.to_be()], stored(&session, "CompA"));
+
+        let output = run(&mut session, "set 0v1 CompA 513");
+        assert_eq!("0v1: CompA: [513, 259]\n", output);
+        assert_eq!(
+            513u64.to_be_bytes(),
+            stored(&session, "CompA")[0].to_ne_bytes()
+        );
+    }
+
+    #[test]
+    fn gc() {
+        let mut session = Session::default();
+        run(&mut session, "comp Parent 2 entity, Tag 1");
+        run(&mut session, "spawn Tag 1");
+        run(&mut session, "spawn Tag 2");
+        run(&mut session, "spawn Tag 3");
+        run(&mut session, "spawn Parent 0v1 2");
+        run(&mut session, "spawn Parent");
+
+        let output = run(&mut session, "query &Parent");
+        assert!(output.contains("3v1: Parent: [0v1, 2v1]"));
+        assert!(output.contains("4v1: Parent: [none, none]"));
+
+        // Entities holding relationships are kept while the unreferenced entity 1v1 is collected
+        let output = run(&mut session, "gc Parent");
+        assert_eq!("Collected 1 entities: 1v1\n", output);
+
+        let remaining: Vec<_> = session.world.iter_entities().map(|e| e.id()).collect();
+        assert_eq!(4, remaining.len());
+        assert!(!remaining.contains(&Entity::from_raw(1)));
+
+        // Without a keep query only referenced entities survive
+        let output = run(&mut session, "gc");
+        assert_eq!("Collected 2 entities: 3v1, 4v1\n", output);
+    }
+
+    #[test]
+    fn lerp() {
+        let mut session = Session::default();
+        run(&mut session, "comp Position 2 f32, Count 1");
+        run(&mut session, "spawn Position 0 10, Count 1");
+        run(&mut session, "spawn Position 4 20.5, Count 2");
+        run(&mut session, "spawn Position");
+
+        let output = run(&mut session, "lerp Position 0 1 0.25 2");
+        assert_eq!("2v1: Position: [1, 12.625]\n", output);
+
+        let output = run(&mut session, "lerp Position 0 1 0.5");
+        assert_eq!("0v1: Position: [2, 15.25]\n", output);
+
+        let output = run(&mut session, "query &mut Position, &Count");
+        assert!(output.contains("0v1: Position: [3, 16.25], Count: [1]"));
+
+        let output = run(&mut session, "lerp Count 0 1 0.5");
+        assert_eq!("Component Count is not float typed\n", output);
+        let output = run(&mut session, "lerp Position 0 5 0.5");
+        assert_eq!("Entity 5 does not exist\n", output);
+    }
+
+    #[test]
+    fn help_search() {
+        let mut session = Session::default();
+        let output = run(&mut session, "help Big-Endian");
+        assert_eq!(
+            "Options: 'be' store elements as big-endian bytes, 'f32' elements are floats,\n",
+            output
+        );
+
+        let output = run(&mut session, "help interpolate");
+        assert_eq!(
+            "lerp      Interpolate a float component between two entities\n\
+             lerp      Interpolate a float typed component between two entities\n\
+             Enter a component, the entities to interpolate between, a factor and optionally\n",
+            output
+        );
+
+        let output = run(&mut session, "help teleport");
+        assert_eq!("No help found for teleport\n", output);
+    }
+
+    #[test]
+    fn components_in_registration_order() {
+        let mut session = Session::default();
+        run(&mut session, "comp A 1, B 1, C 1");
+        run(&mut session, "spawn C 3, B 2, A 1");
+
+        let output = run(&mut session, "query &C, &mut B, ?&A");
+        assert_eq!("0v1: A: [1], B: [3], C: [3]\n", output);
+    }
+
+    #[test]
+    fn verify() {
+        let mut session = Session::default();
+        run(&mut session, "comp A 2, Parent 1 entity");
+        run(&mut session, "spawn A 1 2");
+        run(&mut session, "spawn Parent 0");
+        assert_eq!("World is consistent\n", run(&mut session, "verify"));
+
+        #[derive(Component)]
+        struct Unregistered;
+        let unregistered = session.world.spawn(Unregistered).id();
+        session.world.despawn(Entity::from_raw(0));
+        let id = session.world.component_id::<Unregistered>().unwrap();
+        assert_eq!(
+            format!(
+                "Entity 1v1 has Parent referencing despawned entity 0v1\n\
+                 Entity {:?} has unregistered component {:?}\n\
+                 2 violations found\n",
+                unregistered, id
+            ),
+            run(&mut session, "verify")
+        );
+
+        let a = session.component_names["A"];
+        session.component_meta.get_mut(&a).unwrap().len = 3;
+        assert!(run(&mut session, "verify")
+            .starts_with("Component A is stored with 16 bytes but was created with 24\n"));
+    }
+
+    #[test]
+    fn element_comparisons() {
+        let mut session = Session::default();
+        run(&mut session, "comp A 2, B 1");
+        run(&mut session, "spawn A 1 5, B 5");
+        run(&mut session, "spawn A 5 1, B 5");
+        run(&mut session, "spawn A 5 5");
+        run(&mut session, "spawn A 4 4, B 5");
+
+        let output = run(&mut session, "query &A, A[1] == B[0]");
+        assert_eq!("0v1: A: [1, 5]\n", output);
+        let output = run(&mut session, "query &A, &B, A[0] == B[0], A[1] == A[0]");
+        assert_eq!("", output);
+        let output = run(&mut session, "query &A, A[0] == A[1]");
+        assert_eq!(2, output.lines().count());
+        assert!(output.contains("2v1: A: [5, 5]") && output.contains("3v1: A: [4, 4]"));
+
+        let output = run(&mut session, "query &A, A[2] == B[0]");
+        assert_eq!("Invalid element 2 for A with 2 elements\n", output);
+    }
+
+    #[test]
+    fn spawn_archetype() {
+        let mut session = Session::default();
+        run(&mut session, "comp A 1, B 1");
+
+        let archetype = |output: String| {
+            let (_, archetype) = output.trim().rsplit_once("in archetype ").unwrap();
+            archetype.parse::<usize>().unwrap()
+        };
+        let a = archetype(run(&mut session, "spawn A 1"));
+        let ab = archetype(run(&mut session, "spawn A 1, B 2"));
+        let ba = archetype(run(&mut session, "spawn B 2, A 1"));
+        let many = archetype(run(&mut session, "spawn 3x A 2"));
+
+        assert!(a > 0, "archetype 0 is reserved for empty entities");
+        assert_ne!(a, ab);
+        assert_eq!(ab, ba);
+        assert_eq!(a, many);
+    }
+
+    #[test]
+    fn export_import() {
+        let path = std::env::temp_dir().join(format!("dynamic_export_{}.bin", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        let mut session = Session::default();
+        run(&mut session, "comp A 2, B 1");
+        run(&mut session, "spawn A 1 2");
+        run(&mut session, "spawn A 3 4, B 5");
+        let output = run(&mut session, &format!("export A {}", path));
+        assert_eq!(format!("Exported 2 entities of A to {}\n", path), output);
+
+        run(&mut session, "set 0 A 9 9");
+        session.world.despawn(Entity::from_raw(1));
+        let output = run(&mut session, &format!("import A {}", path));
+        assert_eq!("Imported 2 entities of A (1 spawned)\n", output);
+
+        let output = run(&mut session, "query &A");
+        assert_eq!(2, output.lines().count());
+        assert!(output.contains("0v1: A: [1, 2]"));
+        assert!(output.contains("1v2: A: [3, 4]"));
+
+        let output = run(&mut session, &format!("import B {}", path));
+        assert_eq!(
+            format!("{} has 2 elements per entity but B has 1\n", path),
+            output
+        );
+
+        let header = [u64::MAX, 2].map(u64::to_le_bytes).concat();
+        std::fs::write(path, header).unwrap();
+        let output = run(&mut session, &format!("import A {}", path));
+        assert_eq!(
+            format!("{} does not contain {} entities\n", path, u64::MAX),
+            output
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn since_tick() {
+        let mut session = Session::default();
+        run(&mut session, "comp A 1, B 1");
+        run(&mut session, "spawn A 1, B 1");
+        run(&mut session, "spawn A 2, B 2");
+        assert_eq!("Current tick: 5\n", run(&mut session, "tick"));
+        run(&mut session, "spawn A 3, B 3");
+        run(&mut session, "set 0 B 4");
+
+        let output = run(&mut session, "query &A, since:6 &B");
+        assert_eq!(2, output.lines().count());
+        assert!(output.contains("0v1: A: [1], B: [4]") && output.contains("2v1: A: [3], B: [3]"));
+
+        let output = run(&mut session, "query since:6 &A, since:6 &B");
+        assert_eq!("2v1: A: [3], B: [3]\n", output);
+        let output = run(&mut session, "query &A, since:7 B");
+        assert_eq!("0v1: A: [1]\n", output);
+        assert_eq!("", run(&mut session, "query &A, since:8 B"));
+        assert_eq!(3, run(&mut session, "query &A, since:0 B").lines().count());
+        assert_eq!(
+            "Every since: term of a query must use the same tick\n",
+            run(&mut session, "query since:6 &A, since:7 &B")
+        );
+    }
+
+    #[test]
+    fn archetype_table() {
+        let mut session = Session::default();
+        run(&mut session, "comp A 2, B 1, C 1");
+        run(&mut session, "spawn C 1");
+        let output = run(&mut session, "spawn A 1 2, B 3");
+        let (_, archetype) = output.trim().rsplit_once("in archetype ").unwrap();
+        run(&mut session, "spawn 2x A 10 20, B 300");
+
+        let output = run(&mut session, &format!("table {}", archetype));
+        assert_eq!(
+            "entity  A         B\n\
+             1v1     [1, 2]    [3]\n\
+             2v1     [10, 20]  [300]\n\
+             3v1     [10, 20]  [300]\n",
+            output
+        );
+
+        let output = run(&mut session, "table 100");
+        assert_eq!("Archetype 100 does not exist\n", output);
+    }
+
+    #[test]
+    fn distinct_values() {
+        let mut session = Session::default();
+        run(&mut session, "comp A 2, B 1");
+        run(&mut session, "spawn A 1 2, B 1");
+        run(&mut session, "spawn A 1 2, B 2");
+        run(&mut session, "spawn A 3 4, B 3");
+        run(&mut session, "spawn 2x A 1 2");
+
+        let output = run(&mut session, "query &A distinct");
+        assert_eq!(2, output.lines().count());
+        assert!(output.contains("A: [1, 2]") && output.contains("A: [3, 4]"));
+
+        let output = run(&mut session, "query &mut B, A distinct");
+        assert_eq!("0v1: B: [2]\n2v1: B: [4]\n", output);
+        let output = run(&mut session, "query &B");
+        assert_eq!("0v1: B: [2]\n1v1: B: [2]\n2v1: B: [4]\n", output);
+    }
+
+    #[test]
+    fn swap_registrations() {
+        let mut session = Session::default();
+        run(&mut session, "comp A 1, B 2");
+        run(&mut session, "spawn A 1, B 2 3");
+        run(&mut session, "spawn B 4 5");
+
+        let output = run(&mut session, "swapreg A B");
+        assert_eq!("A now refers to id 1 and B to id 0\n", output);
+
+        let output = run(&mut session, "query &A");
+        assert_eq!(2, output.lines().count());
+        assert!(output.contains("0v1: A: [2, 3]") && output.contains("1v1: A: [4, 5]"));
+        assert_eq!("0v1: B: [1]\n", run(&mut session, "query &B"));
+        assert_eq!("0v1: A: [6, 7]\n", run(&mut session, "set 0 A 6 7"));
+        assert_eq!(
+            "0v1: B: [1], A: [6, 7]\n",
+            run(&mut session, "query &B, &A")
+        );
+
+        run(&mut session, "swapreg B A");
+        assert_eq!(
+            "0v1: A: [1], B: [6, 7]\n",
+            run(&mut session, "query &B, &A")
+        );
+    }
+
+    #[test]
+    fn frozen_components() {
+        let mut session = Session::default();
+        run(&mut session, "comp A 1, B 1");
+        run(&mut session, "spawn A 1, B 1");
+        run(&mut session, "spawn A 2");
+        assert_eq!("Froze A\n", run(&mut session, "freeze A"));
+
+        let frozen = "Component A is frozen\n";
+        assert_eq!(frozen, run(&mut session, "spawn B 2, A 3"));
+        assert_eq!(frozen, run(&mut session, "set 0 A 5"));
+        assert_eq!(frozen, run(&mut session, "query &B, &mut A"));
+        assert_eq!(
+            "0v1: A: [1], B: [2]\n",
+            run(&mut session, "query &A, &mut B")
+        );
+        assert_eq!("Collected 0 entities: \n", run(&mut session, "gc"));
+
+        assert_eq!("Unfroze A\n", run(&mut session, "unfreeze A"));
+        assert!(run(&mut session, "spawn B 2, A 3").starts_with("Entity spawned"));
+        assert_eq!("0v1: A: [5]\n", run(&mut session, "set 0 A 5"));
+        assert_eq!(3, run(&mut session, "query &mut A").lines().count());
+        assert!(run(&mut session, "gc").starts_with("Collected 3 entities"));
+    }
+
+    #[test]
+    fn reflected_tree() {
+        #[derive(Component, Reflect, Default)]
+        #[reflect(Component, Default)]
+        struct Body {
+            position: Vec2,
+            mass: Mass,
+            tags: Vec<u32>,
+        }
+        #[derive(Reflect, Default)]
+        struct Mass(f32);
+
+        let mut session = Session::default();
+        session.type_registry.register::<Body>();
+        run(&mut session, "comp A 1");
+        run(&mut session, "spawn A 1, Body");
+        session
+            .world
+            .query::<&mut Body>()
+            .single_mut(&mut session.world)
+            .tags = vec![7];
+
+        assert_eq!(
+            "0v1\n  \
+             Body: Body\n    \
+             position: Vec2\n      \
+             x: 0.0\n      \
+             y: 0.0\n    \
+             mass: Mass\n      \
+             0: 0.0\n    \
+             tags: Vec<u32>\n      \
+             [0]: 7\n",
+            run(&mut session, "tree 0 Body")
+        );
+        assert_eq!(
+            "Component A is not reflected\n",
+            run(&mut session, "tree 0 A")
+        );
+        assert_eq!("World is consistent\n", run(&mut session, "verify"));
+    }
+
+    #[test]
+    fn map_expression() {
+        let mut session = Session::default();
+        run(&mut session, "comp A 2, B 1 f32, C 1");
+        run(&mut session, "spawn A 1 2");
+        run(&mut session, "spawn A 3 4, B 0.5");
+        run(&mut session, "spawn A 5 6, C 1");
+        let tick = run(&mut session, "tick");
+        let tick: u32 = tick
+            .trim()
+            .trim_start_matches("Current tick: ")
+            .parse()
+            .unwrap();
+
+        let output = run(&mut session, "map A[0] = A[0] * 2 where &A");
+        assert_eq!("Mapped 3 entities\n", output);
+        let output = run(&mut session, &format!("query &A, since:{} A", tick + 1));
+        assert_eq!(3, output.lines().count());
+        assert!(output.contains("0v1: A: [2, 2]"));
+        assert!(output.contains("1v1: A: [6, 4]"));
+        assert!(output.contains("2v1: A: [10, 6]"));
+
+        let output = run(&mut session, "map B[0] = (A[0] - A[1]) / -B[0]");
+        assert_eq!("Mapped 1 entities\n", output);
+        let output = run(&mut session, "map A[1] = A[1] + 100 where !C");
+        assert_eq!("Mapped 2 entities\n", output);
+        let output = run(&mut session, &format!("query &A, &B, since:{} A", tick + 4));
+        assert_eq!("1v1: A: [6, 104], B: [-4]\n", output);
+
+        let output = run(&mut session, "map A[2] = 1");
+        assert_eq!("Invalid element 2 for A with 2 elements\n", output);
+        let output = run(&mut session, "map A[0] = A[0] +");
+        assert_eq!("Unexpected end of expression\n", output);
+    }
+
+    #[test]
+    fn empty_entities() {
+        let mut session = Session::default();
+        run(&mut session, "comp A 1");
+        run(&mut session, "spawn A 1");
+        let empty = session.world.spawn_empty().id();
+        run(&mut session, "spawn A 2");
+
+        assert_eq!(format!("{:?}\n", empty), run(&mut session, "query empty"));
+        assert_eq!(2, run(&mut session, "query &A").lines().count());
+    }
+
+    #[test]
+    fn verbose_timing() {
+        let mut session = Session::default();
+        let output = run(&mut session, "comp A 1");
+        assert_eq!("Component A created with id: 0\n", output);
+
+        session.verbose = true;
+        let output = run(&mut session, "spawn A 1");
+        let (output, timing) = output.trim_end().rsplit_once('\n').unwrap();
+        assert!(output.starts_with("Entity spawned with id: 0v1"));
+        assert!(timing.starts_with("(took ") && timing.ends_with("s)"));
+    }
+
+    #[test]
+    fn alias_views() {
+        let mut session = Session::default();
+        run(&mut session, "comp A 3, B 1");
+        run(&mut session, "spawn A 1 2 3, B 1");
+        run(&mut session, "spawn B 2");
+
+        let output = run(&mut session, "alias-view XZ = A[0, 2]");
+        assert_eq!("View XZ created for A[0, 2]\n", output);
+        assert_eq!("0v1: XZ: [1, 3]\n", run(&mut session, "query &XZ"));
+        assert_eq!("0v1: B: [1]\n", run(&mut session, "query XZ, &B"));
+        assert_eq!("1v1: B: [2]\n", run(&mut session, "query !XZ, &B"));
+        assert_eq!("0v1: XZ: [2, 4]\n", run(&mut session, "query &mut XZ"));
+        assert_eq!("0v1: A: [2, 2, 4]\n", run(&mut session, "query &A"));
+
+        let output = run(&mut session, "alias-view Y = A[3]");
+        assert_eq!("Invalid elements for A[3] with 3 elements\n", output);
+    }
+
+    #[test]
+    fn dup_graph() {
+        let mut session = Session::default();
+        run(&mut session, "comp A 1, Links 2 entity");
+        run(&mut session, "spawn A 1");
+        run(&mut session, "spawn A 2, Links 0");
+        run(&mut session, "spawn A 3, Links 1 0");
+        run(&mut session, "spawn A 4, Links 2");
+
+        let output = run(&mut session, "dup-graph 2");
+        assert_eq!(
+            "Cloned 3 entities: 2v1 -> 4v1, 1v1 -> 5v1, 0v1 -> 6v1\n",
+            output
+        );
+        let output = run(&mut session, "query &A, &Links");
+        assert!(output.contains("4v1: A: [3], Links: [5v1, 6v1]"));
+        assert!(output.contains("5v1: A: [2], Links: [6v1, none]"));
+        assert!(output.contains("2v1: A: [3], Links: [1v1, 0v1]"));
+        assert!(output.contains("3v1: A: [4], Links: [2v1, none]"));
+        assert!(run(&mut session, "query &A, !Links").contains("6v1: A: [1]"));
+    }
+
+    #[test]
+    fn relationship_closure() {
+        let mut session = Session::default();
+        run(&mut session, "comp Next 2 entity, A 1");
+        run(&mut session, "spawn 4x Next");
+        run(&mut session, "spawn A 1");
+        run(&mut session, "set 0v1 Next 2");
+        run(&mut session, "set 1v1 Next 0");
+        run(&mut session, "set 2v1 Next 1 3");
+
+        assert_eq!(
+            "4 entities reachable from 0v1 through Next: 2v1, 1v1, 3v1, 0v1\n",
+            run(&mut session, "closure 0v1 Next")
+        );
+        assert_eq!(
+            "0 entities reachable from 3v1 through Next: \n",
+            run(&mut session, "closure 3 Next")
+        );
+        assert_eq!(
+            "Component A does not reference entities\n",
+            run(&mut session, "closure 4v1 A")
+        );
+    }
+
+    #[test]
+    fn fuse_components() {
+        let mut session = Session::default();
+        run(&mut session, "comp A 2, B 1, C 1, D 1 f32");
+        run(&mut session, "spawn A 1 2, B 3, C 9");
+        run(&mut session, "spawn A 4 5");
+        run(&mut session, "spawn B 6");
+
+        assert_eq!(
+            "Fused A and B into AB for 1 entities\n",
+            run(&mut session, "fuse A B -> AB")
+        );
+        assert_eq!(
+            "0v1: C: [9], AB: [1, 2, 3]\n",
+            run(&mut session, "query &AB, &C")
+        );
+        assert_eq!("1v1: A: [4, 5]\n", run(&mut session, "query &A"));
+
+        assert_eq!(
+            "Fused A and B into Filled for 2 entities\n",
+            run(&mut session, "fuse A B -> Filled fill")
+        );
+        let output = run(&mut session, "query &Filled");
+        assert!(output.contains("1v1: Filled: [4, 5, 0]"));
+        assert!(output.contains("2v1: Filled: [0, 0, 6]"));
+        assert_eq!("", run(&mut session, "query &A || &B"));
+
+        assert_eq!(
+            "Components C and D have different options\n",
+            run(&mut session, "fuse C D -> CD")
+        );
+    }
+
+    #[test]
+    fn element_limit() {
+        let mut session = Session {
+            elem_limit: Some(2),
+            ..Default::default()
+        };
+        run(&mut session, "comp A 5, B 2");
+        run(&mut session, "spawn A 1 2 3 4 5, B 6 7");
+
+        assert_eq!(
+            "0v1: A: [1, 2] … (+3 more), B: [6, 7]\n",
+            run(&mut session, "query &A, &B")
+        );
+        assert_eq!("0v1: A: [1, 2, 3, 4, 5]\n", run(&mut session, "get 0v1 A"));
+    }
+
+    #[test]
+    fn batch_rollback() {
+        let mut session = Session::default();
+        run(&mut session, "comp A 1, B 2");
+        run(&mut session, "spawn A 1");
+
+        run(&mut session, "begin");
+        run(&mut session, "spawn A 2, B 3 4");
+        assert_eq!("Batched command 2\n", run(&mut session, "set 0v1 A 5"));
+        run(&mut session, "set 0v1 B 6");
+        run(&mut session, "spawn B 7");
+        assert_eq!(
+            "Batch rolled back, command 3 failed: Entity 0v1 does not have B\n",
+            run(&mut session, "end")
+        );
+        assert_eq!("0v1: A: [1]\n", run(&mut session, "query &A"));
+        assert_eq!("", run(&mut session, "query &B"));
+
+        run(&mut session, "begin");
+        run(&mut session, "spawn A 2, B 3 4");
+        run(&mut session, "set 0v1 A 5");
+        let output = run(&mut session, "end");
+        assert!(output.ends_with("Batch of 2 commands applied\n"));
+        let output = run(&mut session, "query &A");
+        assert!(output.contains("0v1: A: [5]"));
+        assert!(output.contains("1v1: A: [2]"));
+        assert_eq!("1v1: B: [3, 4]\n", run(&mut session, "query &B"));
+        assert_eq!(
+            "No batch is open, start one with begin\n",
+            run(&mut session, "end")
+        );
+    }
+
+    #[test]
+    fn archetype_stats() {
+        let mut session = Session::default();
+        run(&mut session, "comp A 2, B 1");
+
+        let archetype = |output: String| {
+            let (_, archetype) = output.trim().rsplit_once("in archetype ").unwrap();
+            archetype.to_string()
+        };
+        let ab = archetype(run(&mut session, "spawn A 1 9, B 0"));
+        run(&mut session, "spawn A 4 9, B 0");
+        run(&mut session, "spawn A 7 9, B 0");
+        let a = archetype(run(&mut session, "spawn A 100"));
+
+        assert_eq!(
+            "A[0] over 3 entities: min 1, max 7, mean 4\n",
+            run(&mut session, &format!("archstats {} A", ab))
+        );
+        assert_eq!(
+            "A[0] over 1 entities: min 100, max 100, mean 100\n",
+            run(&mut session, &format!("archstats {} A", a))
+        );
+        assert_eq!(
+            format!("Archetype {} does not have B\n", a),
+            run(&mut session, &format!("archstats {} B", a))
+        );
+    }
+
+    #[test]
+    fn formatters() {
+        let mut session = Session::default();
+        run(&mut session, "comp Tint 2 be, Flags 3");
+        run(&mut session, "spawn Tint 16744192 255, Flags 0 2 1");
+
+        run(&mut session, "format Tint color");
+        run(&mut session, "format Flags bool-list");
+        assert_eq!(
+            "0v1: Tint: [#FF7F00, #0000FF], Flags: [false, true, true]\n",
+            run(&mut session, "query &Tint, &Flags")
+        );
+        run(&mut session, "format Tint hex");
+        assert_eq!(
+            "0v1: Tint: [0xff7f00, 0xff]\n",
+            run(&mut session, "query &Tint")
+        );
+        run(&mut session, "format Tint default");
+        assert_eq!(
+            "0v1: Tint: [16744192, 255]\n",
+            run(&mut session, "query &Tint")
+        );
+        assert_eq!(
+            "Unknown formatter: rgb\n",
+            run(&mut session, "format Tint rgb")
+        );
+    }
+
+    #[test]
+    fn entity_diff() {
+        let mut session = Session::default();
+        run(&mut session, "comp A 2, B 1, C 1, D 1, E 1");
+        run(&mut session, "spawn A 1 2, B 3, C 4, D 5");
+        run(&mut session, "spawn A 1 3, B 3, D 6, E 7");
+
+        assert_eq!(
+            "Only on 0v1: C\nOnly on 1v1: E\nDifferent: A [1, 2] != [1, 3], D [5] != [6]\n",
+            run(&mut session, "entdiff 0 1v1")
+        );
+        assert_eq!(
+            "Only on 0v1: \nOnly on 0v1: \nDifferent: \n",
+            run(&mut session, "entdiff 0 0")
+        );
+    }
+
+    #[test]
+    fn auto_registration() {
+        let mut session = Session::default();
+        assert_eq!(
+            "Component A does not exist\nEntity spawned with id: 0v1 in archetype 0\n",
+            run(&mut session, "spawn A 1 2")
+        );
+        assert!(!session.component_names.contains_key("A"));
+
+        session.autoreg = true;
+        let output = run(&mut session, "spawn A 1 2 3, B");
+        assert!(output.starts_with(
+            "Component A registered with 3 elements\nComponent B registered with 1 elements\n"
+        ));
+        assert!(run(&mut session, "list").contains("A (id: 0, size: 3)\nB (id: 1, size: 1)"));
+        assert_eq!(
+            "1v1: A: [1, 2, 3], B: [0]\n",
+            run(&mut session, "query &A, &B")
+        );
+    }
+
+    #[test]
+    fn snapshot_query() {
+        let mut session = Session::default();
+        run(&mut session, "comp A 1, B 1");
+        run(&mut session, "spawn A 1, B 2");
+        run(&mut session, "spawn A 3");
+        run(&mut session, "spawn B 4");
+
+        assert_eq!(
+            "Saved 2 entities into saveA\n",
+            run(&mut session, "snapshot-query &A into saveA")
+        );
+        run(&mut session, "set 1v1 A 9");
+        run(&mut session, "gc !A || !B");
+        assert_eq!("1v1: A: [9]\n", run(&mut session, "query &A"));
+
+        assert_eq!(
+            "Restored 2 entities from saveA (1 spawned)\n",
+            run(&mut session, "restore saveA")
+        );
+        let output = run(&mut session, "query &A");
+        assert!(output.contains("1v1: A: [3]"));
+        assert!(output.contains("0v2: A: [1]"));
+        assert_eq!("0v2: B: [2]\n", run(&mut session, "query &B"));
+        assert_eq!(
+            "Snapshot saveB does not exist\n",
+            run(&mut session, "restore saveB")
+        );
+    }
+
+    #[test]
+    fn bit_fields() {
+        let mut session = Session::default();
+        run(&mut session, "comp Flags 2 be, F 1 f32");
+        run(&mut session, "spawn Flags 9 1, F 1");
+        run(&mut session, "format Flags bits");
+
+        assert_eq!(
+            "0v1: Flags: [bits: 0,3,7, bits: 0]\n",
+            run(&mut session, "setbit 0v1 Flags 7")
+        );
+        assert_eq!(
+            "0v1: Flags: [bits: 3,7, bits: 0]\n",
+            run(&mut session, "clearbit 0v1 Flags 0")
+        );
+        run(&mut session, "clearbit 0v1 Flags 5");
+        run(&mut session, "setbit 0v1 Flags 63");
+        assert_eq!(
+            "0v1: Flags: [bits: 3,7,63, bits: 0]\n",
+            run(&mut session, "query &Flags")
+        );
+        assert_eq!("Invalid bit: 64\n", run(&mut session, "setbit 0 Flags 64"));
+        assert_eq!(
+            "Component F does not hold integers\n",
+            run(&mut session, "setbit 0 F 1")
+        );
+    }
+
+    #[test]
+    fn hex_display() {
+        let mut session = Session::default();
+        run(&mut session, "comp A 1, Parent 1 entity");
+        run(&mut session, "spawn 30x A 1");
+
+        assert_eq!(
+            "Printing ids in hex\n",
+            run(&mut session, "display-base hex")
+        );
+        assert!(run(&mut session, "spawn Parent 0x1dv1")
+            .starts_with("Entity spawned with id: 0x1ev1 in archetype"));
+        assert_eq!(
+            "0x1ev1: Parent: [0x1dv1]\n",
+            run(&mut session, "query &Parent")
+        );
+        assert!(run(&mut session, "list").contains("Parent (id: 0x1, size: 1)"));
+
+        run(&mut session, "display-base dec");
+        assert_eq!("30v1: Parent: [29v1]\n", run(&mut session, "query &Parent"));
+    }
+
+    #[test]
+    fn spawn_order() {
+        let mut session = Session::default();
+        run(&mut session, "comp A 1, B 1");
+        run(&mut session, "spawn A 1");
+        run(&mut session, "spawn B 1");
+        run(&mut session, "spawn A 1");
+        run(&mut session, "gc A");
+        run(&mut session, "spawn 3x A 2");
+
+        let output = run(&mut session, "query &A, order<3");
+        assert_eq!("0v1: A: [1]\n2v1: A: [1]\n", output);
+        let output = run(&mut session, "query &A, order>=3, order<=4");
+        assert_eq!("1v2: A: [2]\n3v1: A: [2]\n", output);
+        assert_eq!(
+            "Invalid order comparison: order=3\n",
+            run(&mut session, "query &A, order=3")
+        );
+        assert!(!session.spawn_order.contains_key(&Entity::from_raw(1)));
+    }
+
+    #[test]
+    fn assertions() {
+        let mut session = Session::default();
+        run(&mut session, "comp A 1, B 1");
+        run(&mut session, "spawn 3x A 1");
+        run(&mut session, "spawn A 1, B 2");
+
+        assert_eq!(
+            "Assertion passed: 3 entities match\n",
+            run(&mut session, "assert-count &A, !B == 3")
+        );
+        run(&mut session, "assert-nonempty B");
+        assert_eq!(None, session.exit_code());
+
+        assert_eq!(
+            "Assertion failed: expected == 0 entities but 1 match\n",
+            run(&mut session, "assert-empty B")
+        );
+        assert_eq!(None, session.exit_code());
+
+        let mut session = Session {
+            script: true,
+            ..Default::default()
+        };
+        run(&mut session, "comp A 1");
+        run(&mut session, "assert-empty A");
+        assert_eq!(None, session.exit_code());
+        run(&mut session, "assert-count A >= 1");
+        assert_eq!(Some(1), session.exit_code());
+    }
+
+    #[test]
+    fn recall_history() {
+        let mut session = Session {
+            history_len: 2,
+            ..Default::default()
+        };
+        run(&mut session, "comp A 1");
+        run(&mut session, "spawn A 1");
+        let first = run(&mut session, "query &mut A");
+        let second = run(&mut session, "query &mut A");
+        let third = run(&mut session, "query &A");
+        assert_eq!("0v1: A: [2]\n", first);
+
+        assert_eq!(third, run(&mut session, "recall"));
+        assert_eq!(second, run(&mut session, "recall 2"));
+        assert_eq!(
+            "Enter a number between 1 and 2 to recall a query result\n",
+            run(&mut session, "recall 3")
+        );
+    }
+
+    #[test]
+    fn archetype_edges() {
+        let mut session = Session::default();
+        run(&mut session, "comp A 1, Links 1 entity");
+        run(&mut session, "spawn A 1, Links 0");
+        run(&mut session, "spawn A 2, Links 0");
+        run(&mut session, "dup-graph 1");
+
+        let output = run(&mut session, "edges");
+        assert_eq!(
+            "Archetype 0 -> 1: +A, +Links\n\
+             Archetype 0 -> 2: +A\n\
+             Archetype 2 -> 1: +Links\n",
+            output
+        );
+    }
+
+    #[test]
+    fn spawn_outcome() {
+        let mut session = Session::default();
+        run(&mut session, "comp A 2, B 1 be, C 1 f32");
+        let (a, b, c) = (
+            session.component_names["A"],
+            session.component_names["B"],
+            session.component_names["C"],
+        );
+
+        let outcomes = session
+            .spawn_entity("2x A 1, B 7, C 0.5", &mut Vec::new())
+            .unwrap();
+        let components = vec![
+            (a, vec![1, 0]),
+            (b, vec![7]),
+            (c, vec![0.5f32.to_bits() as u64]),
+        ];
+        assert_eq!(
+            vec![
+                SpawnOutcome {
+                    entity: Entity::from_raw(0),
+                    components: components.clone(),
+                },
+                SpawnOutcome {
+                    entity: Entity::from_raw(1),
+                    components,
+                },
+            ],
+            outcomes
+        );
+        assert!(
+            session.spawn_entity("A 1, B x", &mut Vec::new()).unwrap()[0]
+                .components
+                .contains(&(b, vec![0]))
+        );
+    }
+
+    #[test]
+    fn query_round_trip() {
+        let mut session = Session::default();
+        run(&mut session, "comp A, B, C, D, E, F");
+
+        let access = |session: &mut Session, query: &str| {
+            let mut builder = QueryBuilder::<FilteredEntityMut>::new(&mut session.world);
+            builder.parse(query, &session.component_names).unwrap();
+            builder.build().component_access().clone()
+        };
+
+        for (query, canonical) in [
+            ("&A, &mut B, C, ?&D, !E", "&A, &mut B, ?&D, C, !E"),
+            ("?&mut C, &B", "&B, ?&mut C"),
+            ("A || !B, &C", "&C, A || !B"),
+            ("D, E || F", "D, E || F"),
+            ("(A, !B) || C", "(A, !B) || C"),
+        ] {
+            let rendered = run(&mut session, &format!("parse {}", query));
+            assert_eq!(format!("{}\n", canonical), rendered);
+            assert_eq!(access(&mut session, query), access(&mut session, canonical));
+        }
+    }
 }
@@ -34,6 +34,7 @@ crates=(
     bevy_pbr
     bevy_gltf
     bevy_scene
+    bevy_remote
     bevy_sprite
     bevy_gizmos
     bevy_text